@@ -1,23 +1,55 @@
 use axum::{
     body::Body,
     extract::State,
-    http::{Request, StatusCode},
+    http::{HeaderMap, Method, Request, StatusCode},
     middleware::Next,
     response::Response,
     Json,
 };
+use subtle::ConstantTimeEq;
 
-use crate::{auth::verify_jwt, config::get_env_vars, models::AppStore};
+use crate::{
+    auth::verify_jwt,
+    config::get_env_vars,
+    errors::AppError,
+    models::{AppStore, staff::StaffRole},
+};
 
 // This extension gets attached to the request so handlers can read the school_id
 #[derive(Clone)]
 pub struct AuthSchool {
     pub school_id: uuid::Uuid,
     pub username: String,
+    pub role: StaffRole,
+}
+
+impl AuthSchool {
+    /// Fails with Forbidden unless the caller has one of `roles`.
+    pub fn require_role(&self, roles: &[StaffRole]) -> Result<(), AppError> {
+        if roles.contains(&self.role) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!("{:?} accounts cannot do this", self.role)))
+        }
+    }
+}
+
+/// Platform operator routes sit outside the school auth layer; callers send
+/// `Authorization: Bearer <PLATFORM_API_KEY>` instead.
+pub fn require_platform_key(headers: &HeaderMap) -> Result<(), AppError> {
+    let expected: Option<String> = get_env_vars("PLATFORM_API_KEY".to_string()).ok();
+    let provided = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match (expected, provided) {
+        (Some(expected), Some(provided)) if bool::from(provided.as_bytes().ct_eq(expected.as_bytes())) => Ok(()),
+        _ => Err(AppError::Forbidden("Platform access only".to_string())),
+    }
 }
 
 pub async fn auth_middleware(
-    State(_store): State<AppStore>,
+    State(store): State<AppStore>,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, (StatusCode, Json<String>)> {
@@ -56,7 +88,14 @@ pub async fn auth_middleware(
     req.extensions_mut().insert(AuthSchool {
         school_id,
         username: claims.username,
+        role: claims.role,
     });
 
-    Ok(next.run(req).await)
+    let writes = !matches!(*req.method(), Method::GET | Method::HEAD);
+    let response = next.run(req).await;
+    // whatever the school just changed may show on its dashboard
+    if writes && response.status().is_success() {
+        store.invalidate_dashboard(school_id).await;
+    }
+    Ok(response)
 }
\ No newline at end of file
@@ -5,16 +5,18 @@ use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode}
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::errors::AppError;
+use crate::{errors::AppError, models::staff::StaffRole};
 
 #[derive(Serialize, Deserialize)]
 pub struct Claims {
     pub school_id: String,
     pub username: String,
+    #[serde(default)] // tokens issued before staff logins existed belong to owners
+    pub role: StaffRole,
     pub exp: usize, // expiry timestamp
 }
 
-pub fn create_jwt(school_id: Uuid, username: &str, secret: &str) -> Result<String, AppError> {
+pub fn create_jwt(school_id: Uuid, username: &str, role: StaffRole, secret: &str) -> Result<String, AppError> {
     let expiry = chrono::Utc::now()
         .checked_add_signed(chrono::Duration::hours(24))
         .expect("valid timestamp")
@@ -23,6 +25,7 @@ pub fn create_jwt(school_id: Uuid, username: &str, secret: &str) -> Result<Strin
     let claims = Claims {
         school_id: school_id.to_string(),
         username: username.to_string(),
+        role,
         exp: expiry,
     };
 
@@ -0,0 +1,154 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+
+use crate::models::{
+    academics::Term,
+    timetable::{Teacher, TimetableEntry},
+};
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Folds a content line longer than 75 octets (RFC 5545 section 3.1):
+/// each continuation starts with a space, and no UTF-8 character is split.
+fn fold_line(line: &str) -> String {
+    const MAX_OCTETS: usize = 75;
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_OCTETS {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+fn byday(entry: &TimetableEntry) -> &'static str {
+    match entry.slot.weekday.to_chrono() {
+        chrono::Weekday::Mon => "MO",
+        chrono::Weekday::Tue => "TU",
+        chrono::Weekday::Wed => "WE",
+        chrono::Weekday::Thu => "TH",
+        chrono::Weekday::Fri => "FR",
+        chrono::Weekday::Sat => "SA",
+        chrono::Weekday::Sun => "SU",
+    }
+}
+
+/// First date on or after `from` that falls on the slot's weekday.
+fn first_occurrence(from: NaiveDate, entry: &TimetableEntry) -> NaiveDate {
+    let target = entry.slot.weekday.to_chrono().num_days_from_monday() as i64;
+    let current = from.weekday().num_days_from_monday() as i64;
+    from + Duration::days((target - current).rem_euclid(7))
+}
+
+/// Weekly recurring events for a teacher's timetable. Times are floating
+/// (no time zone) so calendars show them in the school's local time. When a
+/// term is given the events start and stop with it, otherwise they repeat
+/// from this week onwards.
+pub fn render_teacher_calendar(
+    school_name: &str,
+    teacher: &Teacher,
+    entries: &[TimetableEntry],
+    term: Option<&Term>,
+) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let from = term
+        .map(|t| t.starts_on)
+        .unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//sch_mgt_sys//Timetable//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!(
+            "X-WR-CALNAME:{}",
+            escape_text(&format!("{} - {} {}", school_name, teacher.first_name, teacher.last_name))
+        ),
+    ];
+
+    for entry in entries {
+        let date = first_occurrence(from, entry);
+        let mut rule = format!("RRULE:FREQ=WEEKLY;BYDAY={}", byday(entry));
+        if let Some(term) = term {
+            rule.push_str(&format!(";UNTIL={}T235959", term.ends_on.format("%Y%m%d")));
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@sch_mgt_sys", entry.slot.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!(
+            "DTSTART:{}T{}",
+            date.format("%Y%m%d"),
+            entry.slot.starts_at.format("%H%M%S")
+        ));
+        lines.push(format!(
+            "DTEND:{}T{}",
+            date.format("%Y%m%d"),
+            entry.slot.ends_at.format("%H%M%S")
+        ));
+        lines.push(rule);
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("{} - {}", entry.subject_name, entry.slot.class_name))
+        ));
+        lines.push(format!("LOCATION:{}", escape_text(&entry.slot.room)));
+        lines.push(format!("DESCRIPTION:Period {}", entry.slot.period));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    // iCalendar requires CRLF line endings
+    let lines: Vec<String> = lines.iter().map(|line| fold_line(line)).collect();
+    let mut calendar = lines.join("\r\n");
+    calendar.push_str("\r\n");
+    calendar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_every_kind_of_line_break() {
+        assert_eq!(escape_text("Lab 2\r\nBlock B\rNorth\nWing"), "Lab 2\\nBlock B\\nNorth\\nWing");
+        assert_eq!(escape_text("Maths; Further, 1\\2"), "Maths\\; Further\\, 1\\\\2");
+    }
+
+    #[test]
+    fn leaves_short_lines_alone() {
+        let line = format!("SUMMARY:{}", "a".repeat(67));
+        assert_eq!(line.len(), 75);
+        assert_eq!(fold_line(&line), line);
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        let line = format!("DESCRIPTION:{}", "x".repeat(200));
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert!(parts.iter().all(|p| p.len() <= 75));
+        assert!(parts[1..].iter().all(|p| p.starts_with(' ')));
+        assert_eq!(parts.concat().replace(' ', ""), line);
+    }
+
+    #[test]
+    fn never_splits_a_multibyte_character() {
+        let line = format!("LOCATION:{}", "é".repeat(60)); // two octets each
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts[0].len(), 75); // 9 + 33 * 2
+        assert!(parts.iter().all(|p| p.len() <= 75));
+        assert_eq!(parts.iter().map(|p| p.trim_start_matches(' ')).collect::<String>(), line);
+    }
+}
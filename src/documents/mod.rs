@@ -0,0 +1,168 @@
+pub mod ical;
+pub mod payment_page;
+pub mod receipt;
+pub mod report_card;
+pub mod reports;
+pub mod student_export;
+
+use std::io::{Cursor, Write};
+
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+    Point, Rect,
+};
+use qrcode::{Color, QrCode};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::errors::AppError;
+
+const PAGE_WIDTH: f32 = 210.0; // A4, in mm
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+
+/// Small top-to-bottom writer over printpdf: keeps a cursor and starts a new
+/// page whenever the next line would not fit.
+pub struct PdfBuilder {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl PdfBuilder {
+    pub fn new(title: &str) -> Result<Self, AppError> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let regular = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            layer,
+            regular,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    fn ensure_space(&mut self, needed: f32) {
+        if self.y - needed < MARGIN {
+            let (page, layer) = self
+                .doc
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    pub fn heading(&mut self, text: &str, size: f32) {
+        let height = size * 0.5;
+        self.ensure_space(height);
+        self.y -= height;
+        self.layer.use_text(text, size, Mm(MARGIN), Mm(self.y), &self.bold);
+    }
+
+    pub fn text(&mut self, text: &str) {
+        self.columns(&[(0.0, text)], false);
+    }
+
+    /// Writes one line of cells; each x offset is in mm from the left margin.
+    pub fn columns(&mut self, cells: &[(f32, &str)], bold: bool) {
+        self.ensure_space(6.0);
+        self.y -= 6.0;
+        let font = if bold { &self.bold } else { &self.regular };
+        for (x, text) in cells {
+            self.layer.use_text(*text, 10.0, Mm(MARGIN + x), Mm(self.y), font);
+        }
+    }
+
+    pub fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    pub fn rule(&mut self) {
+        self.ensure_space(3.0);
+        self.y -= 2.0;
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+        self.y -= 1.0;
+    }
+
+    /// Draws `data` as a QR code `size` mm square, `x` mm from the left
+    /// margin, with its top edge at the cursor.
+    pub fn qr_code(&mut self, data: &str, x: f32, size: f32) -> Result<(), AppError> {
+        let code = QrCode::new(data).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let width = code.width();
+        let module = size / width as f32;
+
+        self.ensure_space(size);
+        let top = self.y;
+        for (index, color) in code.to_colors().into_iter().enumerate() {
+            if color == Color::Dark {
+                let left = MARGIN + x + (index % width) as f32 * module;
+                let bottom = top - (index / width + 1) as f32 * module;
+                self.layer
+                    .add_rect(Rect::new(Mm(left), Mm(bottom), Mm(left + module), Mm(bottom + module)));
+            }
+        }
+        self.y -= size;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, AppError> {
+        self.doc
+            .save_to_bytes()
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+pub fn to_csv(headers: &[String], rows: &[Vec<String>]) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(headers)
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    for row in rows {
+        writer
+            .write_record(row)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InternalServerError(e.to_string()))
+}
+
+/// Packs (file name, contents) pairs into an in-memory ZIP archive.
+pub fn zip_files(files: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>, AppError> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in files {
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        writer
+            .write_all(&contents)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    }
+    let cursor = writer
+        .finish()
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    Ok(cursor.into_inner())
+}
@@ -0,0 +1,116 @@
+use crate::models::{
+    ledger::{Payment, TransactionStatus},
+    money::Money,
+    payment_links::PaymentPage,
+};
+
+use super::escape_html;
+
+fn layout(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: Helvetica, Arial, sans-serif; margin: 0 auto; max-width: 28em; padding: 2em 1em; color: #222; }}
+table {{ width: 100%; border-collapse: collapse; margin: 1em 0; }}
+td {{ padding: 6px 0; border-bottom: 1px solid #ddd; }}
+td:last-child {{ text-align: right; }}
+button, .button {{ display: inline-block; width: 100%; padding: 12px; font-size: 1em; border: 0; border-radius: 4px;
+  background: #1a7f37; color: #fff; text-align: center; text-decoration: none; cursor: pointer; }}
+.note {{ color: #666; font-size: 0.9em; }}
+</style>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        body = body,
+    )
+}
+
+fn details(page: &PaymentPage) -> String {
+    format!(
+        "<table>\n<tr><td>Student</td><td>{student}</td></tr>\n\
+         <tr><td>Admission no.</td><td>{admission}</td></tr>\n\
+         <tr><td>Term</td><td>{term}</td></tr>\n\
+         <tr><td>Balance</td><td>{balance}</td></tr>\n</table>",
+        student = escape_html(&page.student_name),
+        admission = escape_html(&page.admission_number),
+        term = escape_html(&page.term_code),
+        balance = page.balance,
+    )
+}
+
+/// The page a payment link opens: the balance, and a button that starts a
+/// checkout by posting back to `action`.
+pub fn render_page(page: &PaymentPage, action: &str) -> String {
+    let pay = if page.amount_due.amount_minor > 0 {
+        format!(
+            "<form method=\"post\" action=\"{action}\">\n<button type=\"submit\">Pay {amount}</button>\n</form>\n\
+             <p class=\"note\">This link expires on {expires}.</p>",
+            action = escape_html(action),
+            amount = page.amount_due,
+            expires = page.expires_at.format("%-d %B %Y at %H:%M UTC"),
+        )
+    } else {
+        "<p>Nothing is owed on this invoice. Thank you.</p>".to_string()
+    };
+
+    let body = format!(
+        "<h1>{school}</h1>\n<h2>School fees</h2>\n{details}\n{pay}",
+        school = escape_html(&page.school_name),
+        details = details(page),
+    );
+    layout(&format!("Pay {}", page.school_name), &body)
+}
+
+/// Where the payer lands after checkout, once we've asked the gateway how it went.
+pub fn render_outcome(page: &PaymentPage, payment: &Payment, page_url: &str) -> String {
+    let amount = Money::from_minor(payment.amount_kobo, payment.currency);
+    let outcome = match payment.status {
+        TransactionStatus::Successful | TransactionStatus::Refunded => format!(
+            "<h2>Payment received</h2>\n<p>We received {amount}.{receipt}</p>",
+            receipt = match &payment.receipt_number {
+                Some(number) => format!(" Your receipt number is <strong>{}</strong>.", escape_html(number)),
+                None => String::new(),
+            },
+        ),
+        TransactionStatus::Pending => format!(
+            "<h2>Payment processing</h2>\n<p>We haven't had confirmation of your payment of {amount} yet. \
+             Refresh this page in a few minutes; you won't be charged twice.</p>"
+        ),
+        TransactionStatus::Failed => format!(
+            "<h2>Payment not completed</h2>\n<p>Your payment of {amount} did not go through, \
+             and nothing was taken.</p>"
+        ),
+    };
+
+    let again = if page.amount_due.amount_minor > 0 && payment.status != TransactionStatus::Pending {
+        format!(
+            "<p><a class=\"button\" href=\"{}\">Pay {}</a></p>",
+            escape_html(page_url),
+            page.amount_due
+        )
+    } else {
+        String::new()
+    };
+
+    let body = format!(
+        "<h1>{school}</h1>\n{outcome}\n{details}\n{again}",
+        school = escape_html(&page.school_name),
+        details = details(page),
+    );
+    layout(&format!("Payment to {}", page.school_name), &body)
+}
+
+/// A page with nothing to pay: an expired link, a bad token, a gateway error.
+pub fn render_message(title: &str, message: &str) -> String {
+    let body = format!("<h1>{}</h1>\n<p>{}</p>", escape_html(title), escape_html(message));
+    layout(title, &body)
+}
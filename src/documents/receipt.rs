@@ -0,0 +1,195 @@
+use std::env;
+
+use crate::{
+    errors::AppError,
+    models::{
+        money::{Currency, Money},
+        receipts::ReceiptDocument,
+    },
+};
+
+use super::PdfBuilder;
+
+const ONES: [&str; 20] = [
+    "", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(u64, &str); 3] = [(1_000_000_000, "billion"), (1_000_000, "million"), (1_000, "thousand")];
+
+// 0..=999, British style: "one hundred and five"
+fn hundreds_in_words(n: u64) -> String {
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    let rest = n % 100;
+    if rest > 0 {
+        if n >= 100 {
+            parts.push("and".to_string());
+        }
+        if rest < 20 {
+            parts.push(ONES[rest as usize].to_string());
+        } else if rest.is_multiple_of(10) {
+            parts.push(TENS[(rest / 10) as usize].to_string());
+        } else {
+            parts.push(format!("{}-{}", TENS[(rest / 10) as usize], ONES[(rest % 10) as usize]));
+        }
+    }
+    parts.join(" ")
+}
+
+fn number_in_words(mut n: u64) -> String {
+    if n == 0 {
+        return "zero".to_string();
+    }
+    let mut parts = Vec::new();
+    for (scale, name) in SCALES {
+        if n >= scale {
+            parts.push(format!("{} {}", number_in_words(n / scale), name));
+            n %= scale;
+        }
+    }
+    if n > 0 {
+        if !parts.is_empty() && n < 100 {
+            parts.push("and".to_string());
+        }
+        parts.push(hundreds_in_words(n));
+    }
+    parts.join(" ")
+}
+
+/// e.g. 5_000_050 in NGN -> "Fifty thousand naira and fifty kobo only"
+pub fn amount_in_words(amount_minor: u64, currency: Currency) -> String {
+    let (major_name, minor_name) = currency.unit_names();
+    let major = amount_minor / 100;
+    let minor = amount_minor % 100;
+    let mut words = format!("{} {}", number_in_words(major), major_name);
+    if minor > 0 {
+        words.push_str(&format!(" and {} {}", number_in_words(minor), minor_name));
+    }
+    words.push_str(" only");
+
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+/// The public link encoded in the receipt's QR code.
+pub fn check_url(doc: &ReceiptDocument) -> String {
+    let base = env::var("PUBLIC_BASE_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+    format!("{}/receipts/{}/check", base.trim_end_matches('/'), doc.receipt.id)
+}
+
+pub fn file_name(doc: &ReceiptDocument) -> String {
+    format!("{}-{}.pdf", doc.receipt.number, doc.student.last_name).replace(['/', '\\', ' '], "_")
+}
+
+pub fn render_pdf(doc: &ReceiptDocument) -> Result<Vec<u8>, AppError> {
+    let mut pdf = PdfBuilder::new(&format!("Receipt {}", doc.receipt.number))?;
+    let branding = &doc.school.branding;
+
+    pdf.heading(&doc.school.name, 18.0);
+    for line in [&branding.motto, &branding.address].into_iter().flatten() {
+        pdf.text(line);
+    }
+    let contacts: Vec<&str> = [&branding.phone, &branding.email]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    if !contacts.is_empty() {
+        pdf.text(&contacts.join("   "));
+    }
+    pdf.gap(3.0);
+    pdf.heading(&format!("Payment Receipt {}", doc.receipt.number), 13.0);
+    pdf.gap(3.0);
+
+    let paid_at = doc.payment.paid_at.unwrap_or(doc.receipt.issued_at);
+    pdf.text(&format!("Date: {}", paid_at.format("%d %b %Y")));
+    pdf.text(&format!(
+        "Student: {} {}",
+        doc.student.first_name, doc.student.last_name
+    ));
+    pdf.text(&format!("Admission number: {}", doc.student.admission_number));
+    pdf.text(&format!("Class: {}", doc.student.class_name.as_deref().unwrap_or("")));
+    pdf.text(&format!("Term: {}", doc.invoice.term_code));
+    pdf.text(&format!(
+        "Payment method: {}    Reference: {}",
+        doc.payment.method.label(),
+        doc.payment.reference
+    ));
+    pdf.gap(3.0);
+
+    pdf.columns(&[(0.0, "Item"), (130.0, "Amount")], true);
+    let currency = doc.invoice.currency;
+    let money = |amount_minor: i64| Money::new(amount_minor, currency).to_string();
+    pdf.rule();
+    for item in &doc.invoice.line_items {
+        let amount = money(item.amount_kobo as i64);
+        pdf.columns(&[(0.0, item.description.as_str()), (130.0, amount.as_str())], false);
+    }
+    for discount in &doc.invoice.discounts {
+        let label = format!("Less: {}", discount.name);
+        let amount = money(-(discount.amount_kobo as i64));
+        pdf.columns(&[(0.0, label.as_str()), (130.0, amount.as_str())], false);
+    }
+    pdf.rule();
+    let paid = money(doc.receipt.amount_kobo as i64);
+    pdf.columns(&[(0.0, "Amount paid"), (130.0, paid.as_str())], true);
+    for allocation in &doc.payment.installment_allocations {
+        let amount = money(allocation.amount_kobo as i64);
+        pdf.columns(&[(5.0, allocation.label.as_str()), (130.0, amount.as_str())], false);
+    }
+    let balance = money(doc.receipt.balance_after_kobo);
+    pdf.columns(&[(0.0, "Balance remaining"), (130.0, balance.as_str())], true);
+    pdf.gap(2.0);
+    pdf.text(&format!("Amount in words: {}", amount_in_words(doc.receipt.amount_kobo, currency)));
+    pdf.gap(6.0);
+
+    let url = check_url(doc);
+    pdf.qr_code(&url, 0.0, 35.0)?;
+    pdf.text("Scan the code, or visit the link below, to confirm this receipt was issued by the school.");
+    pdf.text(&url);
+    pdf.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_major_and_minor_units() {
+        assert_eq!(amount_in_words(5_000_050, Currency::Ngn), "Fifty thousand naira and fifty kobo only");
+        assert_eq!(amount_in_words(250, Currency::Ghs), "Two cedis and fifty pesewas only");
+        assert_eq!(amount_in_words(1_999, Currency::Usd), "Nineteen dollars and ninety-nine cents only");
+    }
+
+    #[test]
+    fn leaves_out_a_zero_minor_part_but_not_a_zero_major_part() {
+        assert_eq!(amount_in_words(100, Currency::Ngn), "One naira only");
+        assert_eq!(amount_in_words(0, Currency::Ngn), "Zero naira only");
+        assert_eq!(amount_in_words(7, Currency::Kes), "Zero shillings and seven cents only");
+    }
+
+    #[test]
+    fn joins_hundreds_and_tens_with_and() {
+        assert_eq!(amount_in_words(100_500, Currency::Ngn), "One thousand and five naira only");
+        assert_eq!(amount_in_words(11_000_000, Currency::Zar), "One hundred and ten thousand rand only");
+        assert_eq!(
+            amount_in_words(12_345_678_901, Currency::Ngn),
+            "One hundred and twenty-three million four hundred and fifty-six thousand \
+             seven hundred and eighty-nine naira and one kobo only"
+        );
+    }
+
+    #[test]
+    fn handles_round_millions_and_billions() {
+        assert_eq!(amount_in_words(100_000_000, Currency::Ngn), "One million naira only");
+        assert_eq!(amount_in_words(200_000_000_000, Currency::Ngn), "Two billion naira only");
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{errors::AppError, models::report_cards::ReportCard};
+
+use super::{PdfBuilder, escape_html};
+
+fn ordinal(position: usize) -> String {
+    let suffix = match (position % 10, position % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", position, suffix)
+}
+
+fn student_name(card: &ReportCard) -> String {
+    format!("{} {}", card.student.first_name, card.student.last_name)
+}
+
+/// Unique within a class, as names need not be and ZIP entries must be. Safe
+/// to put in a Content-Disposition header.
+pub fn file_name(card: &ReportCard) -> String {
+    format!(
+        "{}-{}-{}-{}.pdf",
+        card.student.last_name, card.student.first_name, card.student.admission_number, card.term.code
+    )
+    .replace(['/', '\\', ' '], "_")
+    .replace('"', "")
+}
+
+pub fn render_html(card: &ReportCard) -> String {
+    let components: Vec<String> = card
+        .subjects
+        .first()
+        .map(|s| s.scores.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let header_cells: String = components
+        .iter()
+        .map(|c| format!("<th>{}</th>", escape_html(c)))
+        .collect();
+
+    let rows: String = card
+        .subjects
+        .iter()
+        .map(|subject| {
+            let score_cells: String = components
+                .iter()
+                .map(|c| {
+                    let score = subject.scores.get(c).map(|s| s.to_string()).unwrap_or_default();
+                    format!("<td>{}</td>", score)
+                })
+                .collect();
+            format!(
+                "<tr><td>{}</td>{}<td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&subject.subject_name),
+                score_cells,
+                subject.total,
+                escape_html(subject.grade.as_deref().unwrap_or("-")),
+                ordinal(subject.position),
+                escape_html(subject.remark.as_deref().unwrap_or("")),
+            )
+        })
+        .collect();
+
+    let attendance = match &card.attendance {
+        Some(a) => format!(
+            "<p><strong>Attendance:</strong> present {} of {} days</p>",
+            a.days_present, a.days_opened
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Report Card - {student}</title>
+<style>
+body {{ font-family: Helvetica, Arial, sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border: 1px solid #999; padding: 4px 8px; text-align: left; }}
+</style>
+</head>
+<body>
+<h1>{school}</h1>
+<h2>Report Card - {term}</h2>
+<p><strong>Student:</strong> {student}<br>
+<strong>Admission number:</strong> {admission}<br>
+<strong>Class:</strong> {class}<br>
+<strong>Department:</strong> {department}</p>
+<table>
+<tr><th>Subject</th>{header_cells}<th>Total</th><th>Grade</th><th>Position</th><th>Remark</th></tr>
+{rows}
+</table>
+<p><strong>Total:</strong> {total} &nbsp; <strong>Average:</strong> {average} &nbsp;
+<strong>Position:</strong> {position} of {class_size}</p>
+{attendance}
+<p><strong>Class teacher's remark:</strong> {teacher}</p>
+<p><strong>Principal's remark:</strong> {principal}</p>
+</body>
+</html>
+"#,
+        school = escape_html(&card.school_name),
+        term = escape_html(&card.term.name),
+        student = escape_html(&student_name(card)),
+        admission = escape_html(&card.student.admission_number),
+        class = escape_html(card.student.class_name.as_deref().unwrap_or("")),
+        department = escape_html(&card.student.department),
+        total = card.total,
+        average = card.average,
+        position = ordinal(card.position),
+        class_size = card.class_size,
+        teacher = escape_html(card.class_teacher_remark.as_deref().unwrap_or("")),
+        principal = escape_html(card.principal_remark.as_deref().unwrap_or("")),
+    )
+}
+
+pub fn render_pdf(card: &ReportCard) -> Result<Vec<u8>, AppError> {
+    let mut pdf = PdfBuilder::new(&format!("Report Card - {}", student_name(card)))?;
+
+    pdf.heading(&card.school_name, 18.0);
+    pdf.heading(&format!("Report Card - {}", card.term.name), 13.0);
+    pdf.gap(3.0);
+    pdf.text(&format!("Student: {}", student_name(card)));
+    pdf.text(&format!("Admission number: {}", card.student.admission_number));
+    pdf.text(&format!("Class: {}", card.student.class_name.as_deref().unwrap_or("")));
+    pdf.text(&format!("Department: {}", card.student.department));
+    pdf.gap(3.0);
+
+    pdf.columns(
+        &[(0.0, "Subject"), (70.0, "Total"), (90.0, "Grade"), (110.0, "Position"), (135.0, "Remark")],
+        true,
+    );
+    pdf.rule();
+    for subject in &card.subjects {
+        let total = subject.total.to_string();
+        let position = ordinal(subject.position);
+        pdf.columns(
+            &[
+                (0.0, subject.subject_name.as_str()),
+                (70.0, total.as_str()),
+                (90.0, subject.grade.as_deref().unwrap_or("-")),
+                (110.0, position.as_str()),
+                (135.0, subject.remark.as_deref().unwrap_or("")),
+            ],
+            false,
+        );
+        let breakdown: Vec<String> = subject
+            .scores
+            .iter()
+            .map(|(component, score)| format!("{}: {}", component, score))
+            .collect();
+        if !breakdown.is_empty() {
+            pdf.columns(&[(5.0, breakdown.join("   ").as_str())], false);
+        }
+    }
+    pdf.rule();
+
+    pdf.text(&format!(
+        "Total: {}    Average: {}    Position: {} of {}",
+        card.total,
+        card.average,
+        ordinal(card.position),
+        card.class_size
+    ));
+    if let Some(attendance) = &card.attendance {
+        pdf.text(&format!(
+            "Attendance: present {} of {} days",
+            attendance.days_present, attendance.days_opened
+        ));
+    }
+    pdf.gap(4.0);
+    pdf.text(&format!(
+        "Class teacher's remark: {}",
+        card.class_teacher_remark.as_deref().unwrap_or("")
+    ));
+    pdf.text(&format!(
+        "Principal's remark: {}",
+        card.principal_remark.as_deref().unwrap_or("")
+    ));
+
+    pdf.finish()
+}
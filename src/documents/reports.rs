@@ -0,0 +1,245 @@
+use crate::{
+    errors::AppError,
+    models::reports::{AgingReport, CollectionsReport, DebtorsReport, OutstandingReport},
+};
+
+use super::{PdfBuilder, to_csv};
+
+const TABLE_WIDTH: f32 = 180.0; // A4 less margins, in mm
+
+/// A report flattened to rows, ready for either export format. Amounts are
+/// plain decimals in the school's currency, which the headers name.
+pub struct ReportTable {
+    pub title: String,
+    pub notes: Vec<String>, // printed above the table in PDFs only
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub widths: Vec<f32>, // relative column widths for PDFs
+}
+
+// e.g. 5_000_050 -> "50000.50"
+fn amount(minor: i64) -> String {
+    let sign = if minor < 0 { "-" } else { "" };
+    let minor_abs = minor.unsigned_abs();
+    format!("{}{}.{:02}", sign, minor_abs / 100, minor_abs % 100)
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+pub fn collections_table(report: &CollectionsReport) -> ReportTable {
+    let currency = report.currency;
+    let mut rows: Vec<Vec<String>> = report
+        .rows
+        .iter()
+        .map(|r| {
+            vec![
+                r.period.clone(),
+                r.method.label().to_string(),
+                r.payments.to_string(),
+                amount(r.collected_kobo as i64),
+                amount(r.refunded_kobo as i64),
+                amount(r.net_kobo),
+            ]
+        })
+        .collect();
+    rows.push(vec![
+        "Total".to_string(),
+        String::new(),
+        report.rows.iter().map(|r| r.payments).sum::<usize>().to_string(),
+        amount(report.collected_kobo as i64),
+        amount(report.refunded_kobo as i64),
+        amount(report.net_kobo),
+    ]);
+
+    let period = match (report.from, report.to) {
+        (Some(from), Some(to)) => format!("From {} to {}", from, to),
+        (Some(from), None) => format!("From {}", from),
+        (None, Some(to)) => format!("Up to {}", to),
+        (None, None) => "All time".to_string(),
+    };
+    let mut notes = vec![period];
+    notes.extend(
+        report
+            .by_method
+            .iter()
+            .map(|m| format!("{}: {} payments, {} {}", m.method.label(), m.payments, currency, amount(m.net_kobo))),
+    );
+
+    ReportTable {
+        title: "Fee collections".to_string(),
+        notes,
+        headers: vec![
+            "Period".to_string(),
+            "Method".to_string(),
+            "Payments".to_string(),
+            format!("Collected ({})", currency),
+            format!("Refunded ({})", currency),
+            format!("Net ({})", currency),
+        ],
+        rows,
+        widths: vec![1.2, 1.2, 0.8, 1.2, 1.2, 1.2],
+    }
+}
+
+/// Two tables in one: by class, then by fee item, separated by a blank row.
+pub fn outstanding_table(report: &OutstandingReport) -> ReportTable {
+    let currency = report.currency;
+    let mut rows: Vec<Vec<String>> = report
+        .by_class
+        .iter()
+        .map(|c| {
+            vec![
+                c.class_name.clone(),
+                c.students.to_string(),
+                amount(c.invoiced_kobo as i64),
+                amount(c.paid_kobo as i64),
+                amount(c.outstanding_kobo as i64),
+            ]
+        })
+        .collect();
+    rows.push(vec![String::new(); 5]);
+    rows.push(strings(&["Fee item", "", "Billed", "", "Outstanding"]));
+    rows.extend(report.by_fee_item.iter().map(|f| {
+        vec![
+            f.description.clone(),
+            String::new(),
+            amount(f.billed_kobo as i64),
+            String::new(),
+            amount(f.outstanding_kobo as i64),
+        ]
+    }));
+    rows.push(vec![
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        amount(report.outstanding_kobo as i64),
+    ]);
+
+    ReportTable {
+        title: "Outstanding balances".to_string(),
+        notes: vec![match &report.term {
+            Some(term) => format!("Term {}", term),
+            None => "All terms".to_string(),
+        }],
+        headers: vec![
+            "Class".to_string(),
+            "Students".to_string(),
+            format!("Invoiced ({})", currency),
+            format!("Paid ({})", currency),
+            format!("Outstanding ({})", currency),
+        ],
+        rows,
+        widths: vec![1.6, 0.8, 1.2, 1.2, 1.2],
+    }
+}
+
+pub fn aging_table(report: &AgingReport) -> ReportTable {
+    let mut rows: Vec<Vec<String>> = report
+        .buckets
+        .iter()
+        .map(|b| vec![b.label.clone(), b.invoices.to_string(), amount(b.outstanding_kobo as i64)])
+        .collect();
+    rows.push(vec![
+        "Total".to_string(),
+        report.buckets.iter().map(|b| b.invoices).sum::<usize>().to_string(),
+        amount(report.outstanding_kobo as i64),
+    ]);
+
+    ReportTable {
+        title: "Aged receivables".to_string(),
+        notes: vec![format!("Days past due as of {}", report.as_of)],
+        headers: vec![
+            "Age".to_string(),
+            "Invoices".to_string(),
+            format!("Outstanding ({})", report.currency),
+        ],
+        rows,
+        widths: vec![1.5, 1.0, 1.5],
+    }
+}
+
+pub fn debtors_table(report: &DebtorsReport) -> ReportTable {
+    let mut rows: Vec<Vec<String>> = report
+        .debtors
+        .iter()
+        .map(|d| {
+            vec![
+                d.admission_number.clone(),
+                d.name.clone(),
+                d.class_name.clone().unwrap_or_default(),
+                d.guardian_phone.clone().unwrap_or_default(),
+                d.oldest_due_date.to_string(),
+                d.days_overdue.max(0).to_string(),
+                amount(d.balance_kobo as i64),
+            ]
+        })
+        .collect();
+    rows.push(vec![
+        "Total".to_string(),
+        format!("{} students", report.debtors.len()),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        amount(report.outstanding_kobo as i64),
+    ]);
+
+    ReportTable {
+        title: "Debtors".to_string(),
+        notes: vec![format!("As of {}", report.as_of)],
+        headers: vec![
+            "Admission no.".to_string(),
+            "Name".to_string(),
+            "Class".to_string(),
+            "Guardian phone".to_string(),
+            "Oldest due".to_string(),
+            "Days overdue".to_string(),
+            format!("Balance ({})", report.currency),
+        ],
+        rows,
+        widths: vec![1.4, 1.8, 0.8, 1.3, 1.1, 0.9, 1.2],
+    }
+}
+
+pub fn render_csv(table: &ReportTable) -> Result<Vec<u8>, AppError> {
+    to_csv(&table.headers, &table.rows)
+}
+
+pub fn render_pdf(table: &ReportTable, school_name: &str) -> Result<Vec<u8>, AppError> {
+    let mut pdf = PdfBuilder::new(&table.title)?;
+    pdf.heading(school_name, 16.0);
+    pdf.heading(&table.title, 13.0);
+    for note in &table.notes {
+        pdf.text(note);
+    }
+    pdf.gap(4.0);
+
+    let scale = TABLE_WIDTH / table.widths.iter().sum::<f32>();
+    let offsets: Vec<f32> = table
+        .widths
+        .iter()
+        .scan(0.0, |x, width| {
+            let offset = *x;
+            *x += width * scale;
+            Some(offset)
+        })
+        .collect();
+    let line = |cells: &[String]| -> Vec<(f32, String)> {
+        offsets.iter().copied().zip(cells.iter().cloned()).collect()
+    };
+
+    let headers = line(&table.headers);
+    let cells: Vec<(f32, &str)> = headers.iter().map(|(x, t)| (*x, t.as_str())).collect();
+    pdf.columns(&cells, true);
+    pdf.rule();
+    for (index, row) in table.rows.iter().enumerate() {
+        let row = line(row);
+        let cells: Vec<(f32, &str)> = row.iter().map(|(x, t)| (*x, t.as_str())).collect();
+        // the last row is always the total
+        pdf.columns(&cells, index + 1 == table.rows.len());
+    }
+    pdf.finish()
+}
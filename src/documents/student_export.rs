@@ -0,0 +1,78 @@
+use crate::{
+    errors::AppError,
+    models::{
+        Gender, Student,
+        custom_fields::{CustomFieldDefinition, display_value},
+    },
+};
+
+use super::to_csv;
+
+fn optional(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+/// One row per student with the core profile followed by a column for each
+/// of the school's custom fields, ordered by key.
+pub fn render_csv(students: &[Student], custom_fields: &[CustomFieldDefinition]) -> Result<Vec<u8>, AppError> {
+    let mut headers: Vec<String> = [
+        "admission_number",
+        "first_name",
+        "last_name",
+        "email",
+        "department",
+        "class_name",
+        "date_of_birth",
+        "gender",
+        "address",
+        "phone",
+        "medical_notes",
+        "previous_school",
+        "guardian_name",
+        "guardian_phone",
+        "guardian_email",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+    headers.extend(custom_fields.iter().map(|f| f.label.clone()));
+
+    let rows: Vec<Vec<String>> = students
+        .iter()
+        .map(|s| {
+            let mut row = vec![
+                s.admission_number.clone(),
+                s.first_name.clone(),
+                s.last_name.clone(),
+                s.email.clone(),
+                s.department.clone(),
+                optional(&s.class_name),
+                s.profile
+                    .date_of_birth
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                match s.profile.gender {
+                    Some(Gender::Male) => "Male".to_string(),
+                    Some(Gender::Female) => "Female".to_string(),
+                    None => String::new(),
+                },
+                optional(&s.profile.address),
+                optional(&s.profile.phone),
+                optional(&s.profile.medical_notes),
+                optional(&s.profile.previous_school),
+                optional(&s.profile.guardian_name),
+                optional(&s.profile.guardian_phone),
+                optional(&s.profile.guardian_email),
+            ];
+            row.extend(custom_fields.iter().map(|f| {
+                s.custom_fields
+                    .get(&f.key)
+                    .map(display_value)
+                    .unwrap_or_default()
+            }));
+            row
+        })
+        .collect();
+
+    to_csv(&headers, &rows)
+}
@@ -1,3 +1,4 @@
+use axum::http::StatusCode;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,4 +17,29 @@ pub enum AppError {
     Unauthorized(String),
     #[error("Conflict: {0}")]
     Conflict(String),
-}
\ No newline at end of file
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+}
+
+impl AppError {
+    pub fn invalid(field: &str, message: &str) -> Self {
+        AppError::UnProcessableEntity {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::UnProcessableEntity { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::ParsingError(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::InternalServerError(_) | AppError::MissingEnvironmentVarible(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
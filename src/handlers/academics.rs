@@ -0,0 +1,141 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        academics::{
+            CreateSubjectRequest, CreateTermRequest, RecordScoresRequest,
+            SetAssessmentComponentsRequest, SetGradingScaleRequest,
+        },
+    },
+};
+
+// -- Term handlers --
+
+pub async fn create_term_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateTermRequest>,
+) -> impl IntoResponse {
+    match store.create_term(auth.school_id, req).await {
+        Ok(term) => (StatusCode::CREATED, Json(term)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_terms_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_terms(auth.school_id).await {
+        Ok(terms) => (StatusCode::OK, Json(terms)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn set_assessment_components_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(term): Path<String>,
+    Json(req): Json<SetAssessmentComponentsRequest>,
+) -> impl IntoResponse {
+    match store.set_assessment_components(auth.school_id, &term, req).await {
+        Ok(term) => (StatusCode::OK, Json(term)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+// -- Subject handlers --
+
+pub async fn create_subject_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateSubjectRequest>,
+) -> impl IntoResponse {
+    match store.create_subject(auth.school_id, req).await {
+        Ok(subject) => (StatusCode::CREATED, Json(subject)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_subjects_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_subjects(auth.school_id).await {
+        Ok(subjects) => (StatusCode::OK, Json(subjects)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+// -- Grading scale handlers --
+
+pub async fn set_grading_scale_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<SetGradingScaleRequest>,
+) -> impl IntoResponse {
+    match store.set_grading_scale(auth.school_id, req).await {
+        Ok(bands) => (StatusCode::OK, Json(bands)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_grading_scale_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_grading_scale(auth.school_id).await {
+        Ok(bands) => (StatusCode::OK, Json(bands)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+// -- Gradebook handlers --
+
+pub async fn record_scores_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((class_name, subject_id, term)): Path<(String, Uuid, String)>,
+    Json(req): Json<RecordScoresRequest>,
+) -> impl IntoResponse {
+    match store
+        .record_scores(auth.school_id, &class_name, subject_id, &term, &auth.username, req)
+        .await
+    {
+        Ok(sheet) => (StatusCode::OK, Json(sheet)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_subject_broadsheet_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((class_name, subject_id, term)): Path<(String, Uuid, String)>,
+) -> impl IntoResponse {
+    match store
+        .get_subject_broadsheet(auth.school_id, &class_name, subject_id, &term)
+        .await
+    {
+        Ok(sheet) => (StatusCode::OK, Json(sheet)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_class_results_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((class_name, term)): Path<(String, String)>,
+) -> impl IntoResponse {
+    match store.get_class_results(auth.school_id, &class_name, &term).await {
+        Ok(results) => (StatusCode::OK, Json(results)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
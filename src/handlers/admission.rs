@@ -0,0 +1,32 @@
+use axum::{
+    Json,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{AppStore, admission::AdmissionNumberFormat},
+};
+
+pub async fn set_admission_format_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<AdmissionNumberFormat>,
+) -> impl IntoResponse {
+    match store.set_admission_format(auth.school_id, req).await {
+        Ok(format) => (StatusCode::OK, Json(format)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_admission_format_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_admission_format(auth.school_id).await {
+        Ok(format) => (StatusCode::OK, Json(format)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
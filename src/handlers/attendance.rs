@@ -0,0 +1,90 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        attendance::{
+            AbsenteesQuery, AttendanceRegisterQuery, CorrectAttendanceRequest,
+            MarkAttendanceRequest,
+        },
+    },
+};
+
+pub async fn mark_attendance_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(class_name): Path<String>,
+    Json(req): Json<MarkAttendanceRequest>,
+) -> impl IntoResponse {
+    match store
+        .mark_attendance(auth.school_id, &class_name, &auth.username, req)
+        .await
+    {
+        Ok(records) => (StatusCode::CREATED, Json(records)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_attendance_register_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(class_name): Path<String>,
+    Query(query): Query<AttendanceRegisterQuery>,
+) -> impl IntoResponse {
+    match store
+        .get_attendance_register(auth.school_id, &class_name, query)
+        .await
+    {
+        Ok(records) => (StatusCode::OK, Json(records)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn correct_attendance_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CorrectAttendanceRequest>,
+) -> impl IntoResponse {
+    match store
+        .correct_attendance(auth.school_id, id, &auth.username, req)
+        .await
+    {
+        Ok(record) => (StatusCode::OK, Json(record)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_attendance_summary_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((id, term)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_attendance_summary(auth.school_id, id, &term).await {
+        Ok(summary) => (StatusCode::OK, Json(summary)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_chronic_absentees_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<AbsenteesQuery>,
+) -> impl IntoResponse {
+    match store.get_chronic_absentees(auth.school_id, query).await {
+        Ok(absentees) => (StatusCode::OK, Json(absentees)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
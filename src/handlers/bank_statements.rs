@@ -0,0 +1,144 @@
+use axum::{
+    Json,
+    extract::{Extension, Multipart, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    errors::AppError,
+    models::{
+        AppStore,
+        bank_statements::{ConfirmLineRequest, MAX_STATEMENT_BYTES, StatementLineQuery},
+        staff::StaffRole,
+    },
+    statements::{self, StatementFormat},
+};
+
+struct StatementUpload {
+    file_name: String,
+    format: Option<StatementFormat>, // detected from the file when left out
+    bytes: Vec<u8>,
+}
+
+// Reads the multipart form: a `file` plus an optional `format` field
+async fn read_statement_upload(mut form: Multipart) -> Result<StatementUpload, AppError> {
+    let mut file = None;
+    let mut format = None;
+
+    while let Some(field) = form
+        .next_field()
+        .await
+        .map_err(|e| AppError::ParsingError(e.to_string()))?
+    {
+        match field.name().unwrap_or_default() {
+            "file" => {
+                let file_name = field.file_name().unwrap_or("statement").to_string();
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::ParsingError(e.to_string()))?;
+                file = Some((file_name, bytes.to_vec()));
+            }
+            "format" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| AppError::ParsingError(e.to_string()))?;
+                format = Some(
+                    serde_json::from_value::<StatementFormat>(serde_json::Value::String(value.to_lowercase()))
+                        .map_err(|_| AppError::invalid("format", "Expected csv, mt940 or camt053"))?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, bytes) = file.ok_or_else(|| AppError::invalid("file", "This field is required"))?;
+    if bytes.len() > MAX_STATEMENT_BYTES {
+        return Err(AppError::invalid("file", "Statements must be 10 MB or smaller"));
+    }
+    Ok(StatementUpload { file_name, format, bytes })
+}
+
+pub async fn import_bank_statement_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    form: Multipart,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let upload = match read_statement_upload(form).await {
+        Ok(upload) => upload,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    let format = upload
+        .format
+        .unwrap_or_else(|| StatementFormat::detect(&upload.bytes));
+    let parsed = match statements::parse(format, &upload.bytes) {
+        Ok(parsed) => parsed,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store
+        .import_bank_statement(auth.school_id, &auth.username, upload.file_name, format, parsed)
+        .await
+    {
+        Ok(report) => (StatusCode::CREATED, Json(report)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_bank_statements_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    (StatusCode::OK, Json(store.get_bank_statements(auth.school_id).await)).into_response()
+}
+
+pub async fn get_bank_statement_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<StatementLineQuery>,
+) -> impl IntoResponse {
+    match store.get_bank_statement(auth.school_id, id, query).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn confirm_statement_line_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ConfirmLineRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.confirm_statement_line(auth.school_id, id, &auth.username, req).await {
+        Ok(line) => (StatusCode::OK, Json(line)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn ignore_statement_line_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.ignore_statement_line(auth.school_id, id, &auth.username).await {
+        Ok(line) => (StatusCode::OK, Json(line)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,36 @@
+use axum::{
+    Json,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{AppStore, money::CurrencySettings, staff::StaffRole},
+};
+
+pub async fn set_currency_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CurrencySettings>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.set_school_currency(auth.school_id, req.currency).await {
+        Ok(school) => (StatusCode::OK, Json(CurrencySettings { currency: school.currency })).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_currency_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_school(auth.school_id).await {
+        Ok(school) => (StatusCode::OK, Json(CurrencySettings { currency: school.currency })).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
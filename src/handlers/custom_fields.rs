@@ -0,0 +1,43 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{AppStore, custom_fields::CreateCustomFieldRequest},
+};
+
+pub async fn create_custom_field_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateCustomFieldRequest>,
+) -> impl IntoResponse {
+    match store.create_custom_field(auth.school_id, req).await {
+        Ok(field) => (StatusCode::CREATED, Json(field)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_custom_fields_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_custom_fields(auth.school_id).await {
+        Ok(fields) => (StatusCode::OK, Json(fields)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn delete_custom_field_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(key): Path<String>,
+) -> impl IntoResponse {
+    match store.delete_custom_field(auth.school_id, &key).await {
+        Ok(()) => (StatusCode::OK, Json("Custom field deleted!")).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
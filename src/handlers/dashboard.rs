@@ -0,0 +1,21 @@
+use axum::{
+    Json,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::AppStore,
+};
+
+pub async fn get_dashboard_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_dashboard(auth.school_id).await {
+        Ok(dashboard) => (StatusCode::OK, Json(dashboard)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,93 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        discounts::{DiscountRequest, SetStudentDiscountsRequest},
+        staff::StaffRole,
+    },
+};
+
+pub async fn create_discount_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<DiscountRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.create_discount(auth.school_id, req).await {
+        Ok(discount) => (StatusCode::CREATED, Json(discount)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_discounts_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_discounts(auth.school_id).await {
+        Ok(discounts) => (StatusCode::OK, Json(discounts)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn update_discount_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<DiscountRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.update_discount(auth.school_id, id, req).await {
+        Ok(discount) => (StatusCode::OK, Json(discount)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn delete_discount_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.delete_discount(auth.school_id, id).await {
+        Ok(()) => (StatusCode::OK, Json("Discount deleted!")).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn set_student_discounts_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+    Json(req): Json<SetStudentDiscountsRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.set_student_discounts(auth.school_id, id, req).await {
+        Ok(student) => (StatusCode::OK, Json(student)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,92 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        fees::{FeeItemRequest, SetOptionalFeeItemsRequest},
+    },
+};
+
+pub async fn create_fee_item_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<FeeItemRequest>,
+) -> impl IntoResponse {
+    match store.create_fee_item(auth.school_id, req).await {
+        Ok(item) => (StatusCode::CREATED, Json(item)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_fee_items_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_fee_items(auth.school_id).await {
+        Ok(items) => (StatusCode::OK, Json(items)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn update_fee_item_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<FeeItemRequest>,
+) -> impl IntoResponse {
+    match store.update_fee_item(auth.school_id, id, req).await {
+        Ok(item) => (StatusCode::OK, Json(item)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn delete_fee_item_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.delete_fee_item(auth.school_id, id).await {
+        Ok(()) => (StatusCode::OK, Json("Fee item deleted!")).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn set_optional_fee_items_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+    Json(req): Json<SetOptionalFeeItemsRequest>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.set_optional_fee_items(auth.school_id, id, req).await {
+        Ok(student) => (StatusCode::OK, Json(student)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_fee_statement_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((id, term)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_fee_statement(auth.school_id, id, &term).await {
+        Ok(statement) => (StatusCode::OK, Json(statement)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
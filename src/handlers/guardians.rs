@@ -0,0 +1,64 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        guardians::{CreateGuardianRequest, SetGuardianStudentsRequest},
+    },
+};
+
+pub async fn create_guardian_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateGuardianRequest>,
+) -> impl IntoResponse {
+    match store.create_guardian(auth.school_id, req).await {
+        Ok(guardian) => (StatusCode::CREATED, Json(guardian)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_guardians_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_guardians(auth.school_id).await {
+        Ok(guardians) => (StatusCode::OK, Json(guardians)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn set_guardian_students_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetGuardianStudentsRequest>,
+) -> impl IntoResponse {
+    match store.set_guardian_students(auth.school_id, id, req).await {
+        Ok(guardian) => (StatusCode::OK, Json(guardian)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_siblings_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_siblings(auth.school_id, id).await {
+        Ok(siblings) => (StatusCode::OK, Json(siblings)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,48 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        installments::{AssignInstallmentPlanRequest, CreateInstallmentPlanRequest},
+    },
+};
+
+pub async fn create_installment_plan_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateInstallmentPlanRequest>,
+) -> impl IntoResponse {
+    match store.create_installment_plan(auth.school_id, req).await {
+        Ok(plan) => (StatusCode::CREATED, Json(plan)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_installment_plans_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_installment_plans(auth.school_id).await {
+        Ok(plans) => (StatusCode::OK, Json(plans)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn assign_installment_plan_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AssignInstallmentPlanRequest>,
+) -> impl IntoResponse {
+    match store.assign_installment_plan(auth.school_id, id, req).await {
+        Ok(invoice) => (StatusCode::OK, Json(invoice)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,88 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        late_fees::{LateFeePolicy, WaiveLateFeeRequest},
+        staff::StaffRole,
+    },
+};
+
+pub async fn set_late_fee_policy_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<LateFeePolicy>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.set_late_fee_policy(auth.school_id, req).await {
+        Ok(policy) => (StatusCode::OK, Json(policy)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_late_fee_policy_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_late_fee_policy(auth.school_id).await {
+        Ok(policy) => (StatusCode::OK, Json(policy)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+/// Runs the late fee job for the caller's school now rather than waiting
+/// for the next scheduled run.
+pub async fn apply_late_fees_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store
+        .apply_late_fees(auth.school_id, Utc::now().date_naive(), &auth.username)
+        .await
+    {
+        Ok(applied) => (StatusCode::OK, Json(applied)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_invoice_late_fees_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.get_invoice_late_fees(auth.school_id, id).await {
+        Ok(fees) => (StatusCode::OK, Json(fees)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn waive_late_fee_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<WaiveLateFeeRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.waive_late_fee(auth.school_id, id, &auth.username, req).await {
+        Ok(fee) => (StatusCode::OK, Json(fee)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
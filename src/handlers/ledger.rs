@@ -0,0 +1,126 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    jobs::reconciliation::verify_payment,
+    models::{
+        AppStore,
+        ledger::{CreateInvoiceRequest, InvoiceQuery},
+    },
+    services::payment_provider,
+};
+
+pub async fn create_invoice_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+    Json(req): Json<CreateInvoiceRequest>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.create_invoice(auth.school_id, id, &auth.username, req).await {
+        Ok(invoice) => (StatusCode::CREATED, Json(invoice)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_student_invoices_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_student_invoices(auth.school_id, id).await {
+        Ok(invoices) => (StatusCode::OK, Json(invoices)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_student_ledger_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_student_ledger(auth.school_id, id).await {
+        Ok(ledger) => (StatusCode::OK, Json(ledger)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_student_payments_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_student_payments(auth.school_id, id).await {
+        Ok(payments) => (StatusCode::OK, Json(payments)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn list_invoices_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<InvoiceQuery>,
+) -> impl IntoResponse {
+    match store.list_invoices(auth.school_id, query).await {
+        Ok(invoices) => (StatusCode::OK, Json(invoices)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_invoice_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.get_invoice(auth.school_id, id).await {
+        Ok(invoice) => (StatusCode::OK, Json(invoice)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn verify_payment_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(reference): Path<String>,
+) -> impl IntoResponse {
+    // only verify references that belong to this school
+    let payment = match store.find_payment_by_reference(auth.school_id, &reference).await {
+        Ok(payment) => payment,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let provider = match payment_provider(&store, &payment).await {
+        Ok(p) => p,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match verify_payment(&store, provider.as_ref(), &reference).await {
+        Ok(payment) => (StatusCode::OK, Json(payment)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
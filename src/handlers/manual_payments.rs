@@ -0,0 +1,215 @@
+use axum::{
+    Json,
+    extract::{Extension, Multipart, Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    errors::AppError,
+    models::{
+        AppStore,
+        ledger::PaymentMethod,
+        manual_payments::{EvidenceInfo, ManualPaymentInput, ManualPaymentPolicy, PaymentEvidence, RejectPaymentRequest},
+        staff::StaffRole,
+    },
+};
+
+// Reads the multipart form: text fields plus an optional `evidence` file
+async fn read_manual_payment(mut form: Multipart) -> Result<ManualPaymentInput, AppError> {
+    let mut term = None;
+    let mut method = None;
+    let mut amount_kobo = None;
+    let mut paid_on = None;
+    let mut external_reference = None;
+    let mut note = None;
+    let mut evidence = None;
+
+    while let Some(field) = form
+        .next_field()
+        .await
+        .map_err(|e| AppError::ParsingError(e.to_string()))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "evidence" {
+            let file_name = field.file_name().unwrap_or("evidence").to_string();
+            // "image/png; name=x" and "IMAGE/PNG" are both image/png
+            let content_type = field
+                .content_type()
+                .unwrap_or("application/octet-stream")
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::ParsingError(e.to_string()))?;
+            evidence = Some(PaymentEvidence {
+                info: EvidenceInfo { file_name, content_type, size_bytes: bytes.len() },
+                bytes: bytes.to_vec(),
+            });
+            continue;
+        }
+
+        let value = field
+            .text()
+            .await
+            .map_err(|e| AppError::ParsingError(e.to_string()))?;
+        match name.as_str() {
+            "term" => term = Some(value),
+            "method" => {
+                method = Some(
+                    serde_json::from_value::<PaymentMethod>(serde_json::Value::String(value))
+                        .map_err(|_| AppError::invalid("method", "Expected Cash, BankTransfer or Pos"))?,
+                )
+            }
+            "amount_kobo" => {
+                amount_kobo = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| AppError::invalid("amount_kobo", "Expected a whole number of kobo"))?,
+                )
+            }
+            "paid_on" => {
+                paid_on = Some(
+                    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                        .map_err(|_| AppError::invalid("paid_on", "Expected a date formatted as YYYY-MM-DD"))?,
+                )
+            }
+            "external_reference" => external_reference = Some(value),
+            "note" => note = Some(value),
+            _ => {}
+        }
+    }
+
+    Ok(ManualPaymentInput {
+        term,
+        method: method.ok_or_else(|| AppError::invalid("method", "This field is required"))?,
+        amount_kobo: amount_kobo.ok_or_else(|| AppError::invalid("amount_kobo", "This field is required"))?,
+        paid_on: paid_on.ok_or_else(|| AppError::invalid("paid_on", "This field is required"))?,
+        external_reference,
+        note,
+        evidence,
+    })
+}
+
+pub async fn record_manual_payment_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+    form: Multipart,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar, StaffRole::Cashier]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let input = match read_manual_payment(form).await {
+        Ok(input) => input,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.record_manual_payment(auth.school_id, id, &auth.username, input).await {
+        Ok(payment) => (StatusCode::CREATED, Json(payment)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_payments_awaiting_approval_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_payments_awaiting_approval(auth.school_id).await {
+        Ok(payments) => (StatusCode::OK, Json(payments)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn approve_manual_payment_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.approve_manual_payment(auth.school_id, id, &auth.username).await {
+        Ok(payment) => (StatusCode::OK, Json(payment)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn reject_manual_payment_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RejectPaymentRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.reject_manual_payment(auth.school_id, id, &auth.username, req).await {
+        Ok(payment) => (StatusCode::OK, Json(payment)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_payment_evidence_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.get_payment_evidence(auth.school_id, id).await {
+        Ok(evidence) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, evidence.info.content_type),
+                // a download, never a page on our origin
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", evidence.info.file_name.replace('"', "")),
+                ),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            ],
+            evidence.bytes,
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn set_manual_payment_policy_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<ManualPaymentPolicy>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.set_manual_payment_policy(auth.school_id, req).await {
+        Ok(policy) => (StatusCode::OK, Json(policy)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_manual_payment_policy_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_manual_payment_policy(auth.school_id).await {
+        Ok(policy) => (StatusCode::OK, Json(policy)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -1,19 +1,52 @@
+pub mod academics;
+pub mod admission;
+pub mod attendance;
+pub mod bank_statements;
+pub mod currency;
+pub mod custom_fields;
+pub mod dashboard;
+pub mod discounts;
+pub mod fees;
+pub mod guardians;
+pub mod installments;
+pub mod late_fees;
+pub mod ledger;
+pub mod manual_payments;
+pub mod payment_links;
+pub mod payment_settings;
+pub mod receipts;
+pub mod refunds;
+pub mod reminders;
+pub mod report_cards;
+pub mod reports;
+pub mod staff;
+pub mod subaccounts;
+pub mod timetable;
+pub mod virtual_accounts;
+pub mod webhooks;
+
 use axum::{
     Json,
     body::Bytes,
-    extract::{Extension, Path, State},
-    http::{HeaderMap, StatusCode},
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
-use hmac::{Hmac, Mac};
-use sha2::Sha512;
-use uuid::Uuid;
 
 use crate::{
     auth::middleware::AuthSchool,
     config::get_env_vars,
-    models::{AppStore, CreateStudentRequest, LoginSchoolRequest, RegisterSchoolRequest},
-    services::initialize_paystack_transaction,
+    errors::AppError,
+    documents::student_export,
+    handlers::webhooks::receive_webhook,
+    models::{
+        AppStore, CreateStudentRequest, LoginSchoolRequest, RegisterSchoolRequest, StudentQuery,
+        UpdateStudentRequest,
+        fees::InitiatePaymentRequest,
+        ledger::amount_due_next,
+        staff::StaffRole,
+    },
+    services::{platform_provider, start_checkout},
 };
 
 // -- Auth handlers --
@@ -41,20 +74,23 @@ pub async fn login_handler(
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
     };
 
-    // Find the school by username
-    let school = match store.find_school_by_username(&req.username).await {
-        Ok(s) => s,
-        Err(_) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json("Invalid username or password".to_string()),
-            )
-                .into_response()
-        }
+    // Owners log in with the school's username, everyone else with a staff login
+    let (school_id, role, password_hash) = match store.find_school_by_username(&req.username).await {
+        Ok(school) => (school.id, StaffRole::Owner, school.password_hash),
+        Err(_) => match store.find_staff_by_username(&req.username).await {
+            Ok(user) => (user.school_id, user.role, user.password_hash),
+            Err(_) => {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json("Invalid username or password".to_string()),
+                )
+                    .into_response()
+            }
+        },
     };
 
     // Verify the password against the stored hash
-    let valid = bcrypt::verify(&req.password, &school.password_hash)
+    let valid = bcrypt::verify(&req.password, &password_hash)
         .unwrap_or(false);
 
     if !valid {
@@ -66,11 +102,12 @@ pub async fn login_handler(
     }
 
     // Generate and return the JWT
-    match crate::auth::create_jwt(school.id, &school.username, &secret) {
+    match crate::auth::create_jwt(school_id, &req.username, role, &secret) {
         Ok(token) => (StatusCode::OK, Json(serde_json::json!({
             "token": token,
-            "school_id": school.id,
-            "username": school.username,
+            "school_id": school_id,
+            "username": req.username,
+            "role": role,
         }))).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
     }
@@ -83,17 +120,26 @@ pub async fn create_student_handler(
     Extension(auth): Extension<AuthSchool>,
     Json(req): Json<CreateStudentRequest>,
 ) -> impl IntoResponse {
-    match store.create_student(auth.school_id, auth.username, req).await {
-        Ok(()) => (StatusCode::CREATED, Json("Student created successfully")).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    let school = match store.get_school(auth.school_id).await {
+        Ok(school) => school,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    match store.create_student(auth.school_id, school.name, req).await {
+        Ok(student) => (StatusCode::CREATED, Json(serde_json::json!({
+            "message": "Student created successfully",
+            "id": student.id,
+            "admission_number": student.admission_number,
+        }))).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
     }
 }
 
 pub async fn get_all_students_handler(
     State(store): State<AppStore>,
     Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<StudentQuery>,
 ) -> impl IntoResponse {
-    match store.get_all_students(auth.school_id).await {
+    match store.search_students(auth.school_id, query).await {
         Ok(students) => (StatusCode::OK, Json(students)).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
     }
@@ -102,19 +148,78 @@ pub async fn get_all_students_handler(
 pub async fn get_student_handler(
     State(store): State<AppStore>,
     Extension(auth): Extension<AuthSchool>,
-    Path(id): Path<Uuid>,
+    Path(id): Path<String>,
 ) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
     match store.get_student(auth.school_id, id).await {
         Ok(student) => (StatusCode::OK, Json(student)).into_response(),
         Err(e) => (StatusCode::NOT_FOUND, Json(e.to_string())).into_response(),
     }
 }
 
+pub async fn update_student_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateStudentRequest>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.update_student(auth.school_id, id, req).await {
+        Ok(student) => (StatusCode::OK, Json(student)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn export_students_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<StudentQuery>,
+) -> impl IntoResponse {
+    let students = match store.search_students(auth.school_id, query).await {
+        Ok(s) => s,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    let custom_fields = match store.get_custom_fields(auth.school_id).await {
+        Ok(f) => f,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match student_export::render_csv(&students, &custom_fields) {
+        Ok(csv) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"students.csv\""),
+            ],
+            csv,
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
 pub async fn delete_student_handler(
     State(store): State<AppStore>,
     Extension(auth): Extension<AuthSchool>,
-    Path(id): Path<Uuid>,
+    Path(id): Path<String>,
 ) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
     match store.delete_student(auth.school_id, id).await {
         Ok(_) => (StatusCode::OK, Json("Student deleted!")).into_response(),
         Err(e) => (StatusCode::NOT_FOUND, Json(e.to_string())).into_response(),
@@ -124,11 +229,12 @@ pub async fn delete_student_handler(
 pub async fn initiate_payment_handler(
     State(store): State<AppStore>,
     Extension(auth): Extension<AuthSchool>,
-    Path(id): Path<Uuid>,
+    Path(id): Path<String>,
+    req: Option<Json<InitiatePaymentRequest>>,
 ) -> impl IntoResponse {
-    let secret_key: String = match get_env_vars("PAYSTACK_SECRET_KEY".to_string()) {
-        Ok(k) => k,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
     };
 
     let student = match store.get_student(auth.school_id, id).await {
@@ -136,58 +242,62 @@ pub async fn initiate_payment_handler(
         Err(e) => return (StatusCode::NOT_FOUND, Json(e.to_string())).into_response(),
     };
 
-    let reference = format!("sch-{}", Uuid::new_v4());
-    let amount_kobo: u64 = 500_000;
+    let Json(req) = req.unwrap_or_default();
+    let term = match req.term {
+        Some(code) => store.get_term(auth.school_id, &code).await,
+        None => store.get_current_term(auth.school_id).await,
+    };
+    let term = match term {
+        Ok(t) => t,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    // bill the term on first payment if the bursar hasn't invoiced it yet
+    let invoice = match store.find_or_create_invoice(auth.school_id, id, &term.code, &auth.username).await {
+        Ok(invoice) => invoice,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
 
-    match initialize_paystack_transaction(&secret_key, &student.email, amount_kobo, &reference).await {
-        Ok(data) => {
-            let _ = store.set_payment_reference(auth.school_id, id, data.reference.clone()).await;
-            (StatusCode::OK, Json(serde_json::json!({
-                "authorization_url": data.authorization_url,
-                "reference": data.reference,
-            }))).into_response()
+    if invoice.balance_kobo <= 0 {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json("Nothing is owed on this term's invoice".to_string()),
+        )
+            .into_response();
+    }
+    let balance_kobo = invoice.balance_kobo as u64;
+
+    // parents can pay any part of the balance; by default they are asked for
+    // whatever is left on the earliest unpaid installment
+    let amount_kobo = match req.amount_kobo {
+        Some(amount) if amount == 0 || amount > balance_kobo => {
+            let e = AppError::invalid("amount_kobo", "Amount must be between 1 and the outstanding balance");
+            return (e.status_code(), Json(e.to_string())).into_response();
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+        Some(amount) => amount,
+        None => amount_due_next(&invoice),
+    };
+
+    match start_checkout(&store, &invoice, amount_kobo, &student.email, None).await {
+        Ok(data) => (StatusCode::OK, Json(serde_json::json!({
+            "authorization_url": data.authorization_url,
+            "reference": data.reference,
+            "invoice_id": invoice.id,
+            "term": term.code,
+            "amount_kobo": amount_kobo,
+        }))).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
     }
 }
 
+/// Webhooks for schools on the platform's Paystack account.
 pub async fn paystack_webhook_handler(
     State(store): State<AppStore>,
     headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
-    let secret_key: String = match get_env_vars("PAYSTACK_SECRET_KEY".to_string()) {
-        Ok(k) => k,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR,
-    };
-
-    let signature = match headers
-        .get("x-paystack-signature")
-        .and_then(|v| v.to_str().ok())
-    {
-        Some(sig) => sig.to_string(),
-        None => return StatusCode::UNAUTHORIZED,
-    };
-
-    let mut mac = Hmac::<Sha512>::new_from_slice(secret_key.as_bytes())
-        .expect("HMAC can take key of any size");
-    mac.update(&body);
-    let expected = hex::encode(mac.finalize().into_bytes());
-
-    if expected != signature {
-        return StatusCode::UNAUTHORIZED;
+    match platform_provider() {
+        Ok(provider) => receive_webhook(&store, provider.as_ref(), None, &headers, &body).await,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
     }
-
-    let event: serde_json::Value = match serde_json::from_slice(&body) {
-        Ok(v) => v,
-        Err(_) => return StatusCode::BAD_REQUEST,
-    };
-
-    if event["event"] == "charge.success" {
-        if let Some(reference) = event["data"]["reference"].as_str() {
-            let _ = store.mark_student_paid_by_reference(reference).await;
-        }
-    }
-
-    StatusCode::OK
-}
\ No newline at end of file
+}
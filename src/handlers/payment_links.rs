@@ -0,0 +1,205 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    documents::payment_page::{render_message, render_outcome, render_page},
+    errors::AppError,
+    jobs::reconciliation::verify_payment,
+    logger::AppLogger,
+    models::{
+        AppStore,
+        payment_links::{CreatePaymentLinkRequest, PaymentLink, link_amount, public_url},
+        staff::StaffRole,
+    },
+    services::{payment_provider, start_checkout},
+};
+
+// -- Staff handlers --
+
+pub async fn create_payment_link_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+    req: Option<Json<CreatePaymentLinkRequest>>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let Json(req) = req.unwrap_or_default();
+    match store.create_payment_link(auth.school_id, id, &auth.username, req).await {
+        Ok(link) => (StatusCode::CREATED, Json(link)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_payment_links_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    (StatusCode::OK, Json(store.get_student_payment_links(auth.school_id, id).await)).into_response()
+}
+
+pub async fn revoke_payment_link_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.revoke_payment_link(auth.school_id, id).await {
+        Ok(link) => (StatusCode::OK, Json(link)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+// -- Public pages, reached through the link itself --
+
+fn error_page(e: AppError) -> Response {
+    let (status, message) = match e {
+        AppError::NotFound => (
+            StatusCode::NOT_FOUND,
+            "This payment link is not valid. Please ask the school for a new one.".to_string(),
+        ),
+        AppError::Forbidden(message) => (StatusCode::GONE, format!("{}. Please ask the school for a new one.", message)),
+        e => {
+            AppLogger::error(&format!("Payment link page failed: {}", e));
+            (
+                e.status_code(),
+                "Something went wrong on our side. Please try again in a few minutes.".to_string(),
+            )
+        }
+    };
+    (status, Html(render_message("Payment link", &message))).into_response()
+}
+
+pub async fn payment_page_handler(State(store): State<AppStore>, Path(token): Path<String>) -> Response {
+    let link = match store.open_payment_link(&token).await {
+        Ok(link) => link,
+        Err(e) => return error_page(e),
+    };
+
+    match store.get_payment_page(&link).await {
+        Ok(page) => Html(render_page(&page, &format!("/pay/{}", token))).into_response(),
+        Err(e) => error_page(e),
+    }
+}
+
+/// Starts a checkout for what the link asks and sends the payer to the gateway.
+pub async fn start_link_checkout_handler(State(store): State<AppStore>, Path(token): Path<String>) -> Response {
+    let link = match store.open_payment_link(&token).await {
+        Ok(link) => link,
+        Err(e) => return error_page(e),
+    };
+    let (student, invoice) = match (
+        store.get_student(link.school_id, link.student_id).await,
+        store.get_invoice(link.school_id, link.invoice_id).await,
+    ) {
+        (Ok(student), Ok(invoice)) => (student, invoice),
+        (Err(e), _) | (_, Err(e)) => return error_page(e),
+    };
+
+    // paid off since the page was opened
+    let amount_kobo = link_amount(&link, &invoice);
+    if amount_kobo == 0 {
+        return Redirect::to(&format!("/pay/{}", token)).into_response();
+    }
+
+    // anyone holding the link can post here; don't open a new checkout on every press
+    if let Some(checkout) = store.reusable_link_checkout(&link, amount_kobo).await {
+        return Redirect::to(&checkout.authorization_url).into_response();
+    }
+
+    let callback_url = public_url(&format!("/pay/{}/return", token));
+    match start_checkout(&store, &invoice, amount_kobo, &student.email, Some(callback_url)).await {
+        Ok(checkout) => {
+            if let Err(e) = store
+                .add_payment_link_checkout(link.id, &checkout.reference, &checkout.authorization_url)
+                .await
+            {
+                return error_page(e);
+            }
+            Redirect::to(&checkout.authorization_url).into_response()
+        }
+        Err(e) => error_page(e),
+    }
+}
+
+/// Gateways append their own names for our reference to the return URL.
+#[derive(Deserialize)]
+pub struct LinkReturnQuery {
+    pub reference: Option<String>, // Paystack
+    pub trxref: Option<String>,    // Paystack, older checkouts
+    pub tx_ref: Option<String>,    // Flutterwave
+}
+
+// The payment the payer is coming back from. Stripe adds nothing to the
+// URL, so without a known reference it's the link's latest checkout.
+fn returning_reference(link: &PaymentLink, query: LinkReturnQuery) -> Option<String> {
+    [query.reference, query.trxref, query.tx_ref]
+        .into_iter()
+        .flatten()
+        .find(|r| link.payment_references.contains(r))
+        .or_else(|| link.payment_references.last().cloned())
+}
+
+/// The page the gateway sends the payer back to. The outcome shown is the
+/// one the gateway confirms, never what the return URL claims.
+pub async fn payment_return_handler(
+    State(store): State<AppStore>,
+    Path(token): Path<String>,
+    Query(query): Query<LinkReturnQuery>,
+) -> Response {
+    // a payer finishing checkout just after the link closes still gets their answer
+    let link = match store.find_payment_link(&token).await {
+        Ok(link) => link,
+        Err(e) => return error_page(e),
+    };
+    let Some(reference) = returning_reference(&link, query) else {
+        return Redirect::to(&format!("/pay/{}", token)).into_response();
+    };
+
+    let verified = match store.find_payment_by_reference(link.school_id, &reference).await {
+        Ok(payment) => match payment_provider(&store, &payment).await {
+            Ok(provider) => verify_payment(&store, provider.as_ref(), &reference).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    let payment = match verified {
+        Ok(payment) => payment,
+        Err(e) => {
+            // show what we know; the webhook or reconciliation will settle it
+            AppLogger::warn(&format!("Could not verify payment {} on return: {}", reference, e));
+            match store.find_payment_by_reference(link.school_id, &reference).await {
+                Ok(payment) => payment,
+                Err(e) => return error_page(e),
+            }
+        }
+    };
+
+    match store.get_payment_page(&link).await {
+        Ok(page) => Html(render_outcome(&page, &payment, &format!("/pay/{}", token))).into_response(),
+        Err(e) => error_page(e),
+    }
+}
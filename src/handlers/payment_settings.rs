@@ -0,0 +1,37 @@
+use axum::{
+    Json,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{AppStore, payment_settings::PaymentSettingsRequest, staff::StaffRole},
+};
+
+pub async fn set_payment_settings_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<PaymentSettingsRequest>,
+) -> impl IntoResponse {
+    // the keys decide whose account every checkout pays into
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.set_payment_settings(auth.school_id, req).await {
+        Ok(settings) => (StatusCode::OK, Json(settings)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_payment_settings_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_payment_settings(auth.school_id).await {
+        Ok(settings) => (StatusCode::OK, Json(settings)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
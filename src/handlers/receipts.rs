@@ -0,0 +1,76 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    documents::receipt,
+    models::{AppStore, SchoolBranding, staff::StaffRole},
+};
+
+pub async fn get_payment_receipt_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let doc = match store.get_receipt_document(auth.school_id, id).await {
+        Ok(doc) => doc,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match receipt::render_pdf(&doc) {
+        Ok(pdf) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("inline; filename=\"{}\"", receipt::file_name(&doc)),
+                ),
+            ],
+            pdf,
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+/// Public: what the QR code on a printed receipt points at.
+pub async fn check_receipt_handler(
+    State(store): State<AppStore>,
+    Path(code): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.check_receipt(code).await {
+        Ok(check) => (StatusCode::OK, Json(check)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn set_branding_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<SchoolBranding>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.set_school_branding(auth.school_id, req).await {
+        Ok(school) => (StatusCode::OK, Json(school.branding)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_branding_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_school(auth.school_id).await {
+        Ok(school) => (StatusCode::OK, Json(school.branding)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
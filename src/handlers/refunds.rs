@@ -0,0 +1,86 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    models::{
+        AppStore,
+        ledger::PaymentMethod,
+        refunds::{CreateRefundRequest, RefundStatus},
+        staff::StaffRole,
+    },
+    services::payment_provider,
+};
+
+pub async fn create_refund_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateRefundRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let (refund, payment) = match store.request_refund(auth.school_id, id, &auth.username, req).await {
+        Ok(opened) => opened,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    // cash, transfers and POS are refunded by hand, so the refund is done once recorded
+    if payment.method != PaymentMethod::Online {
+        return match store.complete_refund(refund.id).await {
+            Ok(refund) => (StatusCode::CREATED, Json(refund)).into_response(),
+            Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+        };
+    }
+
+    // the account that took the money, even if the school has since moved
+    let provider = match payment_provider(&store, &payment).await {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = store.fail_refund(refund.id, &e.to_string()).await;
+            return (e.status_code(), Json(e.to_string())).into_response();
+        }
+    };
+
+    let receipt = match provider.refund(&payment.reference, Some(refund.amount_kobo)).await {
+        Ok(receipt) => receipt,
+        Err(e) => {
+            let _ = store.fail_refund(refund.id, &e.to_string()).await;
+            return (e.status_code(), Json(e.to_string())).into_response();
+        }
+    };
+
+    let refund = match store.set_provider_refund_id(refund.id, receipt.refund_id).await {
+        Ok(refund) => refund,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    // otherwise the provider's refund webhook settles it
+    let outcome = match receipt.status {
+        RefundStatus::Processed => store.complete_refund(refund.id).await,
+        RefundStatus::Failed => store.fail_refund(refund.id, "The provider declined the refund").await,
+        RefundStatus::Pending => Ok(refund),
+    };
+
+    match outcome {
+        Ok(refund) => (StatusCode::CREATED, Json(refund)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_payment_refunds_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.get_payment_refunds(auth.school_id, id).await {
+        Ok(refunds) => (StatusCode::OK, Json(refunds)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,115 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::{StatusCode, header},
+    response::{Html, IntoResponse},
+};
+use crate::{
+    auth::middleware::AuthSchool,
+    documents::{report_card, zip_files},
+    models::{AppStore, report_cards::SetReportCardRemarksRequest},
+};
+
+pub async fn set_report_card_remarks_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((id, term)): Path<(String, String)>,
+    Json(req): Json<SetReportCardRemarksRequest>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.set_report_card_remarks(auth.school_id, id, &term, req).await {
+        Ok(remarks) => (StatusCode::OK, Json(remarks)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_report_card_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((id, term)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let card = match store.build_report_card(auth.school_id, id, &term).await {
+        Ok(card) => card,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match report_card::render_pdf(&card) {
+        Ok(pdf) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("inline; filename=\"{}\"", report_card::file_name(&card)),
+                ),
+            ],
+            pdf,
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_report_card_preview_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((id, term)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.build_report_card(auth.school_id, id, &term).await {
+        Ok(card) => (StatusCode::OK, Html(report_card::render_html(&card))).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_class_report_cards_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path((class_name, term)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let cards = match store.build_class_report_cards(auth.school_id, &class_name, &term).await {
+        Ok(cards) => cards,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let mut files = Vec::with_capacity(cards.len());
+    for card in &cards {
+        match report_card::render_pdf(card) {
+            Ok(pdf) => files.push((report_card::file_name(card), pdf)),
+            Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+        }
+    }
+
+    match zip_files(files) {
+        Ok(archive) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/zip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"report-cards-{}-{}.zip\"",
+                        class_name.replace(['/', '\\', ' ', '"'], "_"),
+                        term.replace(['/', '\\', ' ', '"'], "_")
+                    ),
+                ),
+            ],
+            archive,
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,115 @@
+use axum::{
+    Json,
+    extract::{Extension, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    documents::reports::{self, ReportTable},
+    errors::AppError,
+    models::{
+        AppStore,
+        reports::{BalanceQuery, CollectionsQuery, ReportFormat},
+        staff::StaffRole,
+    },
+};
+
+const REPORT_ROLES: [StaffRole; 2] = [StaffRole::Owner, StaffRole::Bursar];
+
+/// Sends a report as JSON, or flattened to `table` as a CSV or PDF download.
+async fn respond<T: Serialize>(
+    store: &AppStore,
+    auth: &AuthSchool,
+    format: ReportFormat,
+    file_stem: &str,
+    report: Result<T, AppError>,
+    table: fn(&T) -> ReportTable,
+) -> Response {
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let (content_type, extension, body) = match format {
+        ReportFormat::Json => return (StatusCode::OK, Json(report)).into_response(),
+        ReportFormat::Csv => ("text/csv; charset=utf-8", "csv", reports::render_csv(&table(&report))),
+        ReportFormat::Pdf => {
+            let school = match store.get_school(auth.school_id).await {
+                Ok(school) => school,
+                Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+            };
+            ("application/pdf", "pdf", reports::render_pdf(&table(&report), &school.name))
+        }
+    };
+
+    match body {
+        Ok(body) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}.{}\"", file_stem, extension),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_collections_report_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<CollectionsQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&REPORT_ROLES) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let report = store.get_collections_report(auth.school_id, &query).await;
+    respond(&store, &auth, query.format, "collections", report, reports::collections_table).await
+}
+
+pub async fn get_outstanding_report_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<BalanceQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&REPORT_ROLES) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let report = store.get_outstanding_report(auth.school_id, &query).await;
+    respond(&store, &auth, query.format, "outstanding", report, reports::outstanding_table).await
+}
+
+pub async fn get_aging_report_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<BalanceQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&REPORT_ROLES) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let report = store.get_aging_report(auth.school_id, &query).await;
+    respond(&store, &auth, query.format, "aging", report, reports::aging_table).await
+}
+
+pub async fn get_debtors_report_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<BalanceQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&REPORT_ROLES) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let report = store.get_debtors_report(auth.school_id, &query).await;
+    respond(&store, &auth, query.format, "debtors", report, reports::debtors_table).await
+}
@@ -0,0 +1,109 @@
+use axum::{
+    Json,
+    extract::{Extension, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    auth::middleware::{AuthSchool, require_platform_key},
+    models::{
+        AppStore,
+        staff::StaffRole,
+        subaccounts::{SettlementQuery, Subaccount, SubaccountRequest},
+    },
+    services::{platform_commission_percent, platform_paystack},
+};
+
+/// Creates the school's Paystack subaccount on the platform account, or
+/// updates its bank details if it already has one.
+pub async fn set_subaccount_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<SubaccountRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+    if let Err(e) = req.validate() {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+    let paystack = match platform_paystack() {
+        Ok(p) => p,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    };
+
+    let percentage_charge = match platform_commission_percent() {
+        Ok(percent) => percent,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response(),
+    };
+    let existing = store.get_subaccount(auth.school_id).await.ok();
+    let result = match &existing {
+        Some(current) => {
+            paystack
+                .update_subaccount(&current.subaccount_code, &req, percentage_charge)
+                .await
+        }
+        None => paystack.create_subaccount(&req, percentage_charge).await,
+    };
+    let data = match result {
+        Ok(data) => data,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    let now = Utc::now();
+    let subaccount = Subaccount {
+        school_id: auth.school_id,
+        subaccount_code: data.subaccount_code,
+        business_name: req.business_name,
+        settlement_bank: req.settlement_bank,
+        account_number: req.account_number,
+        percentage_charge,
+        created_at: existing.map_or(now, |s| s.created_at),
+        updated_at: now,
+    };
+    (StatusCode::OK, Json(store.save_subaccount(subaccount).await)).into_response()
+}
+
+pub async fn get_subaccount_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_subaccount(auth.school_id).await {
+        Ok(subaccount) => (StatusCode::OK, Json(subaccount)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_settlements_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<SettlementQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.get_settlement_report(Some(auth.school_id), query).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+/// Settlement across every school, for the platform operator. Callers
+/// authenticate with `Authorization: Bearer <PLATFORM_API_KEY>`.
+pub async fn get_platform_settlements_handler(
+    State(store): State<AppStore>,
+    headers: HeaderMap,
+    Query(query): Query<SettlementQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_platform_key(&headers) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.get_settlement_report(None, query).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,130 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    documents::ical,
+    models::{
+        AppStore,
+        timetable::{CreateTeacherRequest, CreateTimetableSlotRequest},
+    },
+};
+
+#[derive(Deserialize)]
+pub struct CalendarQuery {
+    pub term: Option<String>,
+}
+
+// -- Teacher handlers --
+
+pub async fn create_teacher_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateTeacherRequest>,
+) -> impl IntoResponse {
+    match store.create_teacher(auth.school_id, req).await {
+        Ok(teacher) => (StatusCode::CREATED, Json(teacher)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_teachers_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+) -> impl IntoResponse {
+    match store.get_teachers(auth.school_id).await {
+        Ok(teachers) => (StatusCode::OK, Json(teachers)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+// -- Timetable handlers --
+
+pub async fn create_timetable_slot_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Json(req): Json<CreateTimetableSlotRequest>,
+) -> impl IntoResponse {
+    match store.create_timetable_slot(auth.school_id, req).await {
+        Ok(slot) => (StatusCode::CREATED, Json(slot)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn delete_timetable_slot_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.delete_timetable_slot(auth.school_id, id).await {
+        Ok(()) => (StatusCode::OK, Json("Timetable slot deleted!")).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_class_timetable_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(class_name): Path<String>,
+) -> impl IntoResponse {
+    match store.get_class_timetable(auth.school_id, &class_name).await {
+        Ok(entries) => (StatusCode::OK, Json(entries)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_teacher_timetable_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match store.get_teacher_timetable(auth.school_id, id).await {
+        Ok(entries) => (StatusCode::OK, Json(entries)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_teacher_calendar_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CalendarQuery>,
+) -> impl IntoResponse {
+    let teacher = match store.get_teacher(auth.school_id, id).await {
+        Ok(t) => t,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    let school = match store.get_school(auth.school_id).await {
+        Ok(s) => s,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    let term = match query.term {
+        Some(code) => match store.get_term(auth.school_id, &code).await {
+            Ok(t) => Some(t),
+            Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+        },
+        None => None,
+    };
+
+    match store.get_teacher_timetable(auth.school_id, id).await {
+        Ok(entries) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"timetable-{}.ics\"", teacher.id),
+                ),
+            ],
+            ical::render_teacher_calendar(&school.name, &teacher, &entries, term.as_ref()),
+        )
+            .into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
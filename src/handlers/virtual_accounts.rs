@@ -0,0 +1,139 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::AuthSchool,
+    errors::AppError,
+    models::{AppStore, staff::StaffRole, virtual_accounts::VirtualAccount},
+    services::school_paystack,
+};
+
+struct AccountHolder<'a> {
+    student_id: Option<Uuid>,
+    guardian_id: Option<Uuid>,
+    email: &'a str,
+    first_name: &'a str,
+    last_name: &'a str,
+    phone: Option<&'a str>,
+}
+
+async fn open_virtual_account(
+    store: &AppStore,
+    school_id: Uuid,
+    holder: AccountHolder<'_>,
+) -> Result<VirtualAccount, AppError> {
+    let paystack = school_paystack(store, school_id).await?;
+    let (customer_code, account) = paystack
+        .create_dedicated_account(holder.email, holder.first_name, holder.last_name, holder.phone)
+        .await?;
+
+    Ok(store
+        .save_virtual_account(VirtualAccount {
+            id: Uuid::new_v4(),
+            school_id,
+            student_id: holder.student_id,
+            guardian_id: holder.guardian_id,
+            customer_code,
+            account_number: account.account_number,
+            account_name: account.account_name,
+            bank_name: account.bank.name,
+            created_at: Utc::now(),
+        })
+        .await)
+}
+
+pub async fn create_student_virtual_account_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    let student = match store.get_student(auth.school_id, id).await {
+        Ok(student) => student,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    if let Ok(existing) = store.get_student_virtual_account(auth.school_id, id).await {
+        let e = AppError::Conflict(format!("Student already pays into account {}", existing.account_number));
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let holder = AccountHolder {
+        student_id: Some(student.id),
+        guardian_id: None,
+        email: &student.email,
+        first_name: &student.first_name,
+        last_name: &student.last_name,
+        phone: student.profile.guardian_phone.as_deref(),
+    };
+    match open_virtual_account(&store, auth.school_id, holder).await {
+        Ok(account) => (StatusCode::CREATED, Json(account)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn get_student_virtual_account_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = match store.resolve_student_id(auth.school_id, &id).await {
+        Ok(id) => id,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+
+    match store.get_student_virtual_account(auth.school_id, id).await {
+        Ok(account) => (StatusCode::OK, Json(account)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn create_guardian_virtual_account_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner, StaffRole::Bursar]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    let guardian = match store.get_guardian(auth.school_id, id).await {
+        Ok(guardian) => guardian,
+        Err(e) => return (e.status_code(), Json(e.to_string())).into_response(),
+    };
+    if let Some(existing) = store.get_guardian_virtual_account(auth.school_id, id).await {
+        let e = AppError::Conflict(format!("Guardian already pays into account {}", existing.account_number));
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+    let Some(email) = guardian.email.as_deref() else {
+        let e = AppError::invalid("email", "The guardian needs an email address for a virtual account");
+        return (e.status_code(), Json(e.to_string())).into_response();
+    };
+
+    // Paystack wants a first and last name
+    let (first_name, last_name) = guardian.name.trim().rsplit_once(' ').unwrap_or((guardian.name.trim(), ""));
+    let holder = AccountHolder {
+        student_id: None,
+        guardian_id: Some(guardian.id),
+        email,
+        first_name,
+        last_name,
+        phone: guardian.phone.as_deref(),
+    };
+    match open_virtual_account(&store, auth.school_id, holder).await {
+        Ok(account) => (StatusCode::CREATED, Json(account)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,129 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::middleware::{AuthSchool, require_platform_key},
+    logger::AppLogger,
+    models::{
+        AppStore,
+        staff::StaffRole,
+        webhooks::{WebhookEventQuery, WebhookEventStatus},
+    },
+    services::{PaymentProvider, provider_for},
+};
+
+/// Checks the provider's signature, stores the event and processes it once.
+/// `school_id` is set when the event arrived on a school's own endpoint.
+pub async fn receive_webhook(
+    store: &AppStore,
+    provider: &dyn PaymentProvider,
+    school_id: Option<Uuid>,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> StatusCode {
+    if !provider.verify_signature(headers, body) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let parsed = match provider.parse_event(body) {
+        Ok(parsed) => parsed,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    // providers retry until they get a 200, so once an event is stored we
+    // acknowledge it; failures are kept on the event for replay
+    let (event, is_new) = store.record_webhook_event(provider.kind(), school_id, parsed).await;
+    if is_new {
+        match store.process_webhook_event(event.id).await {
+            Ok(processed) if processed.status == WebhookEventStatus::Failed => AppLogger::warn(&format!(
+                "Webhook event {} failed: {}",
+                processed.id,
+                processed.error.unwrap_or_default()
+            )),
+            Ok(_) => {}
+            Err(e) => AppLogger::error(&format!("Could not process webhook event {}: {}", event.id, e)),
+        }
+    }
+
+    StatusCode::OK
+}
+
+/// Webhooks for schools that collect through their own provider account.
+pub async fn school_webhook_handler(
+    State(store): State<AppStore>,
+    Path(school_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    match store.get_payment_settings(school_id).await {
+        Ok(settings) => {
+            let provider = provider_for(&settings);
+            receive_webhook(&store, provider.as_ref(), Some(school_id), &headers, &body).await
+        }
+        Err(e) => e.status_code(),
+    }
+}
+
+pub async fn get_webhook_events_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Query(query): Query<WebhookEventQuery>,
+) -> impl IntoResponse {
+    match store.get_webhook_events(Some(auth.school_id), query).await {
+        Ok(events) => (StatusCode::OK, Json(events)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn replay_webhook_event_handler(
+    State(store): State<AppStore>,
+    Extension(auth): Extension<AuthSchool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth.require_role(&[StaffRole::Owner]) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.replay_webhook_event(Some(auth.school_id), id).await {
+        Ok(event) => (StatusCode::OK, Json(event)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+/// Events on the shared endpoint that matched no school's payment, for the
+/// platform operator to look into.
+pub async fn get_platform_webhook_events_handler(
+    State(store): State<AppStore>,
+    headers: HeaderMap,
+    Query(query): Query<WebhookEventQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_platform_key(&headers) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.get_webhook_events(None, query).await {
+        Ok(events) => (StatusCode::OK, Json(events)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
+
+pub async fn replay_platform_webhook_event_handler(
+    State(store): State<AppStore>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = require_platform_key(&headers) {
+        return (e.status_code(), Json(e.to_string())).into_response();
+    }
+
+    match store.replay_webhook_event(None, id).await {
+        Ok(event) => (StatusCode::OK, Json(event)).into_response(),
+        Err(e) => (e.status_code(), Json(e.to_string())).into_response(),
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::{config::get_env_vars, logger::AppLogger, models::AppStore};
+
+const DEFAULT_INTERVAL_SECS: u64 = 3600;
+
+/// Periodically marks overdue invoices and charges the penalties each
+/// school's late fee policy calls for.
+pub fn spawn(store: AppStore) {
+    let interval_secs: u64 = get_env_vars("LATE_FEE_INTERVAL_SECS".to_string()).unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+
+            let today = Utc::now().date_naive();
+            for school_id in store.get_school_ids().await {
+                if let Err(e) = store.apply_late_fees(school_id, today, "system").await {
+                    AppLogger::warn(&format!("Could not apply late fees for school {}: {}", school_id, e));
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,3 @@
+pub mod late_fees;
+pub mod reconciliation;
+pub mod reminders;
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::{
+    config::get_env_vars,
+    errors::AppError,
+    logger::AppLogger,
+    models::{AppStore, ledger::Payment},
+    services::{PaymentProvider, payment_provider},
+};
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const DEFAULT_PENDING_MINUTES: i64 = 15;
+
+/// Asks the gateway for the real outcome of a payment and records it. Safe
+/// to call repeatedly: settled payments are left as they are.
+pub async fn verify_payment(
+    store: &AppStore,
+    provider: &dyn PaymentProvider,
+    reference: &str,
+) -> Result<Payment, AppError> {
+    let verified = provider.verify(reference).await?;
+    store
+        .apply_gateway_outcome(&verified.reference, verified.status, verified.amount_kobo, &verified.currency)
+        .await
+}
+
+/// Periodically verifies online payments that have been pending for longer
+/// than PAYMENT_PENDING_MINUTES, in case their webhook never arrived.
+pub fn spawn(store: AppStore) {
+    let interval_secs: u64 =
+        get_env_vars("PAYMENT_RECONCILE_INTERVAL_SECS".to_string()).unwrap_or(DEFAULT_INTERVAL_SECS);
+    let pending_minutes: i64 =
+        get_env_vars("PAYMENT_PENDING_MINUTES".to_string()).unwrap_or(DEFAULT_PENDING_MINUTES);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+
+            let started_before = Utc::now() - chrono::Duration::minutes(pending_minutes);
+            for payment in store.get_stale_pending_payments(started_before).await {
+                let outcome = match payment_provider(&store, &payment).await {
+                    Ok(provider) => verify_payment(&store, provider.as_ref(), &payment.reference).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = outcome {
+                    AppLogger::warn(&format!("Could not reconcile payment {}: {}", payment.reference, e));
+                }
+            }
+        }
+    });
+}
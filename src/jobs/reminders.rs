@@ -0,0 +1,135 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    time::Duration,
+};
+
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::{
+    config::get_env_vars,
+    errors::AppError,
+    logger::AppLogger,
+    models::{
+        AppStore,
+        ledger::amount_due_next,
+        money::Money,
+        payment_links::CreatePaymentLinkRequest,
+        reminders::{DeliveryStatus, DueReminder, ReminderChannel, ReminderDelivery},
+    },
+    services::notifications::Notifier,
+};
+
+const DEFAULT_INTERVAL_SECS: u64 = 3600;
+
+fn compose(
+    reminder: &DueReminder,
+    school_name: &str,
+    today: NaiveDate,
+    payment_link: Option<&str>,
+) -> (String, String) {
+    let student = &reminder.student;
+    let invoice = &reminder.invoice;
+    let greeting = match &reminder.recipient_name {
+        Some(name) => format!("Dear {},", name),
+        None => "Dear Parent/Guardian,".to_string(),
+    };
+    let when = if reminder.due_date < today {
+        format!("was due on {}", reminder.due_date.format("%d %b %Y"))
+    } else {
+        format!("is due on {}", reminder.due_date.format("%d %b %Y"))
+    };
+
+    // what the payment link asks for: the next installment, or the whole balance
+    let due_kobo = amount_due_next(invoice);
+    let mut body = format!(
+        "{} {} {}'s {} school fees payment of {} {}.",
+        greeting,
+        student.first_name,
+        student.last_name,
+        invoice.term_code,
+        Money::from_minor(due_kobo, invoice.currency),
+        when
+    );
+    if invoice.balance_kobo > due_kobo as i64 {
+        body.push_str(&format!(
+            " The full outstanding balance is {}.",
+            Money::new(invoice.balance_kobo, invoice.currency)
+        ));
+    }
+    if let Some(link) = payment_link {
+        body.push_str(&format!(" Pay online: {}", link));
+    }
+    body.push_str(&format!(" - {}", school_name));
+
+    let subject = format!("{}: school fees reminder for {}", school_name, student.first_name);
+    (subject, body)
+}
+
+/// Sends every reminder due for a school today and logs each attempt.
+/// Guardians reminded about the same invoice share one payment link.
+pub async fn send_due_reminders(
+    store: &AppStore,
+    notifier: &Notifier,
+    school_id: Uuid,
+    today: NaiveDate,
+) -> Result<Vec<ReminderDelivery>, AppError> {
+    let school = store.get_school(school_id).await?;
+    let mut links: HashMap<Uuid, Option<String>> = HashMap::new();
+    let mut deliveries = Vec::new();
+
+    for reminder in store.get_due_reminders(school_id, today).await? {
+        // another run may have picked it up since the list was drawn
+        let Some(mut delivery) = store.claim_reminder(&reminder, today).await else {
+            continue;
+        };
+        let invoice = &reminder.invoice;
+        if let Entry::Vacant(slot) = links.entry(invoice.id) {
+            // a payment link only opens a checkout once someone presses Pay
+            let req = CreatePaymentLinkRequest { invoice_id: Some(invoice.id), ..Default::default() };
+            let link = match store.create_payment_link(school_id, invoice.student_id, "system", req).await {
+                Ok(link) => Some(link.url),
+                Err(e) => {
+                    // a reminder without a link still beats no reminder
+                    AppLogger::warn(&format!("Could not create payment link for invoice {}: {}", invoice.id, e));
+                    None
+                }
+            };
+            slot.insert(link);
+        }
+        let payment_link = links.get(&invoice.id).cloned().flatten();
+
+        let (subject, message) = compose(&reminder, &school.name, today, payment_link.as_deref());
+        let outcome = match reminder.channel {
+            ReminderChannel::Email => notifier.send_email(&reminder.recipient, &subject, &message).await,
+            ReminderChannel::Sms => notifier.send_sms(&reminder.recipient, &message).await,
+        };
+
+        delivery.message = message;
+        delivery.payment_link = payment_link;
+        delivery.status = if outcome.is_ok() { DeliveryStatus::Sent } else { DeliveryStatus::Failed };
+        delivery.error = outcome.err().map(|e| e.to_string());
+        deliveries.push(store.record_reminder_delivery(delivery).await);
+    }
+    Ok(deliveries)
+}
+
+/// Periodically sends the fee reminders each school's schedule calls for.
+pub fn spawn(store: AppStore) {
+    let interval_secs: u64 = get_env_vars("REMINDER_INTERVAL_SECS".to_string()).unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let notifier = Notifier::from_env();
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+
+            let today = Utc::now().date_naive();
+            for school_id in store.get_school_ids().await {
+                if let Err(e) = send_due_reminders(&store, &notifier, school_id, today).await {
+                    AppLogger::warn(&format!("Could not send reminders for school {}: {}", school_id, e));
+                }
+            }
+        }
+    });
+}
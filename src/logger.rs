@@ -24,9 +24,9 @@ impl AppLogger {
     pub fn info(message: &str) {
         tracing::info!("{}", message);
     }
-    // pub fn warn(message: &str) {
-    //     tracing::warn!("{}", message);
-    // }
+    pub fn warn(message: &str) {
+        tracing::warn!("{}", message);
+    }
     // pub fn debug(message: &str) {
     //     tracing::debug!("{}", message);
     // }
@@ -1,11 +1,14 @@
 mod auth;
 mod config;
+mod documents;
 mod errors;
 mod handlers;
+mod jobs;
 mod logger;
 mod models;
 mod routes;
 mod services;
+mod statements;
 
 use std::net::{Ipv6Addr, SocketAddr};
 use models::AppStore;
@@ -21,11 +24,16 @@ async fn main() {
     let port: u16 = get_env_vars::<u16>("PORT".to_string()).unwrap_or(8080);
     let listening_address = SocketAddr::from((Ipv6Addr::LOCALHOST, port));
     let store = AppStore::new();
+    jobs::reconciliation::spawn(store.clone());
+    jobs::late_fees::spawn(store.clone());
+    jobs::reminders::spawn(store.clone());
     let app = create_router(store);
     let binder = TcpListener::bind(listening_address)
         .await
         .expect("Failed to bind address");
     AppLogger::info(&format!("Server listening at {}", listening_address));
-    axum::serve(binder, app).await.unwrap();
+    if let Err(e) = axum::serve(binder, app).await {
+        AppLogger::error(&format!("Server stopped: {}", e));
+    }
 }
 
@@ -0,0 +1,611 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::AppStore;
+
+// ---- Term ----
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AssessmentComponent {
+    pub name: String,
+    pub weight: u32, // maximum score for this component, all weights add up to 100
+}
+
+#[derive(Clone, Serialize)]
+pub struct Term {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub code: String, // e.g. "2026-T1", used in URLs
+    pub name: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub assessment_components: Vec<AssessmentComponent>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateTermRequest {
+    pub code: String,
+    pub name: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+}
+
+#[derive(Deserialize)]
+pub struct SetAssessmentComponentsRequest {
+    pub components: Vec<AssessmentComponent>,
+}
+
+// ---- Subject ----
+
+#[derive(Clone, Serialize)]
+pub struct Subject {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub name: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateSubjectRequest {
+    pub name: String,
+    pub code: String,
+}
+
+// ---- Grading scale ----
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct GradeBand {
+    pub grade: String,
+    pub min_score: f64, // lowest total (out of 100) that earns this grade
+    pub remark: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetGradingScaleRequest {
+    pub bands: Vec<GradeBand>,
+}
+
+// ---- Scores ----
+
+#[derive(Clone, Serialize)]
+pub struct ScoreEntry {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub subject_id: Uuid,
+    pub term_code: String,
+    pub component: String,
+    pub score: f64,
+    pub recorded_by: String,
+    pub recorded_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct StudentScoreInput {
+    pub student_id: Uuid,
+    pub component: String,
+    pub score: f64,
+}
+
+#[derive(Deserialize)]
+pub struct RecordScoresRequest {
+    pub scores: Vec<StudentScoreInput>,
+}
+
+// ---- Computed results ----
+
+#[derive(Clone, Serialize)]
+pub struct SubjectResult {
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub scores: BTreeMap<String, f64>, // component name -> score
+    pub total: f64,
+    pub grade: Option<String>,
+    pub remark: Option<String>,
+    pub position: usize,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SubjectBroadsheet {
+    pub class_name: String,
+    pub subject: Subject,
+    pub term_code: String,
+    pub components: Vec<AssessmentComponent>,
+    pub results: Vec<SubjectResult>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SubjectSummary {
+    pub subject_id: Uuid,
+    pub subject_name: String,
+    pub scores: BTreeMap<String, f64>,
+    pub total: f64,
+    pub grade: Option<String>,
+    pub remark: Option<String>,
+    pub position: usize, // position in class for this subject
+}
+
+#[derive(Clone, Serialize)]
+pub struct ClassResult {
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub subjects: Vec<SubjectSummary>,
+    pub total: f64,
+    pub average: f64,
+    pub position: usize,
+}
+
+/// Standard competition ranking: equal totals share a position and the next
+/// position is skipped (1, 2, 2, 4).
+fn assign_positions<T>(items: &mut [T], total: impl Fn(&T) -> f64, mut set: impl FnMut(&mut T, usize)) {
+    items.sort_by(|a, b| total(b).total_cmp(&total(a)));
+    let mut previous: Option<f64> = None;
+    let mut position = 0;
+    for (index, item) in items.iter_mut().enumerate() {
+        let value = total(item);
+        if previous != Some(value) {
+            position = index + 1;
+            previous = Some(value);
+        }
+        set(item, position);
+    }
+}
+
+fn grade_for(scale: &[GradeBand], total: f64) -> Option<&GradeBand> {
+    // scale is kept sorted from the highest band down
+    scale.iter().find(|band| total >= band.min_score)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+impl AppStore {
+    // -- Term methods --
+
+    pub async fn create_term(&self, school_id: Uuid, req: CreateTermRequest) -> Result<Term, AppError> {
+        if req.code.trim().is_empty() {
+            return Err(AppError::invalid("code", "Term code cannot be empty"));
+        }
+        if req.ends_on < req.starts_on {
+            return Err(AppError::invalid("ends_on", "Term cannot end before it starts"));
+        }
+
+        let mut terms = self.terms.lock().await;
+        let taken = terms
+            .values()
+            .any(|t| t.school_id == school_id && t.code == req.code);
+        if taken {
+            return Err(AppError::Conflict("Term code already exists".to_string()));
+        }
+
+        let term = Term {
+            id: Uuid::new_v4(),
+            school_id,
+            code: req.code,
+            name: req.name,
+            starts_on: req.starts_on,
+            ends_on: req.ends_on,
+            assessment_components: Vec::new(),
+        };
+
+        terms.insert(term.id.to_string(), term.clone());
+        Ok(term)
+    }
+
+    pub async fn get_terms(&self, school_id: Uuid) -> Result<Vec<Term>, AppError> {
+        let terms = self.terms.lock().await;
+        let mut list: Vec<Term> = terms
+            .values()
+            .filter(|t| t.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|t| t.starts_on);
+        Ok(list)
+    }
+
+    pub async fn get_term(&self, school_id: Uuid, code: &str) -> Result<Term, AppError> {
+        let terms = self.terms.lock().await;
+        terms
+            .values()
+            .find(|t| t.school_id == school_id && t.code == code)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    /// The term whose date range contains today.
+    pub async fn get_current_term(&self, school_id: Uuid) -> Result<Term, AppError> {
+        let today = Utc::now().date_naive();
+        let terms = self.terms.lock().await;
+        terms
+            .values()
+            .find(|t| t.school_id == school_id && t.starts_on <= today && today <= t.ends_on)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn set_assessment_components(
+        &self,
+        school_id: Uuid,
+        code: &str,
+        req: SetAssessmentComponentsRequest,
+    ) -> Result<Term, AppError> {
+        let mut names = HashSet::new();
+        for component in &req.components {
+            if component.name.trim().is_empty() {
+                return Err(AppError::invalid("components", "Component name cannot be empty"));
+            }
+            if component.weight == 0 {
+                return Err(AppError::invalid("components", "Component weight must be greater than zero"));
+            }
+            if !names.insert(component.name.as_str()) {
+                return Err(AppError::invalid("components", "Component names must be unique"));
+            }
+        }
+        let total_weight: u32 = req.components.iter().map(|c| c.weight).sum();
+        if total_weight != 100 {
+            return Err(AppError::invalid("components", "Component weights must add up to 100"));
+        }
+
+        let mut terms = self.terms.lock().await;
+        let term = terms
+            .values_mut()
+            .find(|t| t.school_id == school_id && t.code == code)
+            .ok_or(AppError::NotFound)?;
+
+        term.assessment_components = req.components;
+        Ok(term.clone())
+    }
+
+    // -- Subject methods --
+
+    pub async fn create_subject(&self, school_id: Uuid, req: CreateSubjectRequest) -> Result<Subject, AppError> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::invalid("name", "Subject name cannot be empty"));
+        }
+
+        let mut subjects = self.subjects.lock().await;
+        let taken = subjects
+            .values()
+            .any(|s| s.school_id == school_id && s.code.eq_ignore_ascii_case(&req.code));
+        if taken {
+            return Err(AppError::Conflict("Subject code already exists".to_string()));
+        }
+
+        let subject = Subject {
+            id: Uuid::new_v4(),
+            school_id,
+            name: req.name,
+            code: req.code,
+        };
+
+        subjects.insert(subject.id.to_string(), subject.clone());
+        Ok(subject)
+    }
+
+    pub async fn get_subjects(&self, school_id: Uuid) -> Result<Vec<Subject>, AppError> {
+        let subjects = self.subjects.lock().await;
+        let mut list: Vec<Subject> = subjects
+            .values()
+            .filter(|s| s.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    pub async fn get_subject(&self, school_id: Uuid, id: Uuid) -> Result<Subject, AppError> {
+        let subjects = self.subjects.lock().await;
+        subjects
+            .values()
+            .find(|s| s.id == id && s.school_id == school_id)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    // -- Grading scale methods --
+
+    pub async fn set_grading_scale(
+        &self,
+        school_id: Uuid,
+        req: SetGradingScaleRequest,
+    ) -> Result<Vec<GradeBand>, AppError> {
+        if req.bands.is_empty() {
+            return Err(AppError::invalid("bands", "Grading scale needs at least one band"));
+        }
+        let mut grades = HashSet::new();
+        for band in &req.bands {
+            if !(0.0..=100.0).contains(&band.min_score) {
+                return Err(AppError::invalid("bands", "Minimum score must be between 0 and 100"));
+            }
+            if !grades.insert(band.grade.as_str()) {
+                return Err(AppError::invalid("bands", "Grades must be unique"));
+            }
+        }
+        if !req.bands.iter().any(|b| b.min_score == 0.0) {
+            return Err(AppError::invalid("bands", "Lowest band must start at 0 so every score gets a grade"));
+        }
+
+        let mut bands = req.bands;
+        bands.sort_by(|a, b| b.min_score.total_cmp(&a.min_score));
+
+        self.grading_scales
+            .lock()
+            .await
+            .insert(school_id.to_string(), bands.clone());
+        Ok(bands)
+    }
+
+    pub async fn get_grading_scale(&self, school_id: Uuid) -> Result<Vec<GradeBand>, AppError> {
+        let scales = self.grading_scales.lock().await;
+        Ok(scales.get(&school_id.to_string()).cloned().unwrap_or_default())
+    }
+
+    // -- Score methods --
+
+    pub async fn record_scores(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+        subject_id: Uuid,
+        term_code: &str,
+        recorded_by: &str,
+        req: RecordScoresRequest,
+    ) -> Result<SubjectBroadsheet, AppError> {
+        let term = self.get_term(school_id, term_code).await?;
+        self.get_subject(school_id, subject_id).await?;
+
+        if term.assessment_components.is_empty() {
+            return Err(AppError::invalid("term", "Assessment components are not configured for this term"));
+        }
+
+        let class_students: HashSet<Uuid> = self
+            .get_class_students(school_id, class_name)
+            .await?
+            .iter()
+            .map(|s| s.id)
+            .collect();
+
+        // validate the whole batch before writing anything
+        for input in &req.scores {
+            if !class_students.contains(&input.student_id) {
+                return Err(AppError::invalid(
+                    "student_id",
+                    &format!("Student {} is not in class {}", input.student_id, class_name),
+                ));
+            }
+            let component = term
+                .assessment_components
+                .iter()
+                .find(|c| c.name == input.component)
+                .ok_or_else(|| {
+                    AppError::invalid("component", &format!("Unknown component {}", input.component))
+                })?;
+            if !(0.0..=component.weight as f64).contains(&input.score) {
+                return Err(AppError::invalid(
+                    "score",
+                    &format!("{} scores must be between 0 and {}", component.name, component.weight),
+                ));
+            }
+        }
+
+        {
+            let mut scores = self.scores.lock().await;
+            for input in req.scores {
+                let existing = scores.values_mut().find(|e| {
+                    e.school_id == school_id
+                        && e.student_id == input.student_id
+                        && e.subject_id == subject_id
+                        && e.term_code == term.code
+                        && e.component == input.component
+                });
+
+                match existing {
+                    Some(entry) => {
+                        entry.score = input.score;
+                        entry.recorded_by = recorded_by.to_string();
+                        entry.recorded_at = Utc::now();
+                    }
+                    None => {
+                        let entry = ScoreEntry {
+                            id: Uuid::new_v4(),
+                            school_id,
+                            student_id: input.student_id,
+                            subject_id,
+                            term_code: term.code.clone(),
+                            component: input.component,
+                            score: input.score,
+                            recorded_by: recorded_by.to_string(),
+                            recorded_at: Utc::now(),
+                        };
+                        scores.insert(entry.id.to_string(), entry);
+                    }
+                }
+            }
+        }
+
+        self.get_subject_broadsheet(school_id, class_name, subject_id, term_code)
+            .await
+    }
+
+    pub async fn get_subject_broadsheet(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+        subject_id: Uuid,
+        term_code: &str,
+    ) -> Result<SubjectBroadsheet, AppError> {
+        let term = self.get_term(school_id, term_code).await?;
+        let subject = self.get_subject(school_id, subject_id).await?;
+        let scale = self.get_grading_scale(school_id).await?;
+        let students = self.get_class_students(school_id, class_name).await?;
+
+        let scores = self.scores.lock().await;
+        let mut results: Vec<SubjectResult> = students
+            .iter()
+            .map(|student| {
+                let student_scores: BTreeMap<String, f64> = scores
+                    .values()
+                    .filter(|e| {
+                        e.school_id == school_id
+                            && e.student_id == student.id
+                            && e.subject_id == subject_id
+                            && e.term_code == term.code
+                    })
+                    .map(|e| (e.component.clone(), e.score))
+                    .collect();
+                let total = round2(student_scores.values().sum());
+                let band = grade_for(&scale, total);
+
+                SubjectResult {
+                    student_id: student.id,
+                    student_name: format!("{} {}", student.first_name, student.last_name),
+                    scores: student_scores,
+                    total,
+                    grade: band.map(|b| b.grade.clone()),
+                    remark: band.map(|b| b.remark.clone()),
+                    position: 0,
+                }
+            })
+            .collect();
+
+        assign_positions(&mut results, |r| r.total, |r, p| r.position = p);
+
+        Ok(SubjectBroadsheet {
+            class_name: class_name.to_string(),
+            subject,
+            term_code: term.code,
+            components: term.assessment_components,
+            results,
+        })
+    }
+
+    /// Overall results for every student in a class: one summary per subject
+    /// that has at least one score, plus average and class position.
+    pub async fn get_class_results(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+        term_code: &str,
+    ) -> Result<Vec<ClassResult>, AppError> {
+        let term = self.get_term(school_id, term_code).await?;
+        let subjects = self.get_subjects(school_id).await?;
+        let students = self.get_class_students(school_id, class_name).await?;
+        let student_ids: HashSet<Uuid> = students.iter().map(|s| s.id).collect();
+
+        // subjects offered by this class are the ones somebody has scores in
+        let offered: Vec<Subject> = {
+            let scores = self.scores.lock().await;
+            subjects
+                .into_iter()
+                .filter(|subject| {
+                    scores.values().any(|e| {
+                        e.school_id == school_id
+                            && e.subject_id == subject.id
+                            && e.term_code == term.code
+                            && student_ids.contains(&e.student_id)
+                    })
+                })
+                .collect()
+        };
+
+        let mut results: Vec<ClassResult> = students
+            .iter()
+            .map(|student| ClassResult {
+                student_id: student.id,
+                student_name: format!("{} {}", student.first_name, student.last_name),
+                subjects: Vec::new(),
+                total: 0.0,
+                average: 0.0,
+                position: 0,
+            })
+            .collect();
+
+        for subject in &offered {
+            let sheet = self
+                .get_subject_broadsheet(school_id, class_name, subject.id, term_code)
+                .await?;
+            for row in sheet.results {
+                if let Some(result) = results.iter_mut().find(|r| r.student_id == row.student_id) {
+                    result.subjects.push(SubjectSummary {
+                        subject_id: subject.id,
+                        subject_name: subject.name.clone(),
+                        scores: row.scores,
+                        total: row.total,
+                        grade: row.grade,
+                        remark: row.remark,
+                        position: row.position,
+                    });
+                }
+            }
+        }
+
+        for result in results.iter_mut() {
+            result.total = round2(result.subjects.iter().map(|s| s.total).sum());
+            if !result.subjects.is_empty() {
+                result.average = round2(result.total / result.subjects.len() as f64);
+            }
+        }
+
+        assign_positions(&mut results, |r| r.average, |r, p| r.position = p);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(totals: &[f64]) -> Vec<(f64, usize)> {
+        let mut ranked: Vec<(f64, usize)> = totals.iter().map(|&t| (t, 0)).collect();
+        assign_positions(&mut ranked, |r| r.0, |r, position| r.1 = position);
+        ranked
+    }
+
+    fn scale() -> Vec<GradeBand> {
+        [("A", 70.0, "Excellent"), ("B", 60.0, "Very good"), ("C", 50.0, "Credit"), ("P", 40.0, "Pass")]
+            .into_iter()
+            .map(|(grade, min_score, remark)| GradeBand {
+                grade: grade.to_string(),
+                min_score,
+                remark: remark.to_string(),
+            })
+            .collect()
+    }
+
+    fn grade(total: f64) -> Option<String> {
+        grade_for(&scale(), total).map(|band| band.grade.clone())
+    }
+
+    #[test]
+    fn tied_totals_share_a_position_and_skip_the_next() {
+        assert_eq!(positions(&[55.0, 81.5, 81.5]), vec![(81.5, 1), (81.5, 1), (55.0, 3)]);
+        assert_eq!(
+            positions(&[40.0, 72.0, 90.0, 72.0]),
+            vec![(90.0, 1), (72.0, 2), (72.0, 2), (40.0, 4)]
+        );
+        assert_eq!(positions(&[60.0, 60.0, 60.0]), vec![(60.0, 1), (60.0, 1), (60.0, 1)]);
+    }
+
+    #[test]
+    fn a_band_starts_at_its_minimum_score() {
+        assert_eq!(grade(100.0).as_deref(), Some("A"));
+        assert_eq!(grade(70.0).as_deref(), Some("A"));
+        assert_eq!(grade(69.99).as_deref(), Some("B"));
+        assert_eq!(grade(60.0).as_deref(), Some("B"));
+        assert_eq!(grade(50.0).as_deref(), Some("C"));
+        assert_eq!(grade(40.0).as_deref(), Some("P"));
+    }
+
+    #[test]
+    fn totals_below_the_lowest_band_get_no_grade() {
+        assert_eq!(grade(39.99), None);
+        assert_eq!(grade(0.0), None);
+        assert!(grade_for(&[], 85.0).is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::AppStore;
+
+/// How a school's admission numbers are laid out, e.g. `GHS/2026/0042` is
+/// prefix "GHS", separator "/", the admission year and a 4 digit sequence.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AdmissionNumberFormat {
+    pub prefix: String,
+    pub separator: String,
+    pub include_year: bool, // when set, the sequence restarts every year
+    pub padding: u8,
+}
+
+impl Default for AdmissionNumberFormat {
+    fn default() -> Self {
+        Self {
+            prefix: "ADM".to_string(),
+            separator: "/".to_string(),
+            include_year: true,
+            padding: 4,
+        }
+    }
+}
+
+impl AdmissionNumberFormat {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.prefix.trim().is_empty() {
+            return Err(AppError::invalid("prefix", "Prefix cannot be empty"));
+        }
+        if self.prefix.chars().any(char::is_whitespace) || self.separator.chars().any(char::is_whitespace) {
+            return Err(AppError::invalid("prefix", "Prefix and separator cannot contain spaces"));
+        }
+        if !(1..=10).contains(&self.padding) {
+            return Err(AppError::invalid("padding", "Padding must be between 1 and 10 digits"));
+        }
+        Ok(())
+    }
+
+    pub fn sequence_key(&self, school_id: Uuid, year: i32) -> String {
+        if self.include_year {
+            format!("{}:{}", school_id, year)
+        } else {
+            school_id.to_string()
+        }
+    }
+
+    pub fn render(&self, year: i32, sequence: u32) -> String {
+        let number = format!("{:0width$}", sequence, width = self.padding as usize);
+        if self.include_year {
+            format!("{}{sep}{}{sep}{}", self.prefix, year, number, sep = self.separator)
+        } else {
+            format!("{}{}{}", self.prefix, self.separator, number)
+        }
+    }
+}
+
+impl AppStore {
+    pub async fn set_admission_format(
+        &self,
+        school_id: Uuid,
+        format: AdmissionNumberFormat,
+    ) -> Result<AdmissionNumberFormat, AppError> {
+        format.validate()?;
+        self.admission_formats
+            .lock()
+            .await
+            .insert(school_id.to_string(), format.clone());
+        Ok(format)
+    }
+
+    pub async fn get_admission_format(&self, school_id: Uuid) -> Result<AdmissionNumberFormat, AppError> {
+        let formats = self.admission_formats.lock().await;
+        Ok(formats.get(&school_id.to_string()).cloned().unwrap_or_default())
+    }
+
+    /// Resolves a `/students/{id}` path segment, which may be either the
+    /// student's UUID or their admission number.
+    pub async fn resolve_student_id(&self, school_id: Uuid, key: &str) -> Result<Uuid, AppError> {
+        if let Ok(id) = key.parse::<Uuid>() {
+            return Ok(id);
+        }
+
+        let students = self.students.lock().await;
+        students
+            .values()
+            .find(|s| s.school_id == school_id && s.admission_number.eq_ignore_ascii_case(key))
+            .map(|s| s.id)
+            .ok_or(AppError::NotFound)
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::AppStore;
+
+/// Absence percentage above which a student is reported as a chronic absentee
+/// when the request does not specify its own threshold.
+pub const DEFAULT_ABSENCE_THRESHOLD: f64 = 10.0;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum AttendanceStatus {
+    Present,
+    Absent,
+    Late,
+    Excused,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AttendanceCorrection {
+    pub previous_status: AttendanceStatus,
+    pub new_status: AttendanceStatus,
+    pub reason: String,
+    pub corrected_by: String,
+    pub corrected_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AttendanceRecord {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub class_name: String,
+    pub date: NaiveDate,
+    pub period: Option<u8>, // None means the daily register
+    pub status: AttendanceStatus,
+    pub note: Option<String>,
+    pub marked_by: String,
+    pub marked_at: DateTime<Utc>,
+    pub corrections: Vec<AttendanceCorrection>, // audit trail, oldest first
+}
+
+#[derive(Deserialize)]
+pub struct AttendanceMark {
+    pub student_id: Uuid,
+    pub status: AttendanceStatus,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MarkAttendanceRequest {
+    pub date: NaiveDate,
+    pub period: Option<u8>,
+    pub records: Vec<AttendanceMark>,
+}
+
+#[derive(Deserialize)]
+pub struct CorrectAttendanceRequest {
+    pub status: AttendanceStatus,
+    pub reason: String,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AttendanceRegisterQuery {
+    pub date: NaiveDate,
+    pub period: Option<u8>,
+}
+
+#[derive(Deserialize)]
+pub struct AbsenteesQuery {
+    pub term: String,
+    pub threshold: Option<f64>, // absence percentage
+    pub class_name: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AttendanceSummary {
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub class_name: Option<String>,
+    pub term_code: String,
+    pub days_recorded: u32,
+    pub present: u32,
+    pub late: u32,
+    pub absent: u32,
+    pub excused: u32,
+    pub attendance_rate: f64, // percentage of non-excused days attended
+    pub absence_rate: f64,
+}
+
+impl AttendanceSummary {
+    fn from_records(
+        student_id: Uuid,
+        student_name: String,
+        class_name: Option<String>,
+        term_code: String,
+        records: &[&AttendanceRecord],
+    ) -> Self {
+        let count = |status: AttendanceStatus| records.iter().filter(|r| r.status == status).count() as u32;
+        let present = count(AttendanceStatus::Present);
+        let late = count(AttendanceStatus::Late);
+        let absent = count(AttendanceStatus::Absent);
+        let excused = count(AttendanceStatus::Excused);
+
+        // excused days are left out of the denominator altogether
+        let counted = present + late + absent;
+        let (attendance_rate, absence_rate) = if counted == 0 {
+            (0.0, 0.0)
+        } else {
+            let rate = |n: u32| (n as f64 / counted as f64 * 10_000.0).round() / 100.0;
+            (rate(present + late), rate(absent))
+        };
+
+        Self {
+            student_id,
+            student_name,
+            class_name,
+            term_code,
+            days_recorded: records.len() as u32,
+            present,
+            late,
+            absent,
+            excused,
+            attendance_rate,
+            absence_rate,
+        }
+    }
+}
+
+impl AppStore {
+    pub async fn mark_attendance(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+        marked_by: &str,
+        req: MarkAttendanceRequest,
+    ) -> Result<Vec<AttendanceRecord>, AppError> {
+        if req.date > Utc::now().date_naive() {
+            return Err(AppError::invalid("date", "Attendance cannot be marked for a future date"));
+        }
+
+        let class_students: HashSet<Uuid> = self
+            .get_class_students(school_id, class_name)
+            .await?
+            .iter()
+            .map(|s| s.id)
+            .collect();
+
+        let mut seen = HashSet::new();
+        for mark in &req.records {
+            if !class_students.contains(&mark.student_id) {
+                return Err(AppError::invalid(
+                    "student_id",
+                    &format!("Student {} is not in class {}", mark.student_id, class_name),
+                ));
+            }
+            if !seen.insert(mark.student_id) {
+                return Err(AppError::invalid(
+                    "student_id",
+                    &format!("Student {} is marked more than once", mark.student_id),
+                ));
+            }
+        }
+
+        let mut attendance = self.attendance.lock().await;
+        let already_marked = attendance.values().any(|r| {
+            r.school_id == school_id
+                && r.date == req.date
+                && r.period == req.period
+                && seen.contains(&r.student_id)
+        });
+        if already_marked {
+            return Err(AppError::Conflict(
+                "Attendance already marked for this date, use a correction instead".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let records: Vec<AttendanceRecord> = req
+            .records
+            .into_iter()
+            .map(|mark| AttendanceRecord {
+                id: Uuid::new_v4(),
+                school_id,
+                student_id: mark.student_id,
+                class_name: class_name.to_string(),
+                date: req.date,
+                period: req.period,
+                status: mark.status,
+                note: mark.note,
+                marked_by: marked_by.to_string(),
+                marked_at: now,
+                corrections: Vec::new(),
+            })
+            .collect();
+
+        for record in &records {
+            attendance.insert(record.id.to_string(), record.clone());
+        }
+        Ok(records)
+    }
+
+    pub async fn get_attendance_register(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+        query: AttendanceRegisterQuery,
+    ) -> Result<Vec<AttendanceRecord>, AppError> {
+        let attendance = self.attendance.lock().await;
+        Ok(attendance
+            .values()
+            .filter(|r| {
+                r.school_id == school_id
+                    && r.class_name == class_name
+                    && r.date == query.date
+                    && r.period == query.period
+            })
+            .cloned()
+            .collect())
+    }
+
+    pub async fn correct_attendance(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        corrected_by: &str,
+        req: CorrectAttendanceRequest,
+    ) -> Result<AttendanceRecord, AppError> {
+        if req.reason.trim().is_empty() {
+            return Err(AppError::invalid("reason", "A reason is required for corrections"));
+        }
+
+        let mut attendance = self.attendance.lock().await;
+        let record = attendance
+            .values_mut()
+            .find(|r| r.id == id && r.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        record.corrections.push(AttendanceCorrection {
+            previous_status: record.status,
+            new_status: req.status,
+            reason: req.reason,
+            corrected_by: corrected_by.to_string(),
+            corrected_at: Utc::now(),
+        });
+        record.status = req.status;
+        if req.note.is_some() {
+            record.note = req.note;
+        }
+
+        Ok(record.clone())
+    }
+
+    /// Daily-register summary for one student over the term's date range.
+    /// Per-period records are kept for lesson tracking but not counted here.
+    pub async fn get_attendance_summary(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        term_code: &str,
+    ) -> Result<AttendanceSummary, AppError> {
+        let student = self.get_student(school_id, student_id).await?;
+        let term = self.get_term(school_id, term_code).await?;
+
+        let attendance = self.attendance.lock().await;
+        let records: Vec<&AttendanceRecord> = attendance
+            .values()
+            .filter(|r| {
+                r.school_id == school_id
+                    && r.student_id == student_id
+                    && r.period.is_none()
+                    && r.date >= term.starts_on
+                    && r.date <= term.ends_on
+            })
+            .collect();
+
+        Ok(AttendanceSummary::from_records(
+            student.id,
+            format!("{} {}", student.first_name, student.last_name),
+            student.class_name,
+            term.code,
+            &records,
+        ))
+    }
+
+    pub async fn get_chronic_absentees(
+        &self,
+        school_id: Uuid,
+        query: AbsenteesQuery,
+    ) -> Result<Vec<AttendanceSummary>, AppError> {
+        let threshold = query.threshold.unwrap_or(DEFAULT_ABSENCE_THRESHOLD);
+        if !(0.0..=100.0).contains(&threshold) {
+            return Err(AppError::invalid("threshold", "Threshold must be a percentage between 0 and 100"));
+        }
+
+        let students = match &query.class_name {
+            Some(class_name) => self.get_class_students(school_id, class_name).await?,
+            None => self.get_all_students(school_id).await?,
+        };
+
+        let mut absentees = Vec::new();
+        for student in students {
+            let summary = self
+                .get_attendance_summary(school_id, student.id, &query.term)
+                .await?;
+            if summary.days_recorded > 0 && summary.absence_rate > threshold {
+                absentees.push(summary);
+            }
+        }
+
+        absentees.sort_by(|a, b| b.absence_rate.total_cmp(&a.absence_rate));
+        Ok(absentees)
+    }
+}
@@ -0,0 +1,510 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    statements::{Direction, ParsedLine, StatementFormat},
+};
+
+use super::{
+    AppStore, Student,
+    ledger::{Invoice, Payment, PaymentMethod, amount_due_next},
+    manual_payments::ManualPaymentDetails,
+};
+
+// statements are text; even a year of a busy account stays well under this
+pub const MAX_STATEMENT_BYTES: usize = 10 * 1024 * 1024;
+const MIN_CONFIDENCE: u8 = 35;
+const MAX_PROPOSALS: usize = 3;
+
+#[derive(Clone, Serialize)]
+pub struct BankStatement {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub file_name: String,
+    pub format: StatementFormat,
+    pub imported_by: String,
+    pub imported_at: DateTime<Utc>,
+    pub credits: usize,            // lines kept for matching
+    pub debits_skipped: usize,     // money going out is never a fee payment
+    pub duplicates_skipped: usize, // credits already imported from an earlier statement
+    pub other_currency_skipped: usize, // credits in a currency the school doesn't bill in
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum StatementLineStatus {
+    Unmatched, // nothing looked likely; needs a person
+    Proposed,  // has match proposals waiting for confirmation
+    Confirmed, // posted as a payment
+    Ignored,   // not a fee payment
+}
+
+/// A likely invoice for a statement credit. `confidence` is out of 100.
+#[derive(Clone, Serialize)]
+pub struct MatchProposal {
+    pub invoice_id: Uuid,
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub admission_number: String,
+    pub term_code: String,
+    pub balance_kobo: i64,
+    pub confidence: u8,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StatementLine {
+    pub id: Uuid,
+    pub statement_id: Uuid,
+    pub school_id: Uuid,
+    pub line_number: usize, // position among the statement's credits, from 1
+    pub date: NaiveDate,
+    pub amount_kobo: u64,
+    pub narration: String,
+    pub reference: Option<String>,
+    pub status: StatementLineStatus,
+    pub proposals: Vec<MatchProposal>, // best first
+    pub payment_id: Option<Uuid>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct StatementReport {
+    #[serde(flatten)]
+    pub statement: BankStatement,
+    pub unmatched: usize,
+    pub proposed: usize,
+    pub confirmed: usize,
+    pub ignored: usize,
+    pub unresolved_kobo: u64, // credits still waiting on a decision
+    pub lines: Vec<StatementLine>,
+}
+
+#[derive(Deserialize)]
+pub struct StatementLineQuery {
+    pub status: Option<StatementLineStatus>,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmLineRequest {
+    pub invoice_id: Option<Uuid>, // defaults to the best proposal; set it to reassign
+}
+
+// upper-case words, so "Payment from OBI/ADA" finds "Ada Obi"
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_uppercase)
+        .collect()
+}
+
+// letters and digits only, so "ADM/2026/0001" and "adm 2026 0001" compare equal
+fn compact(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn score(line: &ParsedLine, invoice: &Invoice, student: &Student) -> (u8, Vec<String>) {
+    let text = format!("{} {}", line.narration, line.reference.as_deref().unwrap_or_default());
+    let text_words = words(&text);
+    let has_word = |name: &str| name.len() >= 3 && text_words.contains(&name.to_uppercase());
+
+    let mut confidence: u32 = 0;
+    let mut reasons = Vec::new();
+
+    let admission = compact(&student.admission_number);
+    if !admission.is_empty() && compact(&text).contains(&admission) {
+        confidence += 50;
+        reasons.push("Admission number in the narration".to_string());
+    }
+
+    let balance = invoice.balance_kobo.max(0) as u64;
+    if line.amount_kobo == balance {
+        confidence += 30;
+        reasons.push("Amount equals the invoice balance".to_string());
+    } else if line.amount_kobo == amount_due_next(invoice) {
+        confidence += 25;
+        reasons.push("Amount equals the next installment".to_string());
+    } else if line.amount_kobo < balance {
+        confidence += 5;
+        reasons.push("Amount is a part payment".to_string());
+    }
+
+    if has_word(&student.last_name) {
+        confidence += 10;
+        reasons.push("Surname in the narration".to_string());
+    }
+    if has_word(&student.first_name) {
+        confidence += 10;
+        reasons.push("First name in the narration".to_string());
+    }
+    let guardian_surname = student
+        .profile
+        .guardian_name
+        .as_deref()
+        .and_then(|n| n.split_whitespace().last())
+        .filter(|n| !n.eq_ignore_ascii_case(&student.last_name));
+    if guardian_surname.is_some_and(has_word) {
+        confidence += 10;
+        reasons.push("Guardian's name in the narration".to_string());
+    }
+
+    (confidence.min(100) as u8, reasons)
+}
+
+fn propose(line: &ParsedLine, open: &[(Invoice, Student)]) -> Vec<MatchProposal> {
+    let mut proposals: Vec<MatchProposal> = open
+        .iter()
+        .filter_map(|(invoice, student)| {
+            let (confidence, reasons) = score(line, invoice, student);
+            (confidence >= MIN_CONFIDENCE).then(|| MatchProposal {
+                invoice_id: invoice.id,
+                student_id: student.id,
+                student_name: format!("{} {}", student.first_name, student.last_name),
+                admission_number: student.admission_number.clone(),
+                term_code: invoice.term_code.clone(),
+                balance_kobo: invoice.balance_kobo,
+                confidence,
+                reasons,
+            })
+        })
+        .collect();
+    // stable, so ties stay with the invoice that fell due first
+    proposals.sort_by_key(|p| Reverse(p.confidence));
+    proposals.truncate(MAX_PROPOSALS);
+    proposals
+}
+
+impl AppStore {
+    /// Stores a parsed statement and proposes an open invoice for each
+    /// credit. Nothing is posted until someone confirms a line.
+    pub async fn import_bank_statement(
+        &self,
+        school_id: Uuid,
+        imported_by: &str,
+        file_name: String,
+        format: StatementFormat,
+        parsed: Vec<ParsedLine>,
+    ) -> Result<StatementReport, AppError> {
+        let currency = self.get_school(school_id).await?.currency;
+        let mut invoices: Vec<Invoice> = {
+            let invoices = self.invoices.lock().await;
+            invoices
+                .values()
+                .filter(|i| i.school_id == school_id && i.balance_kobo > 0)
+                .cloned()
+                .collect()
+        };
+        invoices.sort_by_key(|i| i.due_date);
+        let open: Vec<(Invoice, Student)> = {
+            let students = self.students.lock().await;
+            invoices
+                .into_iter()
+                .filter_map(|i| students.get(&i.student_id.to_string()).cloned().map(|s| (i, s)))
+                .collect()
+        };
+
+        let mut statement = BankStatement {
+            id: Uuid::new_v4(),
+            school_id,
+            file_name,
+            format,
+            imported_by: imported_by.to_string(),
+            imported_at: Utc::now(),
+            credits: 0,
+            debits_skipped: 0,
+            duplicates_skipped: 0,
+            other_currency_skipped: 0,
+        };
+
+        let mut lines = self.statement_lines.lock().await;
+        let mut added: Vec<StatementLine> = Vec::new();
+        // identical credits do happen (one fee paid for two siblings), so the
+        // nth copy in this file is only a duplicate if earlier imports hold n
+        let mut occurrences: HashMap<(NaiveDate, u64, String, Option<String>), usize> = HashMap::new();
+        for line in parsed {
+            if line.direction == Direction::Debit {
+                statement.debits_skipped += 1;
+                continue;
+            }
+            // lines without a currency are taken to be in the account's, the school's
+            if line.currency.as_deref().is_some_and(|c| !currency.matches(c)) {
+                statement.other_currency_skipped += 1;
+                continue;
+            }
+            let key = (line.date, line.amount_kobo, line.narration.clone(), line.reference.clone());
+            let occurrence = occurrences.entry(key).or_default();
+            *occurrence += 1;
+            let imported_before = lines
+                .values()
+                .filter(|l| {
+                    l.school_id == school_id
+                        && l.date == line.date
+                        && l.amount_kobo == line.amount_kobo
+                        && l.narration == line.narration
+                        && l.reference == line.reference
+                })
+                .count();
+            if imported_before >= *occurrence {
+                statement.duplicates_skipped += 1;
+                continue;
+            }
+
+            let proposals = propose(&line, &open);
+            statement.credits += 1;
+            added.push(StatementLine {
+                id: Uuid::new_v4(),
+                statement_id: statement.id,
+                school_id,
+                line_number: statement.credits,
+                date: line.date,
+                amount_kobo: line.amount_kobo,
+                narration: line.narration,
+                reference: line.reference,
+                status: if proposals.is_empty() {
+                    StatementLineStatus::Unmatched
+                } else {
+                    StatementLineStatus::Proposed
+                },
+                proposals,
+                payment_id: None,
+                resolved_by: None,
+                resolved_at: None,
+            });
+        }
+        for line in &added {
+            lines.insert(line.id.to_string(), line.clone());
+        }
+        drop(lines);
+
+        self.bank_statements
+            .lock()
+            .await
+            .insert(statement.id.to_string(), statement.clone());
+        Ok(statement_report(statement, added, None))
+    }
+
+    pub async fn get_bank_statements(&self, school_id: Uuid) -> Vec<BankStatement> {
+        let statements = self.bank_statements.lock().await;
+        let mut list: Vec<BankStatement> = statements
+            .values()
+            .filter(|s| s.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|s| s.imported_at);
+        list
+    }
+
+    pub async fn get_bank_statement(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        query: StatementLineQuery,
+    ) -> Result<StatementReport, AppError> {
+        let statement = {
+            let statements = self.bank_statements.lock().await;
+            statements
+                .get(&id.to_string())
+                .filter(|s| s.school_id == school_id)
+                .cloned()
+                .ok_or(AppError::NotFound)?
+        };
+        let lines: Vec<StatementLine> = {
+            let lines = self.statement_lines.lock().await;
+            lines.values().filter(|l| l.statement_id == id).cloned().collect()
+        };
+        Ok(statement_report(statement, lines, query.status))
+    }
+
+    /// Posts a statement credit as a bank transfer against an invoice: the
+    /// best proposal, or any open invoice the bursar picks instead. Like any
+    /// other manual payment, it waits for approval when the school asks for it.
+    pub async fn confirm_statement_line(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        confirmed_by: &str,
+        req: ConfirmLineRequest,
+    ) -> Result<StatementLine, AppError> {
+        let line = self.get_open_statement_line(school_id, id).await?;
+        let invoice_id = req
+            .invoice_id
+            .or_else(|| line.proposals.first().map(|p| p.invoice_id))
+            .ok_or_else(|| AppError::invalid("invoice_id", "Nothing was proposed for this line; choose an invoice"))?;
+        let invoice = self.get_invoice(school_id, invoice_id).await?;
+        let file_name = {
+            let statements = self.bank_statements.lock().await;
+            statements
+                .get(&line.statement_id.to_string())
+                .map(|s| s.file_name.clone())
+                .unwrap_or_default()
+        };
+
+        // one payment per line; the reference makes a second confirm fail
+        let mut payment: Payment = self
+            .create_pending_payment(&invoice, format!("stmt-{}", line.id), line.amount_kobo, PaymentMethod::BankTransfer)
+            .await?;
+        payment.manual = Some(ManualPaymentDetails {
+            paid_on: line.date,
+            external_reference: line.reference.clone(),
+            note: Some(format!("Bank statement {}, line {}: {}", file_name, line.line_number, line.narration)),
+            evidence: None,
+            recorded_by: confirmed_by.to_string(),
+            reviewed_by: None,
+            reviewed_at: None,
+            rejection_reason: None,
+        });
+        self.payments
+            .lock()
+            .await
+            .insert(payment.id.to_string(), payment.clone());
+        // pending payments go through approve_manual_payment like any other
+        let payment = if self.get_manual_payment_policy(school_id).await?.require_approval {
+            payment
+        } else {
+            self.settle_payment(&payment.reference, payment.amount_kobo, confirmed_by)
+                .await?
+        };
+
+        self.resolve_statement_line(id, StatementLineStatus::Confirmed, Some(payment.id), confirmed_by)
+            .await
+    }
+
+    /// Marks a credit as not being a fee payment, e.g. a grant or a loan.
+    pub async fn ignore_statement_line(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        ignored_by: &str,
+    ) -> Result<StatementLine, AppError> {
+        self.get_open_statement_line(school_id, id).await?;
+        self.resolve_statement_line(id, StatementLineStatus::Ignored, None, ignored_by)
+            .await
+    }
+
+    async fn get_open_statement_line(&self, school_id: Uuid, id: Uuid) -> Result<StatementLine, AppError> {
+        let lines = self.statement_lines.lock().await;
+        let line = lines
+            .get(&id.to_string())
+            .filter(|l| l.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+        if matches!(line.status, StatementLineStatus::Confirmed | StatementLineStatus::Ignored) {
+            return Err(AppError::Conflict("This line has already been resolved".to_string()));
+        }
+        Ok(line.clone())
+    }
+
+    async fn resolve_statement_line(
+        &self,
+        id: Uuid,
+        status: StatementLineStatus,
+        payment_id: Option<Uuid>,
+        resolved_by: &str,
+    ) -> Result<StatementLine, AppError> {
+        let mut lines = self.statement_lines.lock().await;
+        let line = lines.get_mut(&id.to_string()).ok_or(AppError::NotFound)?;
+        line.status = status;
+        line.payment_id = payment_id;
+        line.resolved_by = Some(resolved_by.to_string());
+        line.resolved_at = Some(Utc::now());
+        Ok(line.clone())
+    }
+}
+
+fn statement_report(
+    statement: BankStatement,
+    mut lines: Vec<StatementLine>,
+    status: Option<StatementLineStatus>,
+) -> StatementReport {
+    lines.sort_by_key(|l| l.line_number);
+    let count = |status: StatementLineStatus| lines.iter().filter(|l| l.status == status).count();
+    let unresolved_kobo = lines
+        .iter()
+        .filter(|l| matches!(l.status, StatementLineStatus::Unmatched | StatementLineStatus::Proposed))
+        .map(|l| l.amount_kobo)
+        .sum();
+
+    StatementReport {
+        unmatched: count(StatementLineStatus::Unmatched),
+        proposed: count(StatementLineStatus::Proposed),
+        confirmed: count(StatementLineStatus::Confirmed),
+        ignored: count(StatementLineStatus::Ignored),
+        unresolved_kobo,
+        lines: lines
+            .into_iter()
+            .filter(|l| status.is_none_or(|s| l.status == s))
+            .collect(),
+        statement,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        ledger::TransactionStatus,
+        manual_payments::ManualPaymentPolicy,
+        testing::{self, date},
+    };
+
+    async fn statement_line(store: &AppStore, invoice: &Invoice, amount_kobo: u64) -> StatementLine {
+        let line = ParsedLine {
+            date: date(2026, 9, 10),
+            direction: Direction::Credit,
+            amount_kobo,
+            narration: "TRF FROM NGOZI OBI".to_string(),
+            reference: Some("FT26253ABC".to_string()),
+            currency: None,
+        };
+        let report = store
+            .import_bank_statement(invoice.school_id, "bursar", "sept.csv".to_string(), StatementFormat::Csv, vec![line])
+            .await
+            .unwrap();
+        report.lines[0].clone()
+    }
+
+    #[tokio::test]
+    async fn confirmed_lines_settle_straight_away_by_default() {
+        let (store, invoice) = testing::billed_student(5_000_000, date(2099, 12, 31)).await;
+        let line = statement_line(&store, &invoice, 2_000_000).await;
+
+        let req = ConfirmLineRequest { invoice_id: Some(invoice.id) };
+        let line = store.confirm_statement_line(invoice.school_id, line.id, "bursar", req).await.unwrap();
+
+        let reference = format!("stmt-{}", line.id);
+        let payment = store.find_payment_by_reference(invoice.school_id, &reference).await.unwrap();
+        assert_eq!(line.payment_id, Some(payment.id));
+        assert_eq!(payment.status, TransactionStatus::Successful);
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(invoice.balance_kobo, 3_000_000);
+    }
+
+    #[tokio::test]
+    async fn confirmed_lines_wait_for_approval_when_the_school_requires_it() {
+        let (store, invoice) = testing::billed_student(5_000_000, date(2099, 12, 31)).await;
+        let policy = ManualPaymentPolicy { require_approval: true };
+        store.set_manual_payment_policy(invoice.school_id, policy).await.unwrap();
+        let line = statement_line(&store, &invoice, 2_000_000).await;
+
+        let req = ConfirmLineRequest { invoice_id: Some(invoice.id) };
+        let line = store.confirm_statement_line(invoice.school_id, line.id, "bursar", req).await.unwrap();
+        let payment_id = line.payment_id.unwrap();
+
+        let awaiting = store.get_payments_awaiting_approval(invoice.school_id).await.unwrap();
+        assert_eq!(awaiting.iter().map(|p| p.id).collect::<Vec<_>>(), vec![payment_id]);
+        let unpaid = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(unpaid.balance_kobo, 5_000_000);
+
+        assert!(store.approve_manual_payment(invoice.school_id, payment_id, "bursar").await.is_err());
+        let payment = store.approve_manual_payment(invoice.school_id, payment_id, "owner").await.unwrap();
+        assert_eq!(payment.status, TransactionStatus::Successful);
+        let paid = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(paid.balance_kobo, 3_000_000);
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::AppStore;
+
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    Date, // stored as a "YYYY-MM-DD" string
+    Enum,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CustomFieldDefinition {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub key: String, // used in student payloads and as the `custom.<key>` filter
+    pub label: String,
+    pub field_type: CustomFieldType,
+    pub options: Vec<String>, // allowed values for Enum fields
+    pub required: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CreateCustomFieldRequest {
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl CustomFieldDefinition {
+    fn validate_value(&self, value: &Value) -> Result<(), AppError> {
+        let field = format!("custom_fields.{}", self.key);
+        let valid = match self.field_type {
+            CustomFieldType::Text => value.is_string(),
+            CustomFieldType::Number => value.is_number(),
+            CustomFieldType::Date => value
+                .as_str()
+                .is_some_and(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok()),
+            CustomFieldType::Enum => value
+                .as_str()
+                .is_some_and(|v| self.options.iter().any(|o| o == v)),
+        };
+
+        if valid {
+            return Ok(());
+        }
+        let message = match self.field_type {
+            CustomFieldType::Text => "Expected text".to_string(),
+            CustomFieldType::Number => "Expected a number".to_string(),
+            CustomFieldType::Date => "Expected a date formatted as YYYY-MM-DD".to_string(),
+            CustomFieldType::Enum => format!("Expected one of: {}", self.options.join(", ")),
+        };
+        Err(AppError::invalid(&field, &message))
+    }
+}
+
+/// Text form of a custom value, used for filtering and CSV export.
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+impl AppStore {
+    pub async fn create_custom_field(
+        &self,
+        school_id: Uuid,
+        req: CreateCustomFieldRequest,
+    ) -> Result<CustomFieldDefinition, AppError> {
+        let key_is_valid = !req.key.is_empty()
+            && req
+                .key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !key_is_valid {
+            return Err(AppError::invalid("key", "Key may only contain lowercase letters, digits and underscores"));
+        }
+        if req.field_type == CustomFieldType::Enum && req.options.is_empty() {
+            return Err(AppError::invalid("options", "Enum fields need at least one option"));
+        }
+
+        let mut fields = self.custom_fields.lock().await;
+        let taken = fields
+            .values()
+            .any(|f| f.school_id == school_id && f.key == req.key);
+        if taken {
+            return Err(AppError::Conflict("A custom field with this key already exists".to_string()));
+        }
+
+        let field = CustomFieldDefinition {
+            id: Uuid::new_v4(),
+            school_id,
+            key: req.key,
+            label: req.label,
+            field_type: req.field_type,
+            options: req.options,
+            required: req.required,
+        };
+
+        fields.insert(field.id.to_string(), field.clone());
+        Ok(field)
+    }
+
+    pub async fn get_custom_fields(&self, school_id: Uuid) -> Result<Vec<CustomFieldDefinition>, AppError> {
+        let fields = self.custom_fields.lock().await;
+        let mut list: Vec<CustomFieldDefinition> = fields
+            .values()
+            .filter(|f| f.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(list)
+    }
+
+    /// Removes the definition and the stored values for it on every student.
+    pub async fn delete_custom_field(&self, school_id: Uuid, key: &str) -> Result<(), AppError> {
+        {
+            let mut fields = self.custom_fields.lock().await;
+            let id = fields
+                .values()
+                .find(|f| f.school_id == school_id && f.key == key)
+                .map(|f| f.id.to_string())
+                .ok_or(AppError::NotFound)?;
+            fields.remove(&id);
+        }
+
+        let mut students = self.students.lock().await;
+        for student in students.values_mut().filter(|s| s.school_id == school_id) {
+            student.custom_fields.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Checks submitted custom values against the school's schema: unknown
+    /// keys are rejected, required fields must be present and non-null.
+    pub async fn validate_custom_fields(
+        &self,
+        school_id: Uuid,
+        values: &BTreeMap<String, Value>,
+    ) -> Result<(), AppError> {
+        let definitions = self.get_custom_fields(school_id).await?;
+
+        for (key, value) in values {
+            let definition = definitions
+                .iter()
+                .find(|d| &d.key == key)
+                .ok_or_else(|| AppError::invalid(&format!("custom_fields.{}", key), "Unknown custom field"))?;
+            if !value.is_null() {
+                definition.validate_value(value)?;
+            }
+        }
+
+        for definition in definitions.iter().filter(|d| d.required) {
+            let present = values.get(&definition.key).is_some_and(|v| !v.is_null());
+            if !present {
+                return Err(AppError::invalid(
+                    &format!("custom_fields.{}", definition.key),
+                    "This field is required",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet},
+};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{config::get_env_vars, errors::AppError};
+
+use super::{
+    AppStore, PaymentStatus,
+    attendance::AttendanceStatus,
+    ledger::{PaymentMethod, TransactionStatus},
+    money::Currency,
+};
+
+const RECENT_PAYMENTS: usize = 10;
+const UPCOMING_DUE_DAYS: i64 = 14;
+const DEFAULT_CACHE_SECS: i64 = 300;
+
+#[derive(Clone, Serialize)]
+pub struct StudentCounts {
+    pub total: usize,
+    pub active: usize, // billed for the current term
+    pub by_class: BTreeMap<String, usize>,
+    pub by_department: BTreeMap<String, usize>,
+}
+
+#[derive(Clone, Default, Serialize)]
+pub struct PaymentStatusCounts {
+    pub paid: usize,
+    pub partially_paid: usize,
+    pub pending: usize,
+    pub overdue: usize,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TermCollection {
+    pub term_code: String,
+    pub invoiced_kobo: u64,
+    pub collected_kobo: u64,
+    pub outstanding_kobo: u64,
+    pub collection_rate: f64, // percentage of what was invoiced that has been paid
+}
+
+#[derive(Clone, Serialize)]
+pub struct RecentPayment {
+    pub payment_id: Uuid,
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub amount_kobo: u64,
+    pub method: PaymentMethod,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+/// An invoice, or one installment of it, falling due soon.
+#[derive(Clone, Serialize)]
+pub struct UpcomingDue {
+    pub invoice_id: Uuid,
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub label: String, // the term, or the installment
+    pub due_date: NaiveDate,
+    pub amount_due_kobo: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Dashboard {
+    pub generated_at: DateTime<Utc>,
+    pub currency: Currency,
+    pub students: StudentCounts,
+    pub payment_status: PaymentStatusCounts,
+    pub current_term: Option<TermCollection>, // none between terms
+    pub recent_payments: Vec<RecentPayment>,
+    pub upcoming_dues: Vec<UpcomingDue>,
+    pub attendance_rate: Option<f64>, // daily registers this term; none until one is marked
+}
+
+impl AppStore {
+    /// The school's headline figures. They are cached until the school
+    /// writes anything, money moves, or DASHBOARD_CACHE_SECS pass.
+    pub async fn get_dashboard(&self, school_id: Uuid) -> Result<Dashboard, AppError> {
+        let max_age_secs: i64 = get_env_vars("DASHBOARD_CACHE_SECS".to_string()).unwrap_or(DEFAULT_CACHE_SECS);
+        if let Some(cached) = self.dashboards.lock().await.get(&school_id.to_string())
+            && Utc::now() - cached.generated_at < Duration::seconds(max_age_secs)
+        {
+            return Ok(cached.clone());
+        }
+
+        let generation = self.dashboard_generation(school_id).await;
+        let dashboard = self.build_dashboard(school_id).await?;
+
+        // a write landing while we built it may not be in the figures; don't cache them then
+        let generations = self.dashboard_generations.lock().await;
+        if generations.get(&school_id.to_string()).copied().unwrap_or(0) == generation {
+            self.dashboards
+                .lock()
+                .await
+                .insert(school_id.to_string(), dashboard.clone());
+        }
+        Ok(dashboard)
+    }
+
+    pub async fn invalidate_dashboard(&self, school_id: Uuid) {
+        let mut generations = self.dashboard_generations.lock().await;
+        *generations.entry(school_id.to_string()).or_insert(0) += 1;
+        self.dashboards.lock().await.remove(&school_id.to_string());
+    }
+
+    async fn dashboard_generation(&self, school_id: Uuid) -> u64 {
+        self.dashboard_generations
+            .lock()
+            .await
+            .get(&school_id.to_string())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    async fn build_dashboard(&self, school_id: Uuid) -> Result<Dashboard, AppError> {
+        let school = self.get_school(school_id).await?;
+        let today = Utc::now().date_naive();
+        let term = self.get_current_term(school_id).await.ok();
+
+        let students: Vec<_> = {
+            let students = self.students.lock().await;
+            students.values().filter(|s| s.school_id == school_id).cloned().collect()
+        };
+        let name_of = |id: Uuid| {
+            students
+                .iter()
+                .find(|s| s.id == id)
+                .map(|s| format!("{} {}", s.first_name, s.last_name))
+                .unwrap_or_default()
+        };
+
+        let invoices: Vec<_> = {
+            let invoices = self.invoices.lock().await;
+            invoices.values().filter(|i| i.school_id == school_id).cloned().collect()
+        };
+        let term_invoices: Vec<_> = invoices
+            .iter()
+            .filter(|i| term.as_ref().is_some_and(|t| t.code == i.term_code))
+            .collect();
+        let billed: HashSet<Uuid> = term_invoices.iter().map(|i| i.student_id).collect();
+
+        let mut counts = StudentCounts {
+            total: students.len(),
+            active: students.iter().filter(|s| billed.contains(&s.id)).count(),
+            by_class: BTreeMap::new(),
+            by_department: BTreeMap::new(),
+        };
+        let mut payment_status = PaymentStatusCounts::default();
+        for student in &students {
+            let class_name = student.class_name.clone().unwrap_or_else(|| "Unassigned".to_string());
+            *counts.by_class.entry(class_name).or_default() += 1;
+            *counts.by_department.entry(student.department.clone()).or_default() += 1;
+            match student.status {
+                PaymentStatus::Paid => payment_status.paid += 1,
+                PaymentStatus::PartiallyPaid => payment_status.partially_paid += 1,
+                PaymentStatus::Pending => payment_status.pending += 1,
+                PaymentStatus::Overdue => payment_status.overdue += 1,
+            }
+        }
+
+        let current_term = term.as_ref().map(|term| {
+            let invoiced_kobo: u64 = term_invoices.iter().map(|i| i.total_kobo).sum();
+            let collected_kobo: u64 = term_invoices.iter().map(|i| i.amount_paid_kobo).sum();
+            let outstanding_kobo: u64 = term_invoices.iter().map(|i| i.balance_kobo.max(0) as u64).sum();
+            let collection_rate = if invoiced_kobo == 0 {
+                0.0
+            } else {
+                (collected_kobo as f64 / invoiced_kobo as f64 * 10_000.0).round() / 100.0
+            };
+            TermCollection {
+                term_code: term.code.clone(),
+                invoiced_kobo,
+                collected_kobo,
+                outstanding_kobo,
+                collection_rate,
+            }
+        });
+
+        let mut recent_payments: Vec<RecentPayment> = {
+            let payments = self.payments.lock().await;
+            payments
+                .values()
+                .filter(|p| p.school_id == school_id && p.status == TransactionStatus::Successful)
+                .map(|p| RecentPayment {
+                    payment_id: p.id,
+                    student_id: p.student_id,
+                    student_name: name_of(p.student_id),
+                    amount_kobo: p.amount_kobo,
+                    method: p.method,
+                    paid_at: p.paid_at,
+                })
+                .collect()
+        };
+        recent_payments.sort_by_key(|p| Reverse(p.paid_at));
+        recent_payments.truncate(RECENT_PAYMENTS);
+
+        let horizon = today + Duration::days(UPCOMING_DUE_DAYS);
+        let mut upcoming_dues: Vec<UpcomingDue> = Vec::new();
+        for invoice in invoices.iter().filter(|i| i.balance_kobo > 0) {
+            let mut due = |label: String, due_date: NaiveDate, amount_due_kobo: u64| {
+                if today <= due_date && due_date <= horizon && amount_due_kobo > 0 {
+                    upcoming_dues.push(UpcomingDue {
+                        invoice_id: invoice.id,
+                        student_id: invoice.student_id,
+                        student_name: name_of(invoice.student_id),
+                        label,
+                        due_date,
+                        amount_due_kobo,
+                    });
+                }
+            };
+            if invoice.installments.is_empty() {
+                due(invoice.term_code.clone(), invoice.due_date, invoice.balance_kobo as u64);
+            } else {
+                for installment in &invoice.installments {
+                    let label = format!("{} {}", invoice.term_code, installment.label);
+                    due(label, installment.due_date, installment.amount_kobo.saturating_sub(installment.paid_kobo));
+                }
+            }
+        }
+        upcoming_dues.sort_by_key(|d| d.due_date);
+
+        let attendance_rate = match &term {
+            None => None,
+            Some(term) => {
+                let attendance = self.attendance.lock().await;
+                let (attended, counted) = attendance
+                .values()
+                .filter(|r| r.school_id == school_id && r.period.is_none())
+                .filter(|r| term.starts_on <= r.date && r.date <= term.ends_on)
+                .fold((0u32, 0u32), |(attended, counted), r| match r.status {
+                    AttendanceStatus::Present | AttendanceStatus::Late => (attended + 1, counted + 1),
+                    AttendanceStatus::Absent => (attended, counted + 1),
+                    // excused days are left out of the denominator, as in attendance summaries
+                    AttendanceStatus::Excused => (attended, counted),
+                });
+                (counted > 0).then(|| (attended as f64 / counted as f64 * 10_000.0).round() / 100.0)
+            }
+        };
+
+        Ok(Dashboard {
+            generated_at: Utc::now(),
+            currency: school.currency,
+            students: counts,
+            payment_status,
+            current_term,
+            recent_payments,
+            upcoming_dues,
+            attendance_rate,
+        })
+    }
+}
@@ -0,0 +1,379 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, Student, academics::Term, fees::FeeLine};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum DiscountKind {
+    Percentage, // `value` is a percentage, 1 to 100
+    Fixed,      // `value` is an amount in kobo
+}
+
+/// A reduction on what a student is billed, e.g. "Staff child 50%",
+/// "Scholarship" or "Third sibling 10%". Rules apply to students they are
+/// assigned to, and automatically to every child from `min_sibling_position`
+/// onwards in a family (see guardians).
+#[derive(Clone, Serialize)]
+pub struct Discount {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub name: String,
+    pub kind: DiscountKind,
+    pub value: u64,
+    pub fee_item_id: Option<Uuid>, // None discounts the whole invoice
+    pub valid_from: Option<NaiveDate>, // compared with the start of the term being billed
+    pub valid_until: Option<NaiveDate>,
+    pub min_sibling_position: Option<u32>, // e.g. 3 for the third child and any younger ones
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct DiscountRequest {
+    pub name: String,
+    pub kind: DiscountKind,
+    pub value: u64,
+    pub fee_item_id: Option<Uuid>,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+    pub min_sibling_position: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct SetStudentDiscountsRequest {
+    pub discount_ids: Vec<Uuid>,
+}
+
+/// A discount as it applies to one student's fees for a term.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DiscountLine {
+    pub discount_id: Uuid,
+    pub name: String,
+    pub fee_item_id: Option<Uuid>,
+    pub amount_kobo: u64,
+}
+
+impl Discount {
+    fn valid_for(&self, term: &Term) -> bool {
+        self.valid_from.is_none_or(|from| term.starts_on >= from)
+            && self.valid_until.is_none_or(|until| term.starts_on <= until)
+    }
+
+    fn amount_on(&self, base_kobo: u64) -> u64 {
+        match self.kind {
+            DiscountKind::Percentage => base_kobo * self.value / 100,
+            DiscountKind::Fixed => self.value.min(base_kobo),
+        }
+    }
+}
+
+impl AppStore {
+    async fn validate_discount(&self, school_id: Uuid, req: &DiscountRequest) -> Result<(), AppError> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::invalid("name", "Discount name cannot be empty"));
+        }
+        if req.value == 0 {
+            return Err(AppError::invalid("value", "Discount value must be greater than zero"));
+        }
+        if req.kind == DiscountKind::Percentage && req.value > 100 {
+            return Err(AppError::invalid("value", "A percentage discount cannot exceed 100"));
+        }
+        if let (Some(from), Some(until)) = (req.valid_from, req.valid_until)
+            && until < from
+        {
+            return Err(AppError::invalid("valid_until", "Validity cannot end before it starts"));
+        }
+        if req.min_sibling_position.is_some_and(|p| p < 2) {
+            return Err(AppError::invalid("min_sibling_position", "Sibling discounts start from the second child"));
+        }
+        if let Some(fee_item_id) = req.fee_item_id {
+            let items = self.get_fee_items(school_id).await?;
+            if !items.iter().any(|i| i.id == fee_item_id) {
+                return Err(AppError::invalid("fee_item_id", "Unknown fee item"));
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn create_discount(&self, school_id: Uuid, req: DiscountRequest) -> Result<Discount, AppError> {
+        self.validate_discount(school_id, &req).await?;
+
+        let discount = Discount {
+            id: Uuid::new_v4(),
+            school_id,
+            name: req.name,
+            kind: req.kind,
+            value: req.value,
+            fee_item_id: req.fee_item_id,
+            valid_from: req.valid_from,
+            valid_until: req.valid_until,
+            min_sibling_position: req.min_sibling_position,
+            created_at: Utc::now(),
+        };
+
+        self.discounts
+            .lock()
+            .await
+            .insert(discount.id.to_string(), discount.clone());
+        Ok(discount)
+    }
+
+    pub async fn update_discount(&self, school_id: Uuid, id: Uuid, req: DiscountRequest) -> Result<Discount, AppError> {
+        self.validate_discount(school_id, &req).await?;
+
+        let mut discounts = self.discounts.lock().await;
+        let discount = discounts
+            .values_mut()
+            .find(|d| d.id == id && d.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        discount.name = req.name;
+        discount.kind = req.kind;
+        discount.value = req.value;
+        discount.fee_item_id = req.fee_item_id;
+        discount.valid_from = req.valid_from;
+        discount.valid_until = req.valid_until;
+        discount.min_sibling_position = req.min_sibling_position;
+        Ok(discount.clone())
+    }
+
+    /// Removing a rule leaves invoices already billed with it untouched.
+    pub async fn delete_discount(&self, school_id: Uuid, id: Uuid) -> Result<(), AppError> {
+        let mut discounts = self.discounts.lock().await;
+        match discounts.get(&id.to_string()) {
+            Some(discount) if discount.school_id == school_id => {
+                discounts.remove(&id.to_string());
+            }
+            _ => return Err(AppError::NotFound),
+        }
+        drop(discounts);
+
+        let mut students = self.students.lock().await;
+        for student in students.values_mut().filter(|s| s.school_id == school_id) {
+            student.discount_ids.retain(|d| d != &id);
+        }
+        Ok(())
+    }
+
+    pub async fn get_discounts(&self, school_id: Uuid) -> Result<Vec<Discount>, AppError> {
+        let discounts = self.discounts.lock().await;
+        let mut list: Vec<Discount> = discounts
+            .values()
+            .filter(|d| d.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    pub async fn set_student_discounts(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        req: SetStudentDiscountsRequest,
+    ) -> Result<Student, AppError> {
+        let discounts = self.get_discounts(school_id).await?;
+        for id in &req.discount_ids {
+            if !discounts.iter().any(|d| &d.id == id) {
+                return Err(AppError::invalid("discount_ids", &format!("Unknown discount {}", id)));
+            }
+        }
+
+        let mut students = self.students.lock().await;
+        let student = students
+            .values_mut()
+            .find(|s| s.id == student_id && s.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        student.discount_ids = req.discount_ids;
+        Ok(student.clone())
+    }
+
+    /// The discounts a student gets on a term's fee lines. Item discounts are
+    /// taken first, then whole-invoice ones from what is left, so stacked
+    /// discounts never take an item or the invoice below zero.
+    pub async fn discounts_for(
+        &self,
+        student: &Student,
+        term: &Term,
+        lines: &[FeeLine],
+    ) -> Result<Vec<DiscountLine>, AppError> {
+        let sibling_position = self
+            .get_siblings(student.school_id, student.id)
+            .await?
+            .iter()
+            .position(|s| s.id == student.id)
+            .map_or(1, |index| index as u32 + 1);
+
+        let mut applicable: Vec<Discount> = self
+            .get_discounts(student.school_id)
+            .await?
+            .into_iter()
+            .filter(|d| d.valid_for(term))
+            .filter(|d| {
+                student.discount_ids.contains(&d.id)
+                    || d.min_sibling_position.is_some_and(|p| sibling_position >= p)
+            })
+            .collect();
+        // item discounts before whole-invoice ones
+        applicable.sort_by_key(|d| d.fee_item_id.is_none());
+
+        let mut remaining: Vec<(Uuid, u64)> = lines.iter().map(|l| (l.fee_item_id, l.amount_kobo)).collect();
+        let mut result = Vec::new();
+        for discount in applicable {
+            let amount_kobo = match discount.fee_item_id {
+                Some(fee_item_id) => match remaining.iter_mut().find(|(id, _)| *id == fee_item_id) {
+                    Some((_, left)) => {
+                        let amount = discount.amount_on(*left);
+                        *left -= amount;
+                        amount
+                    }
+                    None => 0, // the student isn't billed for this item
+                },
+                None => {
+                    let total: u64 = remaining.iter().map(|(_, left)| left).sum();
+                    let amount = discount.amount_on(total);
+                    // take it off the lines in order so later discounts see what's left
+                    let mut to_take = amount;
+                    for (_, left) in remaining.iter_mut() {
+                        let taken = to_take.min(*left);
+                        *left -= taken;
+                        to_take -= taken;
+                    }
+                    amount
+                }
+            };
+
+            if amount_kobo > 0 {
+                result.push(DiscountLine {
+                    discount_id: discount.id,
+                    name: discount.name,
+                    fee_item_id: discount.fee_item_id,
+                    amount_kobo,
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::guardians::CreateGuardianRequest;
+    use crate::models::testing::{self, TERM, date};
+
+    fn discount(name: &str, kind: DiscountKind, value: u64) -> DiscountRequest {
+        DiscountRequest {
+            name: name.to_string(),
+            kind,
+            value,
+            fee_item_id: None,
+            valid_from: None,
+            valid_until: None,
+            min_sibling_position: None,
+        }
+    }
+
+    async fn assign(store: &AppStore, student: &Student, discounts: &[&Discount]) {
+        let req = SetStudentDiscountsRequest { discount_ids: discounts.iter().map(|d| d.id).collect() };
+        store.set_student_discounts(student.school_id, student.id, req).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn item_discounts_come_off_before_whole_invoice_ones() {
+        let store = AppStore::new();
+        let school_id = testing::school(&store).await;
+        let tuition = testing::fee_item(&store, school_id, "Tuition", 10_000_000).await;
+        testing::fee_item(&store, school_id, "Uniform", 2_000_000).await;
+        let student = testing::student(&store, school_id, "Ada").await;
+
+        // created first, but still applied after the item discount
+        let half = store.create_discount(school_id, discount("Staff child", DiscountKind::Percentage, 50)).await.unwrap();
+        let mut req = discount("Tuition relief", DiscountKind::Fixed, 1_000_000);
+        req.fee_item_id = Some(tuition.id);
+        let relief = store.create_discount(school_id, req).await.unwrap();
+        assign(&store, &student, &[&half, &relief]).await;
+
+        let statement = store.get_fee_statement(school_id, student.id, TERM).await.unwrap();
+        let amounts: Vec<(Uuid, u64)> = statement.discounts.iter().map(|d| (d.discount_id, d.amount_kobo)).collect();
+        assert_eq!(amounts, vec![(relief.id, 1_000_000), (half.id, 5_500_000)]);
+        assert_eq!(statement.net_kobo, 5_500_000);
+    }
+
+    #[tokio::test]
+    async fn stacked_discounts_never_go_below_zero() {
+        let store = AppStore::new();
+        let school_id = testing::school(&store).await;
+        let tuition = testing::fee_item(&store, school_id, "Tuition", 10_000_000).await;
+        let student = testing::student(&store, school_id, "Ada").await;
+
+        let mut req = discount("Full scholarship", DiscountKind::Percentage, 100);
+        req.fee_item_id = Some(tuition.id);
+        let scholarship = store.create_discount(school_id, req).await.unwrap();
+        let bursary = store.create_discount(school_id, discount("Bursary", DiscountKind::Fixed, 500_000)).await.unwrap();
+        assign(&store, &student, &[&scholarship, &bursary]).await;
+
+        let statement = store.get_fee_statement(school_id, student.id, TERM).await.unwrap();
+        assert_eq!(statement.discounts.len(), 1);
+        assert_eq!(statement.discounts[0].amount_kobo, 10_000_000);
+        assert_eq!(statement.net_kobo, 0);
+    }
+
+    #[tokio::test]
+    async fn sibling_discounts_start_at_their_position() {
+        let store = AppStore::new();
+        let school_id = testing::school(&store).await;
+        testing::fee_item(&store, school_id, "Tuition", 10_000_000).await;
+        let mut family = Vec::new();
+        for name in ["Ada", "Bola", "Chidi", "Dayo"] {
+            family.push(testing::student(&store, school_id, name).await);
+        }
+        let guardian = CreateGuardianRequest {
+            name: "Ngozi Obi".to_string(),
+            phone: None,
+            email: None,
+            student_ids: family.iter().map(|s| s.id).collect(),
+        };
+        store.create_guardian(school_id, guardian).await.unwrap();
+
+        let mut req = discount("Third child", DiscountKind::Percentage, 10);
+        req.min_sibling_position = Some(3);
+        store.create_discount(school_id, req).await.unwrap();
+
+        let mut discounted = Vec::new();
+        for student in &family {
+            let statement = store.get_fee_statement(school_id, student.id, TERM).await.unwrap();
+            discounted.push(statement.discounts.iter().map(|d| d.amount_kobo).sum::<u64>());
+        }
+        assert_eq!(discounted, vec![0, 0, 1_000_000, 1_000_000]);
+    }
+
+    #[tokio::test]
+    async fn discounts_apply_only_to_terms_starting_in_their_window() {
+        let store = AppStore::new();
+        let school_id = testing::school(&store).await; // the term starts 2026-09-07
+        testing::fee_item(&store, school_id, "Tuition", 10_000_000).await;
+        let student = testing::student(&store, school_id, "Ada").await;
+
+        let mut expired = discount("Last year", DiscountKind::Fixed, 100_000);
+        expired.valid_until = Some(date(2026, 9, 6));
+        let mut current = discount("From this term", DiscountKind::Fixed, 200_000);
+        current.valid_from = Some(date(2026, 9, 7));
+        current.valid_until = Some(date(2026, 9, 7));
+        let mut upcoming = discount("Next term", DiscountKind::Fixed, 400_000);
+        upcoming.valid_from = Some(date(2026, 9, 8));
+        let mut created = Vec::new();
+        for req in [expired, current, upcoming] {
+            created.push(store.create_discount(school_id, req).await.unwrap());
+        }
+        assign(&store, &student, &created.iter().collect::<Vec<_>>()).await;
+
+        let statement = store.get_fee_statement(school_id, student.id, TERM).await.unwrap();
+        let applied: Vec<Uuid> = statement.discounts.iter().map(|d| d.discount_id).collect();
+        assert_eq!(applied, vec![created[1].id]);
+        assert_eq!(statement.net_kobo, 9_800_000);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, Student, discounts::DiscountLine, money::Currency};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum FeeCategory {
+    Tuition,
+    Levy,
+    Uniform,
+    Transport,
+    Other,
+}
+
+/// One price for a fee item. `grade_level` matches every class that starts
+/// with it (e.g. "JSS1" covers JSS1A and JSS1B); leaving it or `term_code`
+/// out makes the amount apply to all grades or all terms.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct FeeAmount {
+    pub grade_level: Option<String>,
+    pub term_code: Option<String>,
+    pub amount_kobo: u64,
+}
+
+impl FeeAmount {
+    fn matches(&self, class_name: Option<&str>, term_code: &str) -> bool {
+        let grade_matches = match (&self.grade_level, class_name) {
+            (None, _) => true,
+            (Some(grade), Some(class)) => class.starts_with(grade.as_str()),
+            (Some(_), None) => false,
+        };
+        let term_matches = self.term_code.as_deref().is_none_or(|t| t == term_code);
+        grade_matches && term_matches
+    }
+
+    // higher is more specific: grade and term beat grade, which beats term
+    fn specificity(&self) -> u8 {
+        (self.grade_level.is_some() as u8) * 2 + self.term_code.is_some() as u8
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct FeeItem {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub name: String,
+    pub category: FeeCategory,
+    pub mandatory: bool, // optional items are only billed to students who opted in
+    pub amounts: Vec<FeeAmount>,
+}
+
+#[derive(Deserialize)]
+pub struct FeeItemRequest {
+    pub name: String,
+    pub category: FeeCategory,
+    pub mandatory: bool,
+    pub amounts: Vec<FeeAmount>,
+}
+
+#[derive(Deserialize)]
+pub struct SetOptionalFeeItemsRequest {
+    pub fee_item_ids: Vec<Uuid>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct InitiatePaymentRequest {
+    pub term: Option<String>, // defaults to the term running today
+    pub amount_kobo: Option<u64>, // defaults to the next installment, or the whole balance
+}
+
+#[derive(Clone, Serialize)]
+pub struct FeeLine {
+    pub fee_item_id: Uuid,
+    pub name: String,
+    pub category: FeeCategory,
+    pub mandatory: bool,
+    pub amount_kobo: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FeeStatement {
+    pub student_id: Uuid,
+    pub term_code: String,
+    pub currency: Currency,
+    pub lines: Vec<FeeLine>,
+    pub total_kobo: u64,
+    pub discounts: Vec<DiscountLine>,
+    pub net_kobo: u64, // what the student actually owes: total less discounts
+}
+
+impl FeeItem {
+    /// The amount this item costs for a student in a term, if it applies.
+    pub fn amount_for(&self, student: &Student, term_code: &str) -> Option<u64> {
+        self.amounts
+            .iter()
+            .filter(|a| a.matches(student.class_name.as_deref(), term_code))
+            .max_by_key(|a| a.specificity())
+            .map(|a| a.amount_kobo)
+    }
+}
+
+fn validate_fee_item(req: &FeeItemRequest) -> Result<(), AppError> {
+    if req.name.trim().is_empty() {
+        return Err(AppError::invalid("name", "Fee item name cannot be empty"));
+    }
+    if req.amounts.is_empty() {
+        return Err(AppError::invalid("amounts", "Fee item needs at least one amount"));
+    }
+
+    let mut seen = HashSet::new();
+    for amount in &req.amounts {
+        if amount.amount_kobo == 0 {
+            return Err(AppError::invalid("amounts", "Amounts must be greater than zero"));
+        }
+        if !seen.insert((amount.grade_level.clone(), amount.term_code.clone())) {
+            return Err(AppError::invalid("amounts", "Each grade level and term pair can only be priced once"));
+        }
+    }
+    Ok(())
+}
+
+impl AppStore {
+    pub async fn create_fee_item(&self, school_id: Uuid, req: FeeItemRequest) -> Result<FeeItem, AppError> {
+        validate_fee_item(&req)?;
+
+        let item = FeeItem {
+            id: Uuid::new_v4(),
+            school_id,
+            name: req.name,
+            category: req.category,
+            mandatory: req.mandatory,
+            amounts: req.amounts,
+        };
+
+        self.fee_items
+            .lock()
+            .await
+            .insert(item.id.to_string(), item.clone());
+        Ok(item)
+    }
+
+    pub async fn update_fee_item(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        req: FeeItemRequest,
+    ) -> Result<FeeItem, AppError> {
+        validate_fee_item(&req)?;
+
+        let mut items = self.fee_items.lock().await;
+        let item = items
+            .values_mut()
+            .find(|i| i.id == id && i.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        item.name = req.name;
+        item.category = req.category;
+        item.mandatory = req.mandatory;
+        item.amounts = req.amounts;
+        Ok(item.clone())
+    }
+
+    pub async fn delete_fee_item(&self, school_id: Uuid, id: Uuid) -> Result<(), AppError> {
+        let mut items = self.fee_items.lock().await;
+        match items.get(&id.to_string()) {
+            Some(item) if item.school_id == school_id => {
+                items.remove(&id.to_string());
+                Ok(())
+            }
+            _ => Err(AppError::NotFound),
+        }
+    }
+
+    pub async fn get_fee_items(&self, school_id: Uuid) -> Result<Vec<FeeItem>, AppError> {
+        let items = self.fee_items.lock().await;
+        let mut list: Vec<FeeItem> = items
+            .values()
+            .filter(|i| i.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    pub async fn set_optional_fee_items(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        req: SetOptionalFeeItemsRequest,
+    ) -> Result<Student, AppError> {
+        let items = self.get_fee_items(school_id).await?;
+        for id in &req.fee_item_ids {
+            match items.iter().find(|i| &i.id == id) {
+                Some(item) if !item.mandatory => {}
+                Some(_) => {
+                    return Err(AppError::invalid("fee_item_ids", "Mandatory items are billed automatically"));
+                }
+                None => return Err(AppError::invalid("fee_item_ids", &format!("Unknown fee item {}", id))),
+            }
+        }
+
+        let mut students = self.students.lock().await;
+        let student = students
+            .values_mut()
+            .find(|s| s.id == student_id && s.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        student.optional_fee_items = req.fee_item_ids;
+        Ok(student.clone())
+    }
+
+    /// Everything a student is billed for in a term: all mandatory items
+    /// priced for their grade and term, plus the optional ones they opted
+    /// into, less any discounts they qualify for.
+    pub async fn get_fee_statement(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        term_code: &str,
+    ) -> Result<FeeStatement, AppError> {
+        let student = self.get_student(school_id, student_id).await?;
+        let term = self.get_term(school_id, term_code).await?;
+        let items = self.get_fee_items(school_id).await?;
+        let currency = self.get_school(school_id).await?.currency;
+
+        let lines: Vec<FeeLine> = items
+            .iter()
+            .filter(|item| item.mandatory || student.optional_fee_items.contains(&item.id))
+            .filter_map(|item| {
+                item.amount_for(&student, &term.code).map(|amount_kobo| FeeLine {
+                    fee_item_id: item.id,
+                    name: item.name.clone(),
+                    category: item.category,
+                    mandatory: item.mandatory,
+                    amount_kobo,
+                })
+            })
+            .collect();
+
+        let discounts = self.discounts_for(&student, &term, &lines).await?;
+        let total_kobo: u64 = lines.iter().map(|l| l.amount_kobo).sum();
+        let discount_kobo: u64 = discounts.iter().map(|d| d.amount_kobo).sum();
+
+        Ok(FeeStatement {
+            student_id,
+            term_code: term.code,
+            currency,
+            lines,
+            total_kobo,
+            discounts,
+            net_kobo: total_kobo - discount_kobo,
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, Student};
+
+/// A parent or guardian and the children they are responsible for. Students
+/// sharing a guardian are treated as siblings, e.g. for sibling discounts.
+#[derive(Clone, Serialize)]
+pub struct Guardian {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub student_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateGuardianRequest {
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub student_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct SetGuardianStudentsRequest {
+    pub student_ids: Vec<Uuid>,
+}
+
+impl AppStore {
+    async fn validate_guardian_students(&self, school_id: Uuid, student_ids: &[Uuid]) -> Result<(), AppError> {
+        let students = self.students.lock().await;
+        for id in student_ids {
+            if !students.values().any(|s| &s.id == id && s.school_id == school_id) {
+                return Err(AppError::invalid("student_ids", &format!("Unknown student {}", id)));
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn create_guardian(&self, school_id: Uuid, req: CreateGuardianRequest) -> Result<Guardian, AppError> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::invalid("name", "Guardian name cannot be empty"));
+        }
+        self.validate_guardian_students(school_id, &req.student_ids).await?;
+
+        let mut student_ids = req.student_ids;
+        student_ids.sort();
+        student_ids.dedup();
+        let guardian = Guardian {
+            id: Uuid::new_v4(),
+            school_id,
+            name: req.name,
+            phone: req.phone,
+            email: req.email,
+            student_ids,
+            created_at: Utc::now(),
+        };
+
+        self.guardians
+            .lock()
+            .await
+            .insert(guardian.id.to_string(), guardian.clone());
+        Ok(guardian)
+    }
+
+    pub async fn get_guardians(&self, school_id: Uuid) -> Result<Vec<Guardian>, AppError> {
+        let guardians = self.guardians.lock().await;
+        let mut list: Vec<Guardian> = guardians
+            .values()
+            .filter(|g| g.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    pub async fn get_guardian(&self, school_id: Uuid, id: Uuid) -> Result<Guardian, AppError> {
+        let guardians = self.guardians.lock().await;
+        guardians
+            .values()
+            .find(|g| g.id == id && g.school_id == school_id)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn set_guardian_students(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        req: SetGuardianStudentsRequest,
+    ) -> Result<Guardian, AppError> {
+        self.validate_guardian_students(school_id, &req.student_ids).await?;
+
+        let mut guardians = self.guardians.lock().await;
+        let guardian = guardians
+            .values_mut()
+            .find(|g| g.id == id && g.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        guardian.student_ids = req.student_ids;
+        guardian.student_ids.sort();
+        guardian.student_ids.dedup();
+        Ok(guardian.clone())
+    }
+
+    /// The student and everyone sharing a guardian with them, eldest first
+    /// (students without a date of birth go last, by admission number).
+    pub async fn get_siblings(&self, school_id: Uuid, student_id: Uuid) -> Result<Vec<Student>, AppError> {
+        let family: Vec<Uuid> = {
+            let guardians = self.guardians.lock().await;
+            guardians
+                .values()
+                .filter(|g| g.school_id == school_id && g.student_ids.contains(&student_id))
+                .flat_map(|g| g.student_ids.iter().copied())
+                .collect()
+        };
+
+        let students = self.students.lock().await;
+        let mut siblings: Vec<Student> = students
+            .values()
+            .filter(|s| s.school_id == school_id && (s.id == student_id || family.contains(&s.id)))
+            .cloned()
+            .collect();
+        siblings.sort_by(|a, b| {
+            let a_dob = (a.profile.date_of_birth.is_none(), a.profile.date_of_birth);
+            let b_dob = (b.profile.date_of_birth.is_none(), b.profile.date_of_birth);
+            a_dob.cmp(&b_dob).then_with(|| a.admission_number.cmp(&b.admission_number))
+        });
+        Ok(siblings)
+    }
+}
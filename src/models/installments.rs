@@ -0,0 +1,297 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, PaymentStatus, ledger::Invoice};
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct PlanInstallment {
+    pub label: String,
+    pub percentage: u32,
+    pub due_week: u32, // due by the end of this week of the term, counting from 1
+}
+
+#[derive(Clone, Serialize)]
+pub struct InstallmentPlan {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub name: String,
+    pub installments: Vec<PlanInstallment>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateInstallmentPlanRequest {
+    pub name: String,
+    pub installments: Vec<PlanInstallment>,
+}
+
+#[derive(Deserialize)]
+pub struct AssignInstallmentPlanRequest {
+    pub plan_id: Uuid,
+}
+
+/// An installment on a specific invoice. Due dates are fixed when the plan is
+/// assigned; amounts and paid figures follow the invoice total and are
+/// refreshed with it, paying installments off in order.
+#[derive(Clone, Serialize)]
+pub struct InvoiceInstallment {
+    pub label: String,
+    pub percentage: u32,
+    pub due_date: NaiveDate,
+    pub amount_kobo: u64,
+    pub paid_kobo: u64,
+    pub status: PaymentStatus,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct InstallmentAllocation {
+    pub label: String,
+    pub amount_kobo: u64,
+}
+
+/// Splits `total_kobo` by percentage (the last installment absorbs rounding)
+/// and fills each installment from `paid_kobo`, earliest first.
+pub fn apply_installment_amounts(installments: &mut [InvoiceInstallment], total_kobo: u64, paid_kobo: u64) {
+    let today = Utc::now().date_naive();
+    let mut allocated = 0;
+    let mut remaining_paid = paid_kobo;
+    let count = installments.len();
+
+    for (index, installment) in installments.iter_mut().enumerate() {
+        installment.amount_kobo = if index + 1 == count {
+            total_kobo - allocated
+        } else {
+            total_kobo * installment.percentage as u64 / 100
+        };
+        allocated += installment.amount_kobo;
+
+        installment.paid_kobo = remaining_paid.min(installment.amount_kobo);
+        remaining_paid -= installment.paid_kobo;
+
+        installment.status = if installment.paid_kobo >= installment.amount_kobo {
+            PaymentStatus::Paid
+        } else if today > installment.due_date {
+            PaymentStatus::Overdue
+        } else if installment.paid_kobo > 0 {
+            PaymentStatus::PartiallyPaid
+        } else {
+            PaymentStatus::Pending
+        };
+    }
+}
+
+/// Which installments a payment of `amount_kobo` settles, given how much had
+/// already been paid on the invoice before it.
+pub fn allocate_payment(invoice: &Invoice, paid_before_kobo: u64, amount_kobo: u64) -> Vec<InstallmentAllocation> {
+    let payment_start = paid_before_kobo;
+    let payment_end = paid_before_kobo + amount_kobo;
+    let mut installment_start = 0;
+
+    invoice
+        .installments
+        .iter()
+        .filter_map(|installment| {
+            let installment_end = installment_start + installment.amount_kobo;
+            let overlap = payment_end
+                .min(installment_end)
+                .saturating_sub(payment_start.max(installment_start));
+            installment_start = installment_end;
+
+            (overlap > 0).then(|| InstallmentAllocation {
+                label: installment.label.clone(),
+                amount_kobo: overlap,
+            })
+        })
+        .collect()
+}
+
+impl AppStore {
+    pub async fn create_installment_plan(
+        &self,
+        school_id: Uuid,
+        req: CreateInstallmentPlanRequest,
+    ) -> Result<InstallmentPlan, AppError> {
+        if req.installments.is_empty() {
+            return Err(AppError::invalid("installments", "A plan needs at least one installment"));
+        }
+        if req.installments.iter().any(|i| i.percentage == 0 || i.due_week == 0) {
+            return Err(AppError::invalid("installments", "Percentages and due weeks must be greater than zero"));
+        }
+        if req.installments.iter().map(|i| i.percentage).sum::<u32>() != 100 {
+            return Err(AppError::invalid("installments", "Installment percentages must add up to 100"));
+        }
+        if req.installments.windows(2).any(|w| w[1].due_week < w[0].due_week) {
+            return Err(AppError::invalid("installments", "Installments must be listed in due order"));
+        }
+
+        let plan = InstallmentPlan {
+            id: Uuid::new_v4(),
+            school_id,
+            name: req.name,
+            installments: req.installments,
+            created_at: Utc::now(),
+        };
+
+        self.installment_plans
+            .lock()
+            .await
+            .insert(plan.id.to_string(), plan.clone());
+        Ok(plan)
+    }
+
+    pub async fn get_installment_plans(&self, school_id: Uuid) -> Result<Vec<InstallmentPlan>, AppError> {
+        let plans = self.installment_plans.lock().await;
+        let mut list: Vec<InstallmentPlan> = plans
+            .values()
+            .filter(|p| p.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|p| p.created_at);
+        Ok(list)
+    }
+
+    /// Puts an invoice on a plan, dating each installment from the start of
+    /// the invoice's term. The invoice's own due date moves to the last one.
+    pub async fn assign_installment_plan(
+        &self,
+        school_id: Uuid,
+        invoice_id: Uuid,
+        req: AssignInstallmentPlanRequest,
+    ) -> Result<Invoice, AppError> {
+        let plan = {
+            let plans = self.installment_plans.lock().await;
+            plans
+                .values()
+                .find(|p| p.id == req.plan_id && p.school_id == school_id)
+                .cloned()
+                .ok_or(AppError::NotFound)?
+        };
+        let invoice = self.get_invoice(school_id, invoice_id).await?;
+        let term = self.get_term(school_id, &invoice.term_code).await?;
+
+        let installments: Vec<InvoiceInstallment> = plan
+            .installments
+            .iter()
+            .map(|i| InvoiceInstallment {
+                label: i.label.clone(),
+                percentage: i.percentage,
+                due_date: term.starts_on + Duration::days(i.due_week as i64 * 7 - 1),
+                amount_kobo: 0,
+                paid_kobo: 0,
+                status: PaymentStatus::Pending,
+            })
+            .collect();
+
+        {
+            let mut invoices = self.invoices.lock().await;
+            let invoice = invoices
+                .get_mut(&invoice_id.to_string())
+                .ok_or(AppError::NotFound)?;
+            if let Some(last) = installments.last() {
+                invoice.due_date = last.due_date;
+            }
+            invoice.installment_plan_id = Some(plan.id);
+            invoice.installments = installments;
+        }
+
+        let invoice = self.recalculate_invoice(invoice_id).await?;
+        self.refresh_student_status(school_id, invoice.student_id)
+            .await?;
+        Ok(invoice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{money::Currency, testing::date};
+
+    fn installment(label: &str, percentage: u32, due_date: NaiveDate) -> InvoiceInstallment {
+        InvoiceInstallment {
+            label: label.to_string(),
+            percentage,
+            due_date,
+            amount_kobo: 0,
+            paid_kobo: 0,
+            status: PaymentStatus::Pending,
+        }
+    }
+
+    // 40% then 60% of a 1,000,000 kobo invoice, neither due yet
+    fn invoice() -> Invoice {
+        let mut installments = vec![installment("First", 40, date(2099, 1, 31)), installment("Second", 60, date(2099, 3, 31))];
+        apply_installment_amounts(&mut installments, 1_000_000, 0);
+        Invoice {
+            id: Uuid::new_v4(),
+            school_id: Uuid::new_v4(),
+            student_id: Uuid::new_v4(),
+            term_code: "2026-T1".to_string(),
+            currency: Currency::Ngn,
+            line_items: Vec::new(),
+            discounts: Vec::new(),
+            due_date: date(2099, 3, 31),
+            total_kobo: 1_000_000,
+            amount_paid_kobo: 0,
+            balance_kobo: 1_000_000,
+            status: PaymentStatus::Pending,
+            installment_plan_id: None,
+            installments,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn allocations(list: Vec<InstallmentAllocation>) -> Vec<(String, u64)> {
+        list.into_iter().map(|a| (a.label, a.amount_kobo)).collect()
+    }
+
+    #[test]
+    fn the_last_installment_absorbs_rounding() {
+        let due = date(2099, 1, 31);
+        let mut installments = vec![installment("A", 33, due), installment("B", 33, due), installment("C", 34, due)];
+        apply_installment_amounts(&mut installments, 1_000_001, 0);
+
+        let amounts: Vec<u64> = installments.iter().map(|i| i.amount_kobo).collect();
+        assert_eq!(amounts, vec![330_000, 330_000, 340_001]);
+    }
+
+    #[test]
+    fn payments_fill_installments_in_order() {
+        let mut installments = invoice().installments;
+        apply_installment_amounts(&mut installments, 1_000_000, 500_000);
+
+        assert_eq!((installments[0].paid_kobo, &installments[0].status), (400_000, &PaymentStatus::Paid));
+        assert_eq!((installments[1].paid_kobo, &installments[1].status), (100_000, &PaymentStatus::PartiallyPaid));
+    }
+
+    #[test]
+    fn an_unpaid_installment_past_its_due_date_is_overdue() {
+        let mut installments = vec![installment("First", 40, date(2025, 1, 31)), installment("Second", 60, date(2099, 1, 31))];
+        apply_installment_amounts(&mut installments, 1_000_000, 100_000);
+
+        assert_eq!(installments[0].status, PaymentStatus::Overdue);
+        assert_eq!(installments[1].status, PaymentStatus::Pending);
+    }
+
+    #[test]
+    fn a_partial_payment_can_span_two_installments() {
+        let allocated = allocate_payment(&invoice(), 300_000, 300_000);
+        assert_eq!(allocations(allocated), vec![("First".to_string(), 100_000), ("Second".to_string(), 200_000)]);
+    }
+
+    #[test]
+    fn an_exact_payment_settles_one_installment() {
+        assert_eq!(allocations(allocate_payment(&invoice(), 0, 400_000)), vec![("First".to_string(), 400_000)]);
+        assert_eq!(allocations(allocate_payment(&invoice(), 400_000, 600_000)), vec![("Second".to_string(), 600_000)]);
+    }
+
+    #[test]
+    fn an_overpayment_is_allocated_only_up_to_what_is_left() {
+        let allocated = allocate_payment(&invoice(), 900_000, 500_000);
+        assert_eq!(allocations(allocated), vec![("Second".to_string(), 100_000)]);
+
+        assert!(allocate_payment(&invoice(), 1_000_000, 50_000).is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore, PaymentStatus,
+    ledger::{Invoice, LedgerEntryKind, NewLedgerEntry},
+};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum LateFeeKind {
+    #[default]
+    Flat,       // `value` is an amount in kobo
+    Percentage, // `value` is a percentage of the balance outstanding at the time
+}
+
+/// How a school penalises late payment. Penalties start once an invoice has
+/// been overdue for longer than `grace_days`; a one-time penalty is charged
+/// once, a recurring one again every `recurring_every_days` after that.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct LateFeePolicy {
+    pub enabled: bool,
+    pub kind: LateFeeKind,
+    pub value: u64,
+    pub grace_days: u32,
+    pub recurring_every_days: Option<u32>, // None for a one-time penalty
+    pub cap_kobo: Option<u64>, // most that can be charged on one invoice, waived penalties aside
+}
+
+#[derive(Clone, Serialize)]
+pub struct LateFee {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub invoice_id: Uuid,
+    pub occurrence: u32, // 1 for the first penalty on the invoice, 2 for the next...
+    pub amount_kobo: u64,
+    pub applied_at: DateTime<Utc>,
+    pub waived_by: Option<String>,
+    pub waived_reason: Option<String>,
+    pub waived_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct WaiveLateFeeRequest {
+    pub reason: String,
+}
+
+/// The date an unpaid invoice first fell overdue: its due date, or on an
+/// installment plan the due date of the earliest overdue installment.
+fn overdue_since(invoice: &Invoice, today: NaiveDate) -> Option<NaiveDate> {
+    if invoice.balance_kobo <= 0 {
+        return None;
+    }
+    if invoice.installments.is_empty() {
+        (today > invoice.due_date).then_some(invoice.due_date)
+    } else {
+        invoice
+            .installments
+            .iter()
+            .find(|i| i.status == PaymentStatus::Overdue)
+            .map(|i| i.due_date)
+    }
+}
+
+/// How many penalties are due `days_late` days past the grace period.
+fn due_occurrences(policy: &LateFeePolicy, days_late: i64) -> u32 {
+    if days_late <= 0 {
+        return 0;
+    }
+    match policy.recurring_every_days {
+        Some(every) => ((days_late - 1) / every as i64 + 1) as u32,
+        None => 1,
+    }
+}
+
+/// The next penalty on a `balance_kobo` balance, given what the invoice has
+/// already been `charged` in penalties that weren't waived.
+fn penalty_amount(policy: &LateFeePolicy, balance_kobo: u64, charged: u64) -> u64 {
+    let amount_kobo = match policy.kind {
+        LateFeeKind::Flat => policy.value,
+        LateFeeKind::Percentage => balance_kobo * policy.value / 100,
+    };
+    match policy.cap_kobo {
+        Some(cap) => amount_kobo.min(cap.saturating_sub(charged)),
+        None => amount_kobo,
+    }
+}
+
+impl AppStore {
+    pub async fn set_late_fee_policy(&self, school_id: Uuid, policy: LateFeePolicy) -> Result<LateFeePolicy, AppError> {
+        if policy.enabled && policy.value == 0 {
+            return Err(AppError::invalid("value", "Late fee value must be greater than zero"));
+        }
+        if policy.kind == LateFeeKind::Percentage && policy.value > 100 {
+            return Err(AppError::invalid("value", "A percentage late fee cannot exceed 100"));
+        }
+        if policy.recurring_every_days == Some(0) {
+            return Err(AppError::invalid("recurring_every_days", "Recurring penalties need an interval of at least one day"));
+        }
+
+        self.late_fee_policies
+            .lock()
+            .await
+            .insert(school_id.to_string(), policy.clone());
+        Ok(policy)
+    }
+
+    pub async fn get_late_fee_policy(&self, school_id: Uuid) -> Result<LateFeePolicy, AppError> {
+        let policies = self.late_fee_policies.lock().await;
+        Ok(policies.get(&school_id.to_string()).cloned().unwrap_or_default())
+    }
+
+    pub async fn get_invoice_late_fees(&self, school_id: Uuid, invoice_id: Uuid) -> Result<Vec<LateFee>, AppError> {
+        self.get_invoice(school_id, invoice_id).await?;
+
+        let fees = self.late_fees.lock().await;
+        let mut list: Vec<LateFee> = fees
+            .values()
+            .filter(|f| f.invoice_id == invoice_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|f| f.occurrence);
+        Ok(list)
+    }
+
+    /// Brings a school's overdue invoices up to date as of `today`: statuses
+    /// are refreshed (so students show as Overdue) and any penalties the
+    /// policy calls for that haven't been charged yet are posted. Running it
+    /// twice on the same day charges nothing new, even when the runs overlap:
+    /// each occurrence is claimed under the `late_fees` lock.
+    pub async fn apply_late_fees(
+        &self,
+        school_id: Uuid,
+        today: NaiveDate,
+        applied_by: &str,
+    ) -> Result<Vec<LateFee>, AppError> {
+        let policy = self.get_late_fee_policy(school_id).await?;
+        let open: Vec<Uuid> = {
+            let invoices = self.invoices.lock().await;
+            invoices
+                .values()
+                .filter(|i| i.school_id == school_id && i.balance_kobo > 0)
+                .map(|i| i.id)
+                .collect()
+        };
+
+        let mut applied = Vec::new();
+        for invoice_id in open {
+            // statuses are only worked out when the ledger moves, so a due
+            // date passing on its own needs a nudge
+            let invoice = self.recalculate_invoice(invoice_id).await?;
+            self.refresh_student_status(school_id, invoice.student_id)
+                .await?;
+
+            if !policy.enabled {
+                continue;
+            }
+            let Some(since) = overdue_since(&invoice, today) else {
+                continue;
+            };
+            let penalty_from = since + Duration::days(policy.grace_days as i64);
+            if today <= penalty_from {
+                continue;
+            }
+            let due = due_occurrences(&policy, (today - penalty_from).num_days());
+
+            let charged_before = self.get_invoice_late_fees(school_id, invoice_id).await?.len() as u32;
+            for occurrence in charged_before + 1..=due {
+                let balance = self.get_invoice(school_id, invoice_id).await?.balance_kobo.max(0) as u64;
+
+                // another run (the hourly job, or a bursar's) may be charging this invoice too
+                let fee = {
+                    let mut late_fees = self.late_fees.lock().await;
+                    let on_invoice: Vec<&LateFee> = late_fees.values().filter(|f| f.invoice_id == invoice_id).collect();
+                    if on_invoice.iter().any(|f| f.occurrence == occurrence) {
+                        continue;
+                    }
+                    let charged: u64 = on_invoice
+                        .iter()
+                        .filter(|f| f.waived_at.is_none())
+                        .map(|f| f.amount_kobo)
+                        .sum();
+                    let amount_kobo = penalty_amount(&policy, balance, charged);
+                    if amount_kobo == 0 {
+                        break;
+                    }
+
+                    let fee = LateFee {
+                        id: Uuid::new_v4(),
+                        school_id,
+                        student_id: invoice.student_id,
+                        invoice_id,
+                        occurrence,
+                        amount_kobo,
+                        applied_at: Utc::now(),
+                        waived_by: None,
+                        waived_reason: None,
+                        waived_at: None,
+                    };
+                    late_fees.insert(fee.id.to_string(), fee.clone());
+                    fee
+                };
+
+                self.post_ledger_entry(NewLedgerEntry {
+                    school_id,
+                    student_id: invoice.student_id,
+                    invoice_id: Some(invoice_id),
+                    payment_id: None,
+                    kind: LedgerEntryKind::Penalty,
+                    description: format!("Late payment penalty {} - {}", occurrence, invoice.term_code),
+                    debit_kobo: fee.amount_kobo,
+                    credit_kobo: 0,
+                    created_by: applied_by.to_string(),
+                })
+                .await?;
+
+                applied.push(fee);
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Forgives a penalty by posting an equal credit against it.
+    pub async fn waive_late_fee(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        waived_by: &str,
+        req: WaiveLateFeeRequest,
+    ) -> Result<LateFee, AppError> {
+        if req.reason.trim().is_empty() {
+            return Err(AppError::invalid("reason", "Give a reason for waiving the penalty"));
+        }
+
+        let fee = {
+            let mut fees = self.late_fees.lock().await;
+            let fee = fees
+                .values_mut()
+                .find(|f| f.id == id && f.school_id == school_id)
+                .ok_or(AppError::NotFound)?;
+            if fee.waived_at.is_some() {
+                return Err(AppError::Conflict("Penalty has already been waived".to_string()));
+            }
+            fee.waived_by = Some(waived_by.to_string());
+            fee.waived_reason = Some(req.reason.clone());
+            fee.waived_at = Some(Utc::now());
+            fee.clone()
+        };
+
+        self.post_ledger_entry(NewLedgerEntry {
+            school_id,
+            student_id: fee.student_id,
+            invoice_id: Some(fee.invoice_id),
+            payment_id: None,
+            kind: LedgerEntryKind::PenaltyWaiver,
+            description: format!("Late payment penalty {} waived: {}", fee.occurrence, req.reason),
+            debit_kobo: 0,
+            credit_kobo: fee.amount_kobo,
+            created_by: waived_by.to_string(),
+        })
+        .await?;
+
+        Ok(fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(kind: LateFeeKind, value: u64, recurring_every_days: Option<u32>, cap_kobo: Option<u64>) -> LateFeePolicy {
+        LateFeePolicy { enabled: true, kind, value, grace_days: 0, recurring_every_days, cap_kobo }
+    }
+
+    #[test]
+    fn nothing_is_due_within_the_grace_period() {
+        let weekly = policy(LateFeeKind::Flat, 100_000, Some(7), None);
+        assert_eq!(due_occurrences(&weekly, 0), 0);
+        assert_eq!(due_occurrences(&weekly, -3), 0);
+    }
+
+    #[test]
+    fn a_one_time_penalty_is_due_once() {
+        let once = policy(LateFeeKind::Flat, 100_000, None, None);
+        assert_eq!(due_occurrences(&once, 1), 1);
+        assert_eq!(due_occurrences(&once, 400), 1);
+    }
+
+    #[test]
+    fn a_recurring_penalty_falls_due_at_the_start_of_each_interval() {
+        let weekly = policy(LateFeeKind::Flat, 100_000, Some(7), None);
+        assert_eq!(due_occurrences(&weekly, 1), 1);
+        assert_eq!(due_occurrences(&weekly, 7), 1);
+        assert_eq!(due_occurrences(&weekly, 8), 2);
+        assert_eq!(due_occurrences(&weekly, 15), 3);
+
+        let daily = policy(LateFeeKind::Flat, 100_000, Some(1), None);
+        assert_eq!(due_occurrences(&daily, 30), 30);
+    }
+
+    #[test]
+    fn a_flat_penalty_ignores_the_balance() {
+        let flat = policy(LateFeeKind::Flat, 100_000, None, None);
+        assert_eq!(penalty_amount(&flat, 5_000_000, 0), 100_000);
+        assert_eq!(penalty_amount(&flat, 1, 0), 100_000);
+    }
+
+    #[test]
+    fn a_percentage_penalty_rounds_down() {
+        let five_percent = policy(LateFeeKind::Percentage, 5, None, None);
+        assert_eq!(penalty_amount(&five_percent, 5_000_000, 0), 250_000);
+        assert_eq!(penalty_amount(&five_percent, 333, 0), 16);
+    }
+
+    #[test]
+    fn the_cap_limits_what_is_charged_in_total() {
+        let capped = policy(LateFeeKind::Flat, 100_000, Some(7), Some(550_000));
+        assert_eq!(penalty_amount(&capped, 5_000_000, 400_000), 100_000);
+        assert_eq!(penalty_amount(&capped, 5_000_000, 500_000), 50_000);
+        assert_eq!(penalty_amount(&capped, 5_000_000, 550_000), 0);
+        assert_eq!(penalty_amount(&capped, 5_000_000, 600_000), 0);
+    }
+}
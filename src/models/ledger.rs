@@ -0,0 +1,761 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore, PaymentStatus,
+    discounts::DiscountLine,
+    installments::{InstallmentAllocation, InvoiceInstallment, allocate_payment, apply_installment_amounts},
+    manual_payments::ManualPaymentDetails,
+    money::Currency,
+    payment_settings::PaymentGateway,
+    subaccounts::PaymentSplit,
+};
+
+/// Days a generated invoice stays open when the caller doesn't set a due date.
+pub const DEFAULT_PAYMENT_WINDOW_DAYS: i64 = 14;
+
+// ---- Invoice ----
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct InvoiceLineItem {
+    pub fee_item_id: Option<Uuid>,
+    pub description: String,
+    pub amount_kobo: u64, // in the invoice's currency
+}
+
+#[derive(Clone, Serialize)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub term_code: String,
+    pub currency: Currency, // every amount on the invoice is in its minor unit
+    pub line_items: Vec<InvoiceLineItem>,
+    pub discounts: Vec<DiscountLine>, // fixed when the invoice is generated
+    pub due_date: NaiveDate,
+    // the fields below are derived from the ledger, see `recalculate_invoice`
+    pub total_kobo: u64,
+    pub amount_paid_kobo: u64,
+    pub balance_kobo: i64, // negative when the student is in credit
+    pub status: PaymentStatus,
+    pub installment_plan_id: Option<Uuid>,
+    pub installments: Vec<InvoiceInstallment>, // empty unless on an installment plan
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateInvoiceRequest {
+    pub term: String,
+    pub due_date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct InvoiceQuery {
+    pub term: Option<String>,
+    pub status: Option<PaymentStatus>,
+}
+
+// ---- Payment ----
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum PaymentMethod {
+    Online,
+    Cash,
+    BankTransfer,
+    Pos,
+}
+
+impl PaymentMethod {
+    pub fn label(self) -> &'static str {
+        match self {
+            PaymentMethod::Online => "Online",
+            PaymentMethod::Cash => "Cash",
+            PaymentMethod::BankTransfer => "Bank transfer",
+            PaymentMethod::Pos => "POS",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum TransactionStatus {
+    Pending,
+    Successful,
+    Failed,
+    Refunded, // the whole amount has been given back
+}
+
+#[derive(Clone, Serialize)]
+pub struct Payment {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub invoice_id: Uuid,
+    pub reference: String,
+    pub amount_kobo: u64,
+    pub currency: Currency, // always the invoice's; every amount on the payment is in its minor unit
+    pub method: PaymentMethod,
+    pub status: TransactionStatus,
+    pub installment_allocations: Vec<InstallmentAllocation>, // filled in once the payment succeeds
+    pub refunded_kobo: u64,
+    pub receipt_number: Option<String>, // issued once the payment succeeds
+    pub manual: Option<ManualPaymentDetails>, // set for payments recorded by staff
+    pub split: Option<PaymentSplit>, // set for platform payments settled to a school subaccount
+    pub gateway: Option<PaymentGateway>, // set for online payments; refunds and checks go back to it
+    pub created_at: DateTime<Utc>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+// ---- Ledger ----
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum LedgerEntryKind {
+    Charge,
+    Payment,
+    Refund,        // a debit reversing (part of) a payment
+    Discount,      // a credit for a discount or scholarship
+    Penalty,       // a debit for paying late
+    PenaltyWaiver, // a credit cancelling a penalty
+}
+
+/// One movement on a student's fee account. Debits increase what the student
+/// owes, credits reduce it; every balance in the system is the sum of these.
+/// Amounts are in the minor unit of the school's currency.
+#[derive(Clone, Serialize)]
+pub struct LedgerEntry {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub invoice_id: Option<Uuid>,
+    pub payment_id: Option<Uuid>,
+    pub kind: LedgerEntryKind,
+    pub description: String,
+    pub debit_kobo: u64,
+    pub credit_kobo: u64,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LedgerLine {
+    #[serde(flatten)]
+    pub entry: LedgerEntry,
+    pub running_balance_kobo: i64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StudentLedger {
+    pub student_id: Uuid,
+    pub currency: Currency, // every amount in the ledger is in its minor unit
+    pub entries: Vec<LedgerLine>,
+    pub balance_kobo: i64,
+}
+
+/// Fields needed to post a ledger entry; ids and timestamps are filled in.
+pub struct NewLedgerEntry {
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub invoice_id: Option<Uuid>,
+    pub payment_id: Option<Uuid>,
+    pub kind: LedgerEntryKind,
+    pub description: String,
+    pub debit_kobo: u64,
+    pub credit_kobo: u64,
+    pub created_by: String,
+}
+
+/// What a payer is asked for by default: whatever is left on the earliest
+/// unpaid installment, or the whole balance.
+pub fn amount_due_next(invoice: &Invoice) -> u64 {
+    let balance_kobo = invoice.balance_kobo.max(0) as u64;
+    invoice
+        .installments
+        .iter()
+        .find(|i| i.paid_kobo < i.amount_kobo)
+        .map(|i| i.amount_kobo - i.paid_kobo)
+        .unwrap_or(balance_kobo)
+        .min(balance_kobo)
+}
+
+/// Paid once nothing is owed; Overdue if the due date (or, on a plan, any
+/// installment's due date) has passed unpaid; otherwise PartiallyPaid if
+/// something came in, else Pending.
+pub fn invoice_status(invoice: &Invoice) -> PaymentStatus {
+    let overdue = if invoice.installments.is_empty() {
+        Utc::now().date_naive() > invoice.due_date
+    } else {
+        invoice
+            .installments
+            .iter()
+            .any(|i| i.status == PaymentStatus::Overdue)
+    };
+
+    if invoice.balance_kobo <= 0 {
+        PaymentStatus::Paid
+    } else if overdue {
+        PaymentStatus::Overdue
+    } else if invoice.amount_paid_kobo > 0 {
+        PaymentStatus::PartiallyPaid
+    } else {
+        PaymentStatus::Pending
+    }
+}
+
+impl AppStore {
+    // -- Ledger methods --
+
+    pub async fn post_ledger_entry(&self, new: NewLedgerEntry) -> Result<LedgerEntry, AppError> {
+        let entry = LedgerEntry {
+            id: Uuid::new_v4(),
+            school_id: new.school_id,
+            student_id: new.student_id,
+            invoice_id: new.invoice_id,
+            payment_id: new.payment_id,
+            kind: new.kind,
+            description: new.description,
+            debit_kobo: new.debit_kobo,
+            credit_kobo: new.credit_kobo,
+            created_by: new.created_by,
+            created_at: Utc::now(),
+        };
+
+        self.ledger.lock().await.push(entry.clone());
+        // balances moved, possibly from a webhook or a job rather than a request
+        self.invalidate_dashboard(entry.school_id).await;
+
+        if let Some(invoice_id) = entry.invoice_id {
+            self.recalculate_invoice(invoice_id).await?;
+        }
+        self.refresh_student_status(entry.school_id, entry.student_id)
+            .await?;
+        Ok(entry)
+    }
+
+    /// Rebuilds an invoice's totals and status from its ledger entries.
+    pub async fn recalculate_invoice(&self, invoice_id: Uuid) -> Result<Invoice, AppError> {
+        let (charges, refunds, credits, paid, waived) = {
+            let ledger = self.ledger.lock().await;
+            ledger
+                .iter()
+                .filter(|e| e.invoice_id == Some(invoice_id))
+                .fold((0u64, 0u64, 0u64, 0u64, 0u64), |(d, r, c, p, w), e| match e.kind {
+                    // refunds reopen the balance without adding to what was billed
+                    LedgerEntryKind::Refund => (d, r + e.debit_kobo, c + e.credit_kobo, p, w),
+                    LedgerEntryKind::Payment => (d + e.debit_kobo, r, c + e.credit_kobo, p + e.credit_kobo, w),
+                    // a waived penalty no longer counts as billed
+                    LedgerEntryKind::PenaltyWaiver => (d, r, c, p, w + e.credit_kobo),
+                    _ => (d + e.debit_kobo, r, c + e.credit_kobo, p, w),
+                })
+        };
+        let charges = charges.saturating_sub(waived);
+
+        let mut invoices = self.invoices.lock().await;
+        let invoice = invoices
+            .get_mut(&invoice_id.to_string())
+            .ok_or(AppError::NotFound)?;
+
+        invoice.total_kobo = charges;
+        invoice.amount_paid_kobo = paid.saturating_sub(refunds);
+        invoice.balance_kobo = (charges + refunds) as i64 - credits as i64;
+        // credits other than payments (e.g. discounts) still clear installments
+        let settled = (charges as i64 - invoice.balance_kobo.max(0)).max(0) as u64;
+        apply_installment_amounts(&mut invoice.installments, charges, settled);
+        invoice.status = invoice_status(invoice);
+        Ok(invoice.clone())
+    }
+
+    /// A student's status summarises all their invoices: any Overdue invoice
+    /// wins, then any outstanding balance, and Paid only when everything is.
+    pub async fn refresh_student_status(&self, school_id: Uuid, student_id: Uuid) -> Result<PaymentStatus, AppError> {
+        let statuses: Vec<PaymentStatus> = {
+            let invoices = self.invoices.lock().await;
+            invoices
+                .values()
+                .filter(|i| i.school_id == school_id && i.student_id == student_id)
+                .map(|i| i.status.clone())
+                .collect()
+        };
+
+        let status = if statuses.contains(&PaymentStatus::Overdue) {
+            PaymentStatus::Overdue
+        } else if statuses.contains(&PaymentStatus::PartiallyPaid) {
+            PaymentStatus::PartiallyPaid
+        } else if statuses.contains(&PaymentStatus::Pending) || statuses.is_empty() {
+            PaymentStatus::Pending
+        } else {
+            PaymentStatus::Paid
+        };
+
+        let mut students = self.students.lock().await;
+        let student = students
+            .values_mut()
+            .find(|s| s.id == student_id && s.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+        student.status = status.clone();
+        Ok(status)
+    }
+
+    pub async fn get_student_ledger(&self, school_id: Uuid, student_id: Uuid) -> Result<StudentLedger, AppError> {
+        self.get_student(school_id, student_id).await?;
+        let currency = self.get_school(school_id).await?.currency;
+
+        let ledger = self.ledger.lock().await;
+        let mut balance: i64 = 0;
+        let entries: Vec<LedgerLine> = ledger
+            .iter()
+            .filter(|e| e.school_id == school_id && e.student_id == student_id)
+            .map(|entry| {
+                balance += entry.debit_kobo as i64 - entry.credit_kobo as i64;
+                LedgerLine {
+                    entry: entry.clone(),
+                    running_balance_kobo: balance,
+                }
+            })
+            .collect();
+
+        Ok(StudentLedger {
+            student_id,
+            currency,
+            entries,
+            balance_kobo: balance,
+        })
+    }
+
+    // -- Invoice methods --
+
+    /// Bills a student for a term from the fee schedule: one line item and
+    /// one Charge ledger entry per applicable fee item, then one Discount
+    /// credit per discount the student qualifies for.
+    pub async fn create_invoice(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        created_by: &str,
+        req: CreateInvoiceRequest,
+    ) -> Result<Invoice, AppError> {
+        let term = self.get_term(school_id, &req.term).await?;
+        let statement = self.get_fee_statement(school_id, student_id, &term.code).await?;
+        if statement.lines.is_empty() {
+            return Err(AppError::invalid("term", "No fees are scheduled for this student in this term"));
+        }
+
+        let due_date = req.due_date.unwrap_or_else(|| {
+            Utc::now().date_naive().max(term.starts_on) + Duration::days(DEFAULT_PAYMENT_WINDOW_DAYS)
+        });
+
+        let invoice = {
+            let mut invoices = self.invoices.lock().await;
+            // read under the invoices lock, so the school can't switch currency mid-way
+            let currency = self.get_school(school_id).await?.currency;
+            let exists = invoices.values().any(|i| {
+                i.school_id == school_id && i.student_id == student_id && i.term_code == term.code
+            });
+            if exists {
+                return Err(AppError::Conflict("Student already has an invoice for this term".to_string()));
+            }
+
+            let invoice = Invoice {
+                id: Uuid::new_v4(),
+                school_id,
+                student_id,
+                term_code: term.code.clone(),
+                currency,
+                line_items: statement
+                    .lines
+                    .iter()
+                    .map(|l| InvoiceLineItem {
+                        fee_item_id: Some(l.fee_item_id),
+                        description: l.name.clone(),
+                        amount_kobo: l.amount_kobo,
+                    })
+                    .collect(),
+                discounts: statement.discounts.clone(),
+                due_date,
+                total_kobo: 0,
+                amount_paid_kobo: 0,
+                balance_kobo: 0,
+                status: PaymentStatus::Pending,
+                installment_plan_id: None,
+                installments: Vec::new(),
+                created_at: Utc::now(),
+            };
+            invoices.insert(invoice.id.to_string(), invoice.clone());
+            invoice
+        };
+
+        for line in &invoice.line_items {
+            self.post_ledger_entry(NewLedgerEntry {
+                school_id,
+                student_id,
+                invoice_id: Some(invoice.id),
+                payment_id: None,
+                kind: LedgerEntryKind::Charge,
+                description: format!("{} - {}", line.description, term.name),
+                debit_kobo: line.amount_kobo,
+                credit_kobo: 0,
+                created_by: created_by.to_string(),
+            })
+            .await?;
+        }
+        for discount in &invoice.discounts {
+            self.post_ledger_entry(NewLedgerEntry {
+                school_id,
+                student_id,
+                invoice_id: Some(invoice.id),
+                payment_id: None,
+                kind: LedgerEntryKind::Discount,
+                description: format!("{} - {}", discount.name, term.name),
+                debit_kobo: 0,
+                credit_kobo: discount.amount_kobo,
+                created_by: created_by.to_string(),
+            })
+            .await?;
+        }
+
+        self.get_invoice(school_id, invoice.id).await
+    }
+
+    pub async fn get_invoice(&self, school_id: Uuid, id: Uuid) -> Result<Invoice, AppError> {
+        let invoices = self.invoices.lock().await;
+        invoices
+            .values()
+            .find(|i| i.id == id && i.school_id == school_id)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    /// The student's invoice for a term, billing the term first if nobody
+    /// has invoiced it yet.
+    pub async fn find_or_create_invoice(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        term_code: &str,
+        created_by: &str,
+    ) -> Result<Invoice, AppError> {
+        match self.find_student_invoice(school_id, student_id, term_code).await {
+            Ok(invoice) => Ok(invoice),
+            Err(_) => {
+                let req = CreateInvoiceRequest { term: term_code.to_string(), due_date: None };
+                self.create_invoice(school_id, student_id, created_by, req).await
+            }
+        }
+    }
+
+    pub async fn find_student_invoice(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        term_code: &str,
+    ) -> Result<Invoice, AppError> {
+        let invoices = self.invoices.lock().await;
+        invoices
+            .values()
+            .find(|i| i.school_id == school_id && i.student_id == student_id && i.term_code == term_code)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn get_student_invoices(&self, school_id: Uuid, student_id: Uuid) -> Result<Vec<Invoice>, AppError> {
+        self.list_invoices(school_id, InvoiceQuery { term: None, status: None })
+            .await
+            .map(|list| list.into_iter().filter(|i| i.student_id == student_id).collect())
+    }
+
+    pub async fn list_invoices(&self, school_id: Uuid, query: InvoiceQuery) -> Result<Vec<Invoice>, AppError> {
+        let invoices = self.invoices.lock().await;
+        let mut list: Vec<Invoice> = invoices
+            .values()
+            .filter(|i| i.school_id == school_id)
+            .filter(|i| query.term.as_ref().is_none_or(|t| &i.term_code == t))
+            .filter(|i| query.status.as_ref().is_none_or(|s| &i.status == s))
+            .cloned()
+            .collect();
+        list.sort_by_key(|i| i.created_at);
+        Ok(list)
+    }
+
+    // -- Payment methods --
+
+    pub async fn create_pending_payment(
+        &self,
+        invoice: &Invoice,
+        reference: String,
+        amount_kobo: u64,
+        method: PaymentMethod,
+    ) -> Result<Payment, AppError> {
+        let mut payments = self.payments.lock().await;
+        if payments.values().any(|p| p.reference == reference) {
+            return Err(AppError::Conflict("Payment reference already exists".to_string()));
+        }
+
+        let payment = Payment {
+            id: Uuid::new_v4(),
+            school_id: invoice.school_id,
+            student_id: invoice.student_id,
+            invoice_id: invoice.id,
+            reference,
+            amount_kobo,
+            currency: invoice.currency,
+            method,
+            status: TransactionStatus::Pending,
+            installment_allocations: Vec::new(),
+            refunded_kobo: 0,
+            receipt_number: None,
+            manual: None,
+            split: None,
+            gateway: None,
+            created_at: Utc::now(),
+            paid_at: None,
+        };
+
+        payments.insert(payment.id.to_string(), payment.clone());
+        Ok(payment)
+    }
+
+    pub async fn set_payment_gateway(&self, reference: &str, gateway: PaymentGateway) -> Result<(), AppError> {
+        let mut payments = self.payments.lock().await;
+        let payment = payments
+            .values_mut()
+            .find(|p| p.reference == reference)
+            .ok_or(AppError::NotFound)?;
+        payment.gateway = Some(gateway);
+        Ok(())
+    }
+
+    pub async fn get_student_payments(&self, school_id: Uuid, student_id: Uuid) -> Result<Vec<Payment>, AppError> {
+        let payments = self.payments.lock().await;
+        let mut list: Vec<Payment> = payments
+            .values()
+            .filter(|p| p.school_id == school_id && p.student_id == student_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|p| p.created_at);
+        Ok(list)
+    }
+
+    pub async fn fail_payment(&self, reference: &str) -> Result<Payment, AppError> {
+        let mut payments = self.payments.lock().await;
+        let payment = payments
+            .values_mut()
+            .find(|p| p.reference == reference)
+            .ok_or(AppError::NotFound)?;
+
+        if payment.status == TransactionStatus::Pending {
+            payment.status = TransactionStatus::Failed;
+        }
+        Ok(payment.clone())
+    }
+
+    /// Marks a payment successful and credits the ledger with the amount
+    /// actually received. Calling it again for the same reference is a no-op.
+    pub async fn complete_payment(&self, reference: &str, amount_kobo: u64) -> Result<Payment, AppError> {
+        self.settle_payment(reference, amount_kobo, "system").await
+    }
+
+    /// Like `complete_payment`, with the ledger entry attributed to `posted_by`.
+    pub async fn settle_payment(&self, reference: &str, amount_kobo: u64, posted_by: &str) -> Result<Payment, AppError> {
+        let mut payment = {
+            let mut payments = self.payments.lock().await;
+            let payment = payments
+                .values_mut()
+                .find(|p| p.reference == reference)
+                .ok_or(AppError::NotFound)?;
+
+            if matches!(payment.status, TransactionStatus::Successful | TransactionStatus::Refunded) {
+                return Ok(payment.clone());
+            }
+            payment.status = TransactionStatus::Successful;
+            payment.amount_kobo = amount_kobo;
+            // cash and transfers are dated by when the money changed hands
+            payment.paid_at = match &payment.manual {
+                Some(manual) => manual.paid_on.and_hms_opt(0, 0, 0).map(|t| t.and_utc()),
+                None => Some(Utc::now()),
+            };
+            payment.clone()
+        };
+
+        let invoice = self.get_invoice(payment.school_id, payment.invoice_id).await?;
+        let settled_before = (invoice.total_kobo as i64 - invoice.balance_kobo.max(0)).max(0) as u64;
+        payment.installment_allocations = allocate_payment(&invoice, settled_before, amount_kobo);
+        if let Some(stored) = self.payments.lock().await.get_mut(&payment.id.to_string()) {
+            stored.installment_allocations = payment.installment_allocations.clone();
+        }
+
+        self.post_ledger_entry(NewLedgerEntry {
+            school_id: payment.school_id,
+            student_id: payment.student_id,
+            invoice_id: Some(payment.invoice_id),
+            payment_id: Some(payment.id),
+            kind: LedgerEntryKind::Payment,
+            description: format!("Payment {}", payment.reference),
+            debit_kobo: 0,
+            credit_kobo: amount_kobo,
+            created_by: posted_by.to_string(),
+        })
+        .await?;
+
+        let receipt = self.issue_receipt(payment.id).await?;
+        payment.receipt_number = Some(receipt.number);
+        Ok(payment)
+    }
+
+    pub async fn find_payment_by_reference(&self, school_id: Uuid, reference: &str) -> Result<Payment, AppError> {
+        let payments = self.payments.lock().await;
+        payments
+            .values()
+            .find(|p| p.reference == reference && p.school_id == school_id)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Online payments still waiting on the gateway that were started before
+    /// `started_before`, oldest first.
+    pub async fn get_stale_pending_payments(&self, started_before: DateTime<Utc>) -> Vec<Payment> {
+        let payments = self.payments.lock().await;
+        let mut list: Vec<Payment> = payments
+            .values()
+            .filter(|p| p.method == PaymentMethod::Online && p.status == TransactionStatus::Pending)
+            .filter(|p| p.created_at < started_before)
+            .cloned()
+            .collect();
+        list.sort_by_key(|p| p.created_at);
+        list
+    }
+
+    /// Applies the outcome the gateway reported for a transaction. Outcomes
+    /// that are not final yet leave the payment pending, and a charge in
+    /// another currency than the invoice's settles nothing.
+    pub async fn apply_gateway_outcome(
+        &self,
+        reference: &str,
+        status: TransactionStatus,
+        amount_kobo: u64,
+        currency: &str,
+    ) -> Result<Payment, AppError> {
+        if status == TransactionStatus::Successful {
+            let payments = self.payments.lock().await;
+            let payment = payments
+                .values()
+                .find(|p| p.reference == reference)
+                .ok_or(AppError::NotFound)?;
+            payment.currency.expect(Some(currency))?;
+        }
+
+        match status {
+            TransactionStatus::Successful => self.complete_payment(reference, amount_kobo).await,
+            TransactionStatus::Failed => self.fail_payment(reference).await,
+            TransactionStatus::Pending | TransactionStatus::Refunded => {
+                let payments = self.payments.lock().await;
+                payments
+                    .values()
+                    .find(|p| p.reference == reference)
+                    .cloned()
+                    .ok_or(AppError::NotFound)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{billed_student, date, paid_payment};
+
+    fn far_off() -> NaiveDate {
+        date(2099, 12, 31)
+    }
+
+    async fn student_status(store: &AppStore, invoice: &Invoice) -> PaymentStatus {
+        store.get_student(invoice.school_id, invoice.student_id).await.unwrap().status
+    }
+
+    #[tokio::test]
+    async fn a_new_invoice_is_pending_for_its_full_amount() {
+        let (store, invoice) = billed_student(5_000_000, far_off()).await;
+
+        assert_eq!(invoice.total_kobo, 5_000_000);
+        assert_eq!(invoice.balance_kobo, 5_000_000);
+        assert_eq!(invoice.amount_paid_kobo, 0);
+        assert_eq!(invoice.status, PaymentStatus::Pending);
+        assert_eq!(student_status(&store, &invoice).await, PaymentStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn payments_move_an_invoice_from_partly_paid_to_paid() {
+        let (store, invoice) = billed_student(5_000_000, far_off()).await;
+
+        paid_payment(&store, &invoice, "sch-1", 2_000_000).await;
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!((invoice.amount_paid_kobo, invoice.balance_kobo), (2_000_000, 3_000_000));
+        assert_eq!(invoice.status, PaymentStatus::PartiallyPaid);
+        assert_eq!(student_status(&store, &invoice).await, PaymentStatus::PartiallyPaid);
+
+        paid_payment(&store, &invoice, "sch-2", 3_000_000).await;
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(invoice.balance_kobo, 0);
+        assert_eq!(invoice.status, PaymentStatus::Paid);
+        assert_eq!(student_status(&store, &invoice).await, PaymentStatus::Paid);
+    }
+
+    #[tokio::test]
+    async fn an_overpayment_leaves_a_credit_balance() {
+        let (store, invoice) = billed_student(5_000_000, far_off()).await;
+
+        paid_payment(&store, &invoice, "sch-1", 6_000_000).await;
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(invoice.amount_paid_kobo, 6_000_000);
+        assert_eq!(invoice.balance_kobo, -1_000_000);
+        assert_eq!(invoice.status, PaymentStatus::Paid);
+
+        let ledger = store.get_student_ledger(invoice.school_id, invoice.student_id).await.unwrap();
+        assert_eq!(ledger.balance_kobo, -1_000_000);
+    }
+
+    #[tokio::test]
+    async fn settling_a_payment_again_changes_nothing() {
+        let (store, invoice) = billed_student(5_000_000, far_off()).await;
+        let first = paid_payment(&store, &invoice, "sch-1", 2_000_000).await;
+        let entries = store.ledger.lock().await.len();
+
+        let again = store.settle_payment("sch-1", 2_000_000, "bursar").await.unwrap();
+        assert_eq!(again.status, TransactionStatus::Successful);
+        assert_eq!(again.receipt_number, first.receipt_number);
+        assert_eq!(store.ledger.lock().await.len(), entries);
+        assert_eq!(store.receipts.lock().await.len(), 1);
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(invoice.balance_kobo, 3_000_000);
+    }
+
+    #[tokio::test]
+    async fn each_settled_payment_gets_a_receipt() {
+        let (store, invoice) = billed_student(5_000_000, far_off()).await;
+
+        let first = paid_payment(&store, &invoice, "sch-1", 1_000_000).await;
+        let second = paid_payment(&store, &invoice, "sch-2", 1_000_000).await;
+
+        let (first, second) = (first.receipt_number.unwrap(), second.receipt_number.unwrap());
+        assert_ne!(first, second);
+        let receipts = store.receipts.lock().await;
+        assert_eq!(receipts.len(), 2);
+        assert!(receipts.values().any(|r| r.number == first && r.balance_after_kobo == 4_000_000));
+    }
+
+    #[tokio::test]
+    async fn an_unpaid_invoice_past_its_due_date_is_overdue() {
+        let (store, invoice) = billed_student(5_000_000, date(2025, 1, 31)).await;
+        assert_eq!(invoice.status, PaymentStatus::Overdue);
+        assert_eq!(student_status(&store, &invoice).await, PaymentStatus::Overdue);
+
+        // part-paid is still overdue; paid in full is not
+        paid_payment(&store, &invoice, "sch-1", 1_000_000).await;
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(invoice.status, PaymentStatus::Overdue);
+
+        paid_payment(&store, &invoice, "sch-2", 4_000_000).await;
+        let invoice = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(invoice.status, PaymentStatus::Paid);
+    }
+}
@@ -0,0 +1,244 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore,
+    ledger::{Payment, PaymentMethod, TransactionStatus},
+};
+
+// keeps uploads to something a phone photo or a scanned slip fits in
+pub const MAX_EVIDENCE_BYTES: usize = 5 * 1024 * 1024;
+
+// photos and scans only; anything a browser could run (HTML, SVG) is refused
+const EVIDENCE_CONTENT_TYPES: [&str; 6] =
+    ["image/jpeg", "image/png", "image/gif", "image/webp", "image/heic", "application/pdf"];
+
+/// Whether manual payments need a second person to sign them off before
+/// they reach the ledger.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct ManualPaymentPolicy {
+    pub require_approval: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct EvidenceInfo {
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: usize,
+}
+
+/// An uploaded teller slip, transfer receipt or POS printout.
+#[derive(Clone)]
+pub struct PaymentEvidence {
+    pub info: EvidenceInfo,
+    pub bytes: Vec<u8>,
+}
+
+/// What staff captured when recording a cash, transfer or POS payment.
+#[derive(Clone, Serialize)]
+pub struct ManualPaymentDetails {
+    pub paid_on: NaiveDate,
+    pub external_reference: Option<String>, // teller number or bank transfer reference
+    pub note: Option<String>,
+    pub evidence: Option<EvidenceInfo>,
+    pub recorded_by: String,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+}
+
+pub struct ManualPaymentInput {
+    pub term: Option<String>, // defaults to the term running today
+    pub method: PaymentMethod,
+    pub amount_kobo: u64,
+    pub paid_on: NaiveDate,
+    pub external_reference: Option<String>,
+    pub note: Option<String>,
+    pub evidence: Option<PaymentEvidence>,
+}
+
+#[derive(Deserialize)]
+pub struct RejectPaymentRequest {
+    pub reason: String,
+}
+
+impl AppStore {
+    pub async fn set_manual_payment_policy(
+        &self,
+        school_id: Uuid,
+        policy: ManualPaymentPolicy,
+    ) -> Result<ManualPaymentPolicy, AppError> {
+        self.manual_payment_policies
+            .lock()
+            .await
+            .insert(school_id.to_string(), policy.clone());
+        Ok(policy)
+    }
+
+    pub async fn get_manual_payment_policy(&self, school_id: Uuid) -> Result<ManualPaymentPolicy, AppError> {
+        let policies = self.manual_payment_policies.lock().await;
+        Ok(policies.get(&school_id.to_string()).cloned().unwrap_or_default())
+    }
+
+    /// Records money received outside the gateway. It goes straight to the
+    /// ledger unless the school wants a second person to approve it first.
+    pub async fn record_manual_payment(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        recorded_by: &str,
+        input: ManualPaymentInput,
+    ) -> Result<Payment, AppError> {
+        if input.method == PaymentMethod::Online {
+            return Err(AppError::invalid("method", "Online payments are recorded by the gateway"));
+        }
+        if input.paid_on > Utc::now().date_naive() {
+            return Err(AppError::invalid("paid_on", "Payment date cannot be in the future"));
+        }
+        let has_reference = input.external_reference.as_deref().is_some_and(|r| !r.trim().is_empty());
+        if input.method != PaymentMethod::Cash && !has_reference {
+            return Err(AppError::invalid("external_reference", "Transfers and POS payments need a bank or terminal reference"));
+        }
+        if let Some(evidence) = &input.evidence
+            && evidence.bytes.len() > MAX_EVIDENCE_BYTES
+        {
+            return Err(AppError::invalid("evidence", "Evidence files must be 5 MB or smaller"));
+        }
+        if let Some(evidence) = &input.evidence
+            && !EVIDENCE_CONTENT_TYPES.contains(&evidence.info.content_type.as_str())
+        {
+            return Err(AppError::invalid("evidence", "Evidence must be a JPEG, PNG, GIF, WebP or HEIC image, or a PDF"));
+        }
+
+        let term = match &input.term {
+            Some(code) => self.get_term(school_id, code).await?,
+            None => self.get_current_term(school_id).await?,
+        };
+        let invoice = self
+            .find_or_create_invoice(school_id, student_id, &term.code, recorded_by)
+            .await?;
+        if input.amount_kobo == 0 || input.amount_kobo as i64 > invoice.balance_kobo {
+            return Err(AppError::invalid("amount_kobo", "Amount must be between 1 and the outstanding balance"));
+        }
+
+        let mut payment = self
+            .create_pending_payment(&invoice, format!("manual-{}", Uuid::new_v4()), input.amount_kobo, input.method)
+            .await?;
+        payment.manual = Some(ManualPaymentDetails {
+            paid_on: input.paid_on,
+            external_reference: input.external_reference,
+            note: input.note,
+            evidence: input.evidence.as_ref().map(|e| e.info.clone()),
+            recorded_by: recorded_by.to_string(),
+            reviewed_by: None,
+            reviewed_at: None,
+            rejection_reason: None,
+        });
+        self.payments
+            .lock()
+            .await
+            .insert(payment.id.to_string(), payment.clone());
+        if let Some(evidence) = input.evidence {
+            self.payment_evidence
+                .lock()
+                .await
+                .insert(payment.id.to_string(), evidence);
+        }
+
+        if self.get_manual_payment_policy(school_id).await?.require_approval {
+            return Ok(payment);
+        }
+        self.settle_payment(&payment.reference, payment.amount_kobo, recorded_by)
+            .await
+    }
+
+    // a manual payment still waiting for someone other than its recorder
+    async fn get_reviewable_payment(&self, school_id: Uuid, payment_id: Uuid, reviewer: &str) -> Result<Payment, AppError> {
+        let payments = self.payments.lock().await;
+        let payment = payments
+            .get(&payment_id.to_string())
+            .filter(|p| p.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+        let manual = payment
+            .manual
+            .as_ref()
+            .ok_or_else(|| AppError::invalid("payment", "Only manual payments need approval"))?;
+
+        if payment.status != TransactionStatus::Pending {
+            return Err(AppError::Conflict("Payment has already been reviewed".to_string()));
+        }
+        if manual.recorded_by == reviewer {
+            return Err(AppError::Forbidden("A payment must be approved by someone other than who recorded it".to_string()));
+        }
+        Ok(payment.clone())
+    }
+
+    async fn mark_reviewed(&self, payment_id: Uuid, reviewer: &str, rejection_reason: Option<String>) {
+        if let Some(manual) = self
+            .payments
+            .lock()
+            .await
+            .get_mut(&payment_id.to_string())
+            .and_then(|p| p.manual.as_mut())
+        {
+            manual.reviewed_by = Some(reviewer.to_string());
+            manual.reviewed_at = Some(Utc::now());
+            manual.rejection_reason = rejection_reason;
+        }
+    }
+
+    pub async fn approve_manual_payment(&self, school_id: Uuid, payment_id: Uuid, reviewer: &str) -> Result<Payment, AppError> {
+        let payment = self.get_reviewable_payment(school_id, payment_id, reviewer).await?;
+        self.mark_reviewed(payment_id, reviewer, None).await;
+        self.settle_payment(&payment.reference, payment.amount_kobo, reviewer)
+            .await
+    }
+
+    pub async fn reject_manual_payment(
+        &self,
+        school_id: Uuid,
+        payment_id: Uuid,
+        reviewer: &str,
+        req: RejectPaymentRequest,
+    ) -> Result<Payment, AppError> {
+        if req.reason.trim().is_empty() {
+            return Err(AppError::invalid("reason", "A reason is required"));
+        }
+        let payment = self.get_reviewable_payment(school_id, payment_id, reviewer).await?;
+        self.mark_reviewed(payment_id, reviewer, Some(req.reason)).await;
+        self.fail_payment(&payment.reference).await
+    }
+
+    pub async fn get_payments_awaiting_approval(&self, school_id: Uuid) -> Result<Vec<Payment>, AppError> {
+        let payments = self.payments.lock().await;
+        let mut list: Vec<Payment> = payments
+            .values()
+            .filter(|p| p.school_id == school_id && p.manual.is_some())
+            .filter(|p| p.status == TransactionStatus::Pending)
+            .cloned()
+            .collect();
+        list.sort_by_key(|p| p.created_at);
+        Ok(list)
+    }
+
+    pub async fn get_payment_evidence(&self, school_id: Uuid, payment_id: Uuid) -> Result<PaymentEvidence, AppError> {
+        let owned = self
+            .payments
+            .lock()
+            .await
+            .get(&payment_id.to_string())
+            .is_some_and(|p| p.school_id == school_id);
+        if !owned {
+            return Err(AppError::NotFound);
+        }
+        self.payment_evidence
+            .lock()
+            .await
+            .get(&payment_id.to_string())
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+}
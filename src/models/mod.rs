@@ -1,10 +1,68 @@
-use std::{collections::HashMap, sync::Arc};
-
+pub mod academics;
+pub mod admission;
+pub mod attendance;
+pub mod bank_statements;
+pub mod custom_fields;
+pub mod dashboard;
+pub mod discounts;
+pub mod fees;
+pub mod guardians;
+pub mod installments;
+pub mod late_fees;
+pub mod ledger;
+pub mod manual_payments;
+pub mod money;
+pub mod payment_links;
+pub mod payment_settings;
+pub mod receipts;
+pub mod refunds;
+pub mod reminders;
+pub mod report_cards;
+pub mod reports;
+pub mod staff;
+pub mod subaccounts;
+#[cfg(test)]
+pub mod testing;
+pub mod timetable;
+pub mod virtual_accounts;
+pub mod webhooks;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use chrono::{Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::errors::AppError;
+use academics::{GradeBand, ScoreEntry, Subject, Term};
+use admission::AdmissionNumberFormat;
+use attendance::AttendanceRecord;
+use bank_statements::{BankStatement, StatementLine};
+use custom_fields::{CustomFieldDefinition, display_value};
+use dashboard::Dashboard;
+use discounts::Discount;
+use fees::FeeItem;
+use guardians::Guardian;
+use installments::InstallmentPlan;
+use late_fees::{LateFee, LateFeePolicy};
+use ledger::{Invoice, LedgerEntry, Payment};
+use manual_payments::{ManualPaymentPolicy, PaymentEvidence};
+use money::Currency;
+use payment_links::PaymentLink;
+use payment_settings::PaymentSettings;
+use receipts::Receipt;
+use refunds::Refund;
+use reminders::{ReminderDelivery, ReminderPreferences, ReminderSchedule};
+use report_cards::ReportCardRemarks;
+use staff::StaffUser;
+use subaccounts::Subaccount;
+use timetable::{Teacher, TimetableSlot};
+use virtual_accounts::VirtualAccount;
+use webhooks::WebhookEvent;
 
 // ---- School ----
 
@@ -15,6 +73,17 @@ pub struct School {
     pub username: String,
     #[serde(skip_serializing)] // never expose password hash in responses
     pub password_hash: String,
+    pub branding: SchoolBranding,
+    pub currency: Currency, // what the school bills and collects in
+}
+
+/// Contact details printed under the school's name on receipts.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct SchoolBranding {
+    pub motto: Option<String>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -22,6 +91,8 @@ pub struct RegisterSchoolRequest {
     pub name: String,
     pub username: String,
     pub password: String,
+    #[serde(default)] // NGN
+    pub currency: Currency,
 }
 
 #[derive(Deserialize)]
@@ -32,10 +103,34 @@ pub struct LoginSchoolRequest {
 
 // ---- Student ----
 
-#[derive(Clone, Deserialize, Serialize, PartialEq)]
+// Derived from the student's invoices, see `AppStore::refresh_student_status`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum PaymentStatus {
     Paid,
+    PartiallyPaid,
     Pending,
+    Overdue,
+}
+
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
+pub enum Gender {
+    Male,
+    Female,
+}
+
+/// Optional personal details shared by every school; anything school
+/// specific goes in `custom_fields` instead.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct StudentProfile {
+    pub date_of_birth: Option<NaiveDate>,
+    pub gender: Option<Gender>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub medical_notes: Option<String>,
+    pub previous_school: Option<String>,
+    pub guardian_name: Option<String>,
+    pub guardian_phone: Option<String>,
+    pub guardian_email: Option<String>,
 }
 
 #[derive(Clone, Serialize)]
@@ -43,12 +138,18 @@ pub struct Student {
     pub id: Uuid,
     pub school_id: Uuid, // ties student to a school
     pub school_name: String,
+    pub admission_number: String, // human readable, unique per school
     pub first_name: String,
     pub last_name: String,
     pub email: String,
     pub status: PaymentStatus,
     pub department: String,
-    pub payment_reference: Option<String>,
+    pub class_name: Option<String>, // e.g. "JSS1A"
+    #[serde(flatten)]
+    pub profile: StudentProfile,
+    pub custom_fields: BTreeMap<String, serde_json::Value>, // validated against the school's schema
+    pub optional_fee_items: Vec<Uuid>, // optional fee items this student has opted into
+    pub discount_ids: Vec<Uuid>, // discounts assigned to this student by staff
 }
 
 #[derive(Deserialize)]
@@ -57,6 +158,34 @@ pub struct CreateStudentRequest {
     pub last_name: String,
     pub email: String,
     pub department: String,
+    #[serde(default)]
+    pub class_name: Option<String>,
+    #[serde(flatten)]
+    pub profile: StudentProfile,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, serde_json::Value>,
+}
+
+// Updates replace the whole editable profile, so they take the same shape
+pub type UpdateStudentRequest = CreateStudentRequest;
+
+#[derive(Deserialize)]
+pub struct StudentQuery {
+    pub search: Option<String>, // matches admission number, name or email
+    pub class_name: Option<String>,
+    pub department: Option<String>,
+    pub gender: Option<String>,
+    #[serde(flatten)]
+    pub filters: HashMap<String, String>, // `custom.<key>=value` filters on custom fields
+}
+
+fn validate_profile(profile: &StudentProfile) -> Result<(), AppError> {
+    if let Some(date_of_birth) = profile.date_of_birth
+        && date_of_birth > Utc::now().date_naive()
+    {
+        return Err(AppError::invalid("date_of_birth", "Date of birth cannot be in the future"));
+    }
+    Ok(())
 }
 
 // ---- AppStore ----
@@ -64,14 +193,90 @@ pub struct CreateStudentRequest {
 #[derive(Clone)]
 pub struct AppStore {
     pub schools: Arc<Mutex<HashMap<String, School>>>,
+    pub staff: Arc<Mutex<HashMap<String, StaffUser>>>,
     pub students: Arc<Mutex<HashMap<String, Student>>>,
+    pub terms: Arc<Mutex<HashMap<String, Term>>>,
+    pub subjects: Arc<Mutex<HashMap<String, Subject>>>,
+    pub grading_scales: Arc<Mutex<HashMap<String, Vec<GradeBand>>>>, // keyed by school id
+    pub scores: Arc<Mutex<HashMap<String, ScoreEntry>>>,
+    pub report_card_remarks: Arc<Mutex<HashMap<String, ReportCardRemarks>>>, // keyed by "student_id:term"
+    pub attendance: Arc<Mutex<HashMap<String, AttendanceRecord>>>,
+    pub teachers: Arc<Mutex<HashMap<String, Teacher>>>,
+    pub timetable_slots: Arc<Mutex<HashMap<String, TimetableSlot>>>,
+    pub admission_formats: Arc<Mutex<HashMap<String, AdmissionNumberFormat>>>, // keyed by school id
+    pub admission_sequences: Arc<Mutex<HashMap<String, u32>>>, // last issued sequence per school (and year)
+    pub custom_fields: Arc<Mutex<HashMap<String, CustomFieldDefinition>>>,
+    pub fee_items: Arc<Mutex<HashMap<String, FeeItem>>>,
+    pub discounts: Arc<Mutex<HashMap<String, Discount>>>,
+    pub guardians: Arc<Mutex<HashMap<String, Guardian>>>,
+    pub invoices: Arc<Mutex<HashMap<String, Invoice>>>,
+    pub payments: Arc<Mutex<HashMap<String, Payment>>>,
+    pub ledger: Arc<Mutex<Vec<LedgerEntry>>>, // append-only, in posting order
+    pub installment_plans: Arc<Mutex<HashMap<String, InstallmentPlan>>>,
+    pub late_fee_policies: Arc<Mutex<HashMap<String, LateFeePolicy>>>, // keyed by school id
+    pub late_fees: Arc<Mutex<HashMap<String, LateFee>>>,
+    pub refunds: Arc<Mutex<HashMap<String, Refund>>>,
+    pub reminder_schedules: Arc<Mutex<HashMap<String, ReminderSchedule>>>, // keyed by school id
+    pub reminder_preferences: Arc<Mutex<HashMap<String, ReminderPreferences>>>, // keyed by student id
+    pub reminder_log: Arc<Mutex<HashMap<String, ReminderDelivery>>>,
+    pub receipts: Arc<Mutex<HashMap<String, Receipt>>>, // keyed by verification code
+    pub receipt_sequences: Arc<Mutex<HashMap<String, u32>>>, // last issued receipt number per school
+    pub webhook_events: Arc<Mutex<HashMap<String, WebhookEvent>>>,
+    pub payment_settings: Arc<Mutex<HashMap<String, PaymentSettings>>>, // keyed by school id
+    pub subaccounts: Arc<Mutex<HashMap<String, Subaccount>>>, // keyed by school id
+    pub virtual_accounts: Arc<Mutex<HashMap<String, VirtualAccount>>>,
+    pub payment_links: Arc<Mutex<HashMap<String, PaymentLink>>>,
+    pub manual_payment_policies: Arc<Mutex<HashMap<String, ManualPaymentPolicy>>>, // keyed by school id
+    pub payment_evidence: Arc<Mutex<HashMap<String, PaymentEvidence>>>, // keyed by payment id
+    pub bank_statements: Arc<Mutex<HashMap<String, BankStatement>>>,
+    pub statement_lines: Arc<Mutex<HashMap<String, StatementLine>>>,
+    pub dashboards: Arc<Mutex<HashMap<String, Dashboard>>>, // cached per school id
+    pub dashboard_generations: Arc<Mutex<HashMap<String, u64>>>, // bumped on each invalidation, per school id
 }
 
 impl AppStore {
     pub fn new() -> Self {
         Self {
             schools: Arc::new(Mutex::new(HashMap::new())),
+            staff: Arc::new(Mutex::new(HashMap::new())),
             students: Arc::new(Mutex::new(HashMap::new())),
+            terms: Arc::new(Mutex::new(HashMap::new())),
+            subjects: Arc::new(Mutex::new(HashMap::new())),
+            grading_scales: Arc::new(Mutex::new(HashMap::new())),
+            scores: Arc::new(Mutex::new(HashMap::new())),
+            report_card_remarks: Arc::new(Mutex::new(HashMap::new())),
+            attendance: Arc::new(Mutex::new(HashMap::new())),
+            teachers: Arc::new(Mutex::new(HashMap::new())),
+            timetable_slots: Arc::new(Mutex::new(HashMap::new())),
+            admission_formats: Arc::new(Mutex::new(HashMap::new())),
+            admission_sequences: Arc::new(Mutex::new(HashMap::new())),
+            custom_fields: Arc::new(Mutex::new(HashMap::new())),
+            fee_items: Arc::new(Mutex::new(HashMap::new())),
+            discounts: Arc::new(Mutex::new(HashMap::new())),
+            guardians: Arc::new(Mutex::new(HashMap::new())),
+            invoices: Arc::new(Mutex::new(HashMap::new())),
+            payments: Arc::new(Mutex::new(HashMap::new())),
+            ledger: Arc::new(Mutex::new(Vec::new())),
+            installment_plans: Arc::new(Mutex::new(HashMap::new())),
+            late_fee_policies: Arc::new(Mutex::new(HashMap::new())),
+            late_fees: Arc::new(Mutex::new(HashMap::new())),
+            refunds: Arc::new(Mutex::new(HashMap::new())),
+            reminder_schedules: Arc::new(Mutex::new(HashMap::new())),
+            reminder_preferences: Arc::new(Mutex::new(HashMap::new())),
+            reminder_log: Arc::new(Mutex::new(HashMap::new())),
+            receipts: Arc::new(Mutex::new(HashMap::new())),
+            receipt_sequences: Arc::new(Mutex::new(HashMap::new())),
+            webhook_events: Arc::new(Mutex::new(HashMap::new())),
+            payment_settings: Arc::new(Mutex::new(HashMap::new())),
+            subaccounts: Arc::new(Mutex::new(HashMap::new())),
+            virtual_accounts: Arc::new(Mutex::new(HashMap::new())),
+            payment_links: Arc::new(Mutex::new(HashMap::new())),
+            manual_payment_policies: Arc::new(Mutex::new(HashMap::new())),
+            payment_evidence: Arc::new(Mutex::new(HashMap::new())),
+            bank_statements: Arc::new(Mutex::new(HashMap::new())),
+            statement_lines: Arc::new(Mutex::new(HashMap::new())),
+            dashboards: Arc::new(Mutex::new(HashMap::new())),
+            dashboard_generations: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -80,8 +285,9 @@ impl AppStore {
     pub async fn register_school(&self, req: RegisterSchoolRequest) -> Result<School, AppError> {
         let mut schools = self.schools.lock().await;
 
-        // check username is not already taken
-        let taken = schools.values().any(|s| s.username == req.username);
+        // check username is not already taken, by a school or a staff login
+        let taken = schools.values().any(|s| s.username == req.username)
+            || self.staff.lock().await.values().any(|u| u.username == req.username);
         if taken {
             return Err(AppError::Conflict("Username already taken".to_string()));
         }
@@ -94,6 +300,8 @@ impl AppStore {
             name: req.name,
             username: req.username,
             password_hash,
+            branding: SchoolBranding::default(),
+            currency: req.currency,
         };
 
         schools.insert(school.id.to_string(), school.clone());
@@ -109,6 +317,22 @@ impl AppStore {
             .ok_or(AppError::NotFound)
     }
 
+    pub async fn get_school_ids(&self) -> Vec<Uuid> {
+        self.schools.lock().await.values().map(|s| s.id).collect()
+    }
+
+    pub async fn get_school(&self, id: Uuid) -> Result<School, AppError> {
+        let schools = self.schools.lock().await;
+        schools.get(&id.to_string()).cloned().ok_or(AppError::NotFound)
+    }
+
+    pub async fn set_school_branding(&self, id: Uuid, branding: SchoolBranding) -> Result<School, AppError> {
+        let mut schools = self.schools.lock().await;
+        let school = schools.get_mut(&id.to_string()).ok_or(AppError::NotFound)?;
+        school.branding = branding;
+        Ok(school.clone())
+    }
+
     // -- Student methods --
 
     pub async fn create_student(
@@ -116,25 +340,113 @@ impl AppStore {
         school_id: Uuid,
         school_name: String,
         req: CreateStudentRequest,
-    ) -> Result<(), AppError> {
+    ) -> Result<Student, AppError> {
+        validate_profile(&req.profile)?;
+        self.validate_custom_fields(school_id, &req.custom_fields).await?;
+        let format = self.get_admission_format(school_id).await?;
+
+        // hold the students lock while numbering so two concurrent creates
+        // can never be handed the same admission number
+        let mut students = self.students.lock().await;
+        let mut sequences = self.admission_sequences.lock().await;
+
+        let year = Utc::now().year();
+        let sequence = sequences
+            .entry(format.sequence_key(school_id, year))
+            .or_insert(0);
+        let admission_number = loop {
+            *sequence += 1;
+            let candidate = format.render(year, *sequence);
+            let taken = students.values().any(|s| {
+                s.school_id == school_id && s.admission_number.eq_ignore_ascii_case(&candidate)
+            });
+            if !taken {
+                break candidate;
+            }
+        };
+
         let new_student = Student {
             id: Uuid::new_v4(),
             school_name,
+            admission_number,
             school_id,
             first_name: req.first_name,
             last_name: req.last_name,
             email: req.email,
             department: req.department,
+            class_name: req.class_name,
+            profile: req.profile,
+            custom_fields: req.custom_fields,
+            optional_fee_items: Vec::new(),
+            discount_ids: Vec::new(),
             status: PaymentStatus::Pending,
-            payment_reference: None,
         };
 
-        self.students
-            .lock()
-            .await
-            .insert(new_student.id.to_string(), new_student);
+        students.insert(new_student.id.to_string(), new_student.clone());
 
-        Ok(())
+        Ok(new_student)
+    }
+
+    pub async fn update_student(
+        &self,
+        school_id: Uuid,
+        id: Uuid,
+        req: UpdateStudentRequest,
+    ) -> Result<Student, AppError> {
+        validate_profile(&req.profile)?;
+        self.validate_custom_fields(school_id, &req.custom_fields).await?;
+
+        let mut students = self.students.lock().await;
+        let student = students
+            .values_mut()
+            .find(|s| s.id == id && s.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+
+        student.first_name = req.first_name;
+        student.last_name = req.last_name;
+        student.email = req.email;
+        student.department = req.department;
+        student.class_name = req.class_name;
+        student.profile = req.profile;
+        student.custom_fields = req.custom_fields;
+        Ok(student.clone())
+    }
+
+    pub async fn search_students(&self, school_id: Uuid, query: StudentQuery) -> Result<Vec<Student>, AppError> {
+        let mut list = self.get_all_students(school_id).await?;
+        if let Some(search) = query.search.map(|s| s.to_lowercase()) {
+            list.retain(|s| {
+                s.admission_number.to_lowercase().contains(&search)
+                    || format!("{} {}", s.first_name, s.last_name)
+                        .to_lowercase()
+                        .contains(&search)
+                    || s.email.to_lowercase().contains(&search)
+            });
+        }
+        if let Some(class_name) = &query.class_name {
+            list.retain(|s| s.class_name.as_ref() == Some(class_name));
+        }
+        if let Some(department) = &query.department {
+            list.retain(|s| s.department.eq_ignore_ascii_case(department));
+        }
+        if let Some(gender) = &query.gender {
+            list.retain(|s| match &s.profile.gender {
+                Some(Gender::Male) => gender.eq_ignore_ascii_case("male"),
+                Some(Gender::Female) => gender.eq_ignore_ascii_case("female"),
+                None => false,
+            });
+        }
+        for (param, expected) in &query.filters {
+            if let Some(key) = param.strip_prefix("custom.") {
+                list.retain(|s| {
+                    s.custom_fields
+                        .get(key)
+                        .is_some_and(|v| display_value(v).eq_ignore_ascii_case(expected))
+                });
+            }
+        }
+        list.sort_by(|a, b| a.admission_number.cmp(&b.admission_number));
+        Ok(list)
     }
 
     pub async fn get_all_students(&self, school_id: Uuid) -> Result<Vec<Student>, AppError> {
@@ -146,6 +458,21 @@ impl AppStore {
             .collect())
     }
 
+    pub async fn get_class_students(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+    ) -> Result<Vec<Student>, AppError> {
+        let students = self.students.lock().await;
+        let mut list: Vec<Student> = students
+            .values()
+            .filter(|s| s.school_id == school_id && s.class_name.as_deref() == Some(class_name))
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| (&a.last_name, &a.first_name).cmp(&(&b.last_name, &b.first_name)));
+        Ok(list)
+    }
+
     pub async fn get_student(&self, school_id: Uuid, id: Uuid) -> Result<Student, AppError> {
         let students = self.students.lock().await;
         students
@@ -169,37 +496,4 @@ impl AppStore {
             Err(AppError::NotFound)
         }
     }
-
-    pub async fn set_payment_reference(
-        &self,
-        school_id: Uuid,
-        id: Uuid,
-        reference: String,
-    ) -> Result<(), AppError> {
-        let mut students = self.students.lock().await;
-        let student = students
-            .values_mut()
-            .find(|s| s.id == id && s.school_id == school_id);
-
-        if let Some(student) = student {
-            student.payment_reference = Some(reference);
-            Ok(())
-        } else {
-            Err(AppError::NotFound)
-        }
-    }
-
-    pub async fn mark_student_paid_by_reference(&self, reference: &str) -> Result<(), AppError> {
-        let mut students = self.students.lock().await;
-        let student = students
-            .values_mut()
-            .find(|s| s.payment_reference.as_deref() == Some(reference));
-
-        if let Some(student) = student {
-            student.status = PaymentStatus::Paid;
-            Ok(())
-        } else {
-            Err(AppError::NotFound)
-        }
-    }
-}
\ No newline at end of file
+}
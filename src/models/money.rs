@@ -0,0 +1,135 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, School};
+
+/// The currencies our gateways can collect in. Every one of them has 100
+/// minor units to the major unit, which the `_kobo` amount fields rely on.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    #[default]
+    Ngn,
+    Ghs,
+    Kes,
+    Zar,
+    Usd,
+}
+
+impl Currency {
+    /// The ISO 4217 code, e.g. "NGN".
+    pub fn code(self) -> &'static str {
+        match self {
+            Currency::Ngn => "NGN",
+            Currency::Ghs => "GHS",
+            Currency::Kes => "KES",
+            Currency::Zar => "ZAR",
+            Currency::Usd => "USD",
+        }
+    }
+
+    /// The major and minor unit, as written out on receipts.
+    pub fn unit_names(self) -> (&'static str, &'static str) {
+        match self {
+            Currency::Ngn => ("naira", "kobo"),
+            Currency::Ghs => ("cedis", "pesewas"),
+            Currency::Kes => ("shillings", "cents"),
+            Currency::Zar => ("rand", "cents"),
+            Currency::Usd => ("dollars", "cents"),
+        }
+    }
+
+    /// Gateways report currencies as codes, in either case.
+    pub fn matches(self, code: &str) -> bool {
+        self.code().eq_ignore_ascii_case(code)
+    }
+
+    /// Rejects an amount a gateway reported in another currency.
+    pub fn expect(self, code: Option<&str>) -> Result<(), AppError> {
+        let code = code.unwrap_or_default();
+        if !self.matches(code) {
+            return Err(AppError::invalid("currency", &format!("Expected {}, got {}", self.code(), code)));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// An amount in a currency's minor unit (kobo, pesewas, cents). Negative
+/// amounts are credits.
+///
+/// Model and API fields named `*_kobo` predate other currencies and stay
+/// bare integers for compatibility: they hold minor units of the currency
+/// given alongside them, which is always the school's.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Money {
+    pub amount_minor: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount_minor: i64, currency: Currency) -> Self {
+        Money { amount_minor, currency }
+    }
+
+    pub fn from_minor(amount_minor: u64, currency: Currency) -> Self {
+        Money::new(amount_minor as i64, currency)
+    }
+
+    /// The amount to send a gateway, which never takes credits.
+    pub fn minor_units(&self) -> u64 {
+        self.amount_minor.max(0) as u64
+    }
+}
+
+/// e.g. 5_000_050 in NGN -> "NGN 50,000.50".
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.amount_minor < 0 { "-" } else { "" };
+        let amount = self.amount_minor.unsigned_abs();
+        let major = (amount / 100).to_string();
+        let mut grouped = String::new();
+        for (index, digit) in major.chars().enumerate() {
+            if index > 0 && (major.len() - index).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        write!(f, "{}{} {}.{:02}", sign, self.currency, grouped, amount % 100)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CurrencySettings {
+    pub currency: Currency,
+}
+
+impl AppStore {
+    /// Changes the currency new invoices are billed in. Refused once the
+    /// school has billed anyone, since balances can't be mixed.
+    pub async fn set_school_currency(&self, id: Uuid, currency: Currency) -> Result<School, AppError> {
+        // held until the change is made, so no invoice can be created in between
+        let invoices = self.invoices.lock().await;
+        let billed = invoices.values().any(|i| i.school_id == id);
+
+        let mut schools = self.schools.lock().await;
+        let school = schools.get_mut(&id.to_string()).ok_or(AppError::NotFound)?;
+        if billed && school.currency != currency {
+            return Err(AppError::Conflict(format!(
+                "The school already bills in {}; its currency can no longer change",
+                school.currency
+            )));
+        }
+        school.currency = currency;
+        Ok(school.clone())
+    }
+}
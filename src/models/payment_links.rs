@@ -0,0 +1,337 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{config::get_env_vars, errors::AppError};
+
+use super::{
+    AppStore,
+    ledger::{Invoice, TransactionStatus, amount_due_next},
+    money::Money,
+};
+
+/// How long a link stays open when staff don't say (PAYMENT_LINK_HOURS overrides).
+const DEFAULT_LINK_HOURS: i64 = 72;
+const MAX_LINK_HOURS: i64 = 24 * 30;
+/// How long a payer pressing Pay again is sent back to the checkout they already started.
+const CHECKOUT_REUSE_MINUTES: i64 = 15;
+
+/// A shareable link a parent can pay an invoice through without logging in.
+/// The token in `url` carries the link id and expiry, signed with
+/// PAYMENT_LINK_SECRET, so a tampered or guessed link never reaches the store.
+#[derive(Clone, Serialize)]
+pub struct PaymentLink {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub invoice_id: Uuid,
+    pub amount_kobo: Option<u64>, // fixed by staff; otherwise whatever is due next
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub payment_references: Vec<String>, // checkouts started through the link, oldest first
+    #[serde(skip)]
+    pub latest_checkout: Option<LinkCheckout>,
+}
+
+/// The gateway page of the last checkout started through a link.
+#[derive(Clone)]
+pub struct LinkCheckout {
+    pub reference: String,
+    pub authorization_url: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct CreatePaymentLinkRequest {
+    pub invoice_id: Option<Uuid>,
+    pub term: Option<String>, // used when no invoice is given; defaults to the current term
+    pub amount_kobo: Option<u64>,
+    pub expires_in_hours: Option<i64>,
+}
+
+/// What the public payment page shows.
+pub struct PaymentPage {
+    pub school_name: String,
+    pub student_name: String,
+    pub admission_number: String,
+    pub term_code: String,
+    pub balance: Money,
+    pub amount_due: Money, // zero once nothing is owed
+    pub expires_at: DateTime<Utc>,
+}
+
+// kept apart from JWT_SECRET, so a leaked link key can't sign staff tokens or the reverse
+fn link_secret() -> Result<String, AppError> {
+    get_env_vars("PAYMENT_LINK_SECRET".to_string())
+}
+
+fn signature(payload: &str, secret: &str) -> Result<Hmac<Sha256>, AppError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    mac.update(payload.as_bytes());
+    Ok(mac)
+}
+
+// "<link id>.<expiry, unix seconds>.<hex hmac-sha256 of the two>"
+fn sign_token(id: Uuid, expires_at: DateTime<Utc>, secret: &str) -> Result<String, AppError> {
+    let payload = format!("{}.{}", id, expires_at.timestamp());
+    let mac = signature(&payload, secret)?;
+    Ok(format!("{}.{}", payload, hex::encode(mac.finalize().into_bytes())))
+}
+
+fn verify_token(token: &str, secret: &str) -> Result<(Uuid, DateTime<Utc>), AppError> {
+    let (payload, sig) = token.rsplit_once('.').ok_or(AppError::NotFound)?;
+    let sig = hex::decode(sig).map_err(|_| AppError::NotFound)?;
+    signature(payload, secret)?
+        .verify_slice(&sig)
+        .map_err(|_| AppError::NotFound)?;
+
+    let (id, expires) = payload.split_once('.').ok_or(AppError::NotFound)?;
+    let id = id.parse::<Uuid>().map_err(|_| AppError::NotFound)?;
+    let expires_at = expires
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        .ok_or(AppError::NotFound)?;
+    Ok((id, expires_at))
+}
+
+pub fn public_url(path: &str) -> String {
+    let base: String =
+        get_env_vars("PUBLIC_BASE_URL".to_string()).unwrap_or_else(|_| "http://localhost:8080".to_string());
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+/// The amount a checkout through the link asks for, capped at the balance.
+pub fn link_amount(link: &PaymentLink, invoice: &Invoice) -> u64 {
+    let balance_kobo = invoice.balance_kobo.max(0) as u64;
+    link.amount_kobo.unwrap_or_else(|| amount_due_next(invoice)).min(balance_kobo)
+}
+
+impl AppStore {
+    pub async fn create_payment_link(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        created_by: &str,
+        req: CreatePaymentLinkRequest,
+    ) -> Result<PaymentLink, AppError> {
+        let invoice = match req.invoice_id {
+            Some(invoice_id) => {
+                let invoice = self.get_invoice(school_id, invoice_id).await?;
+                if invoice.student_id != student_id {
+                    return Err(AppError::invalid("invoice_id", "The invoice belongs to another student"));
+                }
+                invoice
+            }
+            None => {
+                let term = match &req.term {
+                    Some(code) => self.get_term(school_id, code).await?,
+                    None => self.get_current_term(school_id).await?,
+                };
+                self.find_or_create_invoice(school_id, student_id, &term.code, created_by).await?
+            }
+        };
+
+        if invoice.balance_kobo <= 0 {
+            return Err(AppError::invalid("invoice_id", "Nothing is owed on this invoice"));
+        }
+        if let Some(amount) = req.amount_kobo
+            && (amount == 0 || amount > invoice.balance_kobo as u64)
+        {
+            return Err(AppError::invalid("amount_kobo", "Amount must be between 1 and the outstanding balance"));
+        }
+
+        let default_hours: i64 = get_env_vars("PAYMENT_LINK_HOURS".to_string()).unwrap_or(DEFAULT_LINK_HOURS);
+        let hours = req.expires_in_hours.unwrap_or(default_hours);
+        if !(1..=MAX_LINK_HOURS).contains(&hours) {
+            return Err(AppError::invalid(
+                "expires_in_hours",
+                &format!("Links can stay open for 1 to {} hours", MAX_LINK_HOURS),
+            ));
+        }
+
+        let id = Uuid::new_v4();
+        let created_at = Utc::now();
+        // whole seconds, as the token carries them
+        let expires_at = Utc
+            .timestamp_opt((created_at + Duration::hours(hours)).timestamp(), 0)
+            .single()
+            .ok_or_else(|| AppError::InternalServerError("expiry out of range".to_string()))?;
+        let link = PaymentLink {
+            id,
+            school_id,
+            student_id,
+            invoice_id: invoice.id,
+            amount_kobo: req.amount_kobo,
+            url: public_url(&format!("/pay/{}", sign_token(id, expires_at, &link_secret()?)?)),
+            expires_at,
+            created_by: created_by.to_string(),
+            created_at,
+            revoked_at: None,
+            payment_references: Vec::new(),
+            latest_checkout: None,
+        };
+
+        self.payment_links.lock().await.insert(id.to_string(), link.clone());
+        Ok(link)
+    }
+
+    pub async fn get_student_payment_links(&self, school_id: Uuid, student_id: Uuid) -> Vec<PaymentLink> {
+        let links = self.payment_links.lock().await;
+        let mut list: Vec<PaymentLink> = links
+            .values()
+            .filter(|l| l.school_id == school_id && l.student_id == student_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|l| l.created_at);
+        list
+    }
+
+    pub async fn revoke_payment_link(&self, school_id: Uuid, id: Uuid) -> Result<PaymentLink, AppError> {
+        let mut links = self.payment_links.lock().await;
+        let link = links
+            .get_mut(&id.to_string())
+            .filter(|l| l.school_id == school_id)
+            .ok_or(AppError::NotFound)?;
+        if link.revoked_at.is_none() {
+            link.revoked_at = Some(Utc::now());
+        }
+        Ok(link.clone())
+    }
+
+    /// The link a public token stands for, open or not, if the token is genuine.
+    pub async fn find_payment_link(&self, token: &str) -> Result<PaymentLink, AppError> {
+        let (id, expires_at) = verify_token(token, &link_secret()?)?;
+        self.payment_links
+            .lock()
+            .await
+            .get(&id.to_string())
+            .filter(|l| l.expires_at == expires_at)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Like `find_payment_link`, but only while the link can still take payments.
+    pub async fn open_payment_link(&self, token: &str) -> Result<PaymentLink, AppError> {
+        let link = self.find_payment_link(token).await?;
+
+        if link.revoked_at.is_some() {
+            return Err(AppError::Forbidden("This payment link has been withdrawn by the school".to_string()));
+        }
+        if Utc::now() >= link.expires_at {
+            return Err(AppError::Forbidden("This payment link has expired".to_string()));
+        }
+        Ok(link)
+    }
+
+    pub async fn add_payment_link_checkout(
+        &self,
+        id: Uuid,
+        reference: &str,
+        authorization_url: &str,
+    ) -> Result<(), AppError> {
+        let mut links = self.payment_links.lock().await;
+        let link = links.get_mut(&id.to_string()).ok_or(AppError::NotFound)?;
+        link.payment_references.push(reference.to_string());
+        link.latest_checkout = Some(LinkCheckout {
+            reference: reference.to_string(),
+            authorization_url: authorization_url.to_string(),
+            started_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// The link's last checkout, if it was started in the past few minutes for
+    /// `amount_kobo` and is still unpaid, so pressing Pay again doesn't open
+    /// another one.
+    pub async fn reusable_link_checkout(&self, link: &PaymentLink, amount_kobo: u64) -> Option<LinkCheckout> {
+        let checkout = link
+            .latest_checkout
+            .clone()
+            .filter(|c| Utc::now() - c.started_at < Duration::minutes(CHECKOUT_REUSE_MINUTES))?;
+        let payment = self.find_payment_by_reference(link.school_id, &checkout.reference).await.ok()?;
+        (payment.status == TransactionStatus::Pending && payment.amount_kobo == amount_kobo).then_some(checkout)
+    }
+
+    pub async fn get_payment_page(&self, link: &PaymentLink) -> Result<PaymentPage, AppError> {
+        let school = self.get_school(link.school_id).await?;
+        let student = self.get_student(link.school_id, link.student_id).await?;
+        let invoice = self.get_invoice(link.school_id, link.invoice_id).await?;
+        let currency = invoice.currency;
+
+        Ok(PaymentPage {
+            school_name: school.name,
+            student_name: format!("{} {}", student.first_name, student.last_name),
+            admission_number: student.admission_number,
+            term_code: invoice.term_code.clone(),
+            balance: Money::new(invoice.balance_kobo, currency),
+            amount_due: Money::from_minor(link_amount(link, &invoice), currency),
+            expires_at: link.expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "link-secret";
+
+    fn token() -> (Uuid, DateTime<Utc>, String) {
+        let id = Uuid::new_v4();
+        let expires_at = Utc.timestamp_opt(1_790_000_000, 0).unwrap();
+        (id, expires_at, sign_token(id, expires_at, SECRET).unwrap())
+    }
+
+    fn rejected(token: &str, secret: &str) -> bool {
+        matches!(verify_token(token, secret), Err(AppError::NotFound))
+    }
+
+    #[test]
+    fn a_genuine_token_round_trips() {
+        let (id, expires_at, token) = token();
+        let (got_id, got_expires_at) = verify_token(&token, SECRET).unwrap();
+        assert_eq!(got_id, id);
+        assert_eq!(got_expires_at, expires_at);
+    }
+
+    #[test]
+    fn a_tampered_id_is_rejected() {
+        let (id, _, token) = token();
+        let forged = token.replacen(&id.to_string(), &Uuid::new_v4().to_string(), 1);
+        assert!(rejected(&forged, SECRET));
+    }
+
+    #[test]
+    fn a_tampered_expiry_is_rejected() {
+        let (_, expires_at, token) = token();
+        let later = (expires_at + Duration::days(365)).timestamp().to_string();
+        let forged = token.replacen(&expires_at.timestamp().to_string(), &later, 1);
+        assert!(rejected(&forged, SECRET));
+    }
+
+    #[test]
+    fn a_tampered_signature_is_rejected() {
+        let (_, _, token) = token();
+        let (payload, sig) = token.rsplit_once('.').unwrap();
+        let flipped = if sig.starts_with('0') { "1" } else { "0" };
+        assert!(rejected(&format!("{}.{}{}", payload, flipped, &sig[1..]), SECRET));
+        assert!(rejected(&format!("{}.{}", payload, &sig[2..]), SECRET));
+        assert!(rejected(&format!("{}.not-hex", payload), SECRET));
+        assert!(rejected(payload, SECRET));
+    }
+
+    #[test]
+    fn a_token_signed_with_another_secret_is_rejected() {
+        let id = Uuid::new_v4();
+        let expires_at = Utc::now() + Duration::hours(1);
+        let token = sign_token(id, expires_at, "another-secret").unwrap();
+        assert!(rejected(&token, SECRET));
+    }
+}
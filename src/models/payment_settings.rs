@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, ledger::Payment};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum PaymentProviderKind {
+    Paystack,
+    Flutterwave,
+    Stripe,
+}
+
+/// The gateway account an online payment went through: the platform's
+/// Paystack account, or the school's own account with `provider`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PaymentGateway {
+    pub provider: PaymentProviderKind,
+    pub platform: bool,
+}
+
+/// The gateway a school collects fees through, with its own account
+/// credentials. Schools without settings use the platform Paystack account.
+#[derive(Clone, Serialize)]
+pub struct PaymentSettings {
+    pub school_id: Uuid,
+    pub provider: PaymentProviderKind,
+    #[serde(skip_serializing)] // never expose credentials in responses
+    pub secret_key: String,
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>, // Flutterwave secret hash or Stripe signing secret
+    pub webhook_path: String, // where the school should point its provider's webhooks
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct PaymentSettingsRequest {
+    pub provider: PaymentProviderKind,
+    pub secret_key: String,
+    pub webhook_secret: Option<String>,
+}
+
+impl AppStore {
+    pub async fn set_payment_settings(
+        &self,
+        school_id: Uuid,
+        req: PaymentSettingsRequest,
+    ) -> Result<PaymentSettings, AppError> {
+        if req.secret_key.trim().is_empty() {
+            return Err(AppError::invalid("secret_key", "Secret key cannot be empty"));
+        }
+        // Paystack signs webhooks with the secret key; the others use a separate secret
+        let needs_webhook_secret = req.provider != PaymentProviderKind::Paystack;
+        if needs_webhook_secret && req.webhook_secret.as_deref().is_none_or(|s| s.trim().is_empty()) {
+            return Err(AppError::invalid("webhook_secret", "This provider needs a webhook secret"));
+        }
+
+        let settings = PaymentSettings {
+            school_id,
+            provider: req.provider,
+            secret_key: req.secret_key,
+            webhook_secret: req.webhook_secret,
+            webhook_path: format!("/webhook/schools/{}", school_id),
+            updated_at: Utc::now(),
+        };
+
+        self.payment_settings
+            .lock()
+            .await
+            .insert(school_id.to_string(), settings.clone());
+        Ok(settings)
+    }
+
+    pub async fn get_payment_settings(&self, school_id: Uuid) -> Result<PaymentSettings, AppError> {
+        self.payment_settings
+            .lock()
+            .await
+            .get(&school_id.to_string())
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    /// The gateway account a payment went through. Payments from before
+    /// gateways were recorded are taken to have used the school's current one.
+    pub async fn payment_gateway(&self, payment: &Payment) -> PaymentGateway {
+        if let Some(gateway) = payment.gateway {
+            return gateway;
+        }
+        match self.get_payment_settings(payment.school_id).await {
+            Ok(settings) => PaymentGateway { provider: settings.provider, platform: false },
+            Err(_) => PaymentGateway { provider: PaymentProviderKind::Paystack, platform: true },
+        }
+    }
+}
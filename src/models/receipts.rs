@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore, School, Student,
+    ledger::{Invoice, Payment, TransactionStatus},
+    money::Currency,
+};
+
+/// Proof of a successful payment. `id` doubles as the verification code in
+/// the public receipt-check link, so it is never reused or guessable.
+#[derive(Clone, Serialize)]
+pub struct Receipt {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub payment_id: Uuid,
+    pub number: String, // sequential per school, e.g. "RCT-000042"
+    pub currency: Currency, // the payment's; both amounts are in its minor unit
+    pub amount_kobo: u64,
+    pub balance_after_kobo: i64, // what was still owed on the invoice once this was paid
+    pub issued_at: DateTime<Utc>,
+}
+
+/// Everything printed on a receipt.
+pub struct ReceiptDocument {
+    pub receipt: Receipt,
+    pub school: School,
+    pub student: Student,
+    pub payment: Payment,
+    pub invoice: Invoice,
+}
+
+/// What anyone holding the receipt link may see.
+#[derive(Serialize)]
+pub struct ReceiptCheck {
+    pub receipt_number: String,
+    pub school_name: String,
+    pub student_name: String, // initial and surname only
+    pub amount_kobo: u64,
+    pub currency: Currency,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub payment_status: TransactionStatus, // shows Refunded if the money went back
+}
+
+impl AppStore {
+    /// Gives a settled payment its receipt. Calling it again returns the
+    /// receipt already issued instead of using up another number.
+    pub async fn issue_receipt(&self, payment_id: Uuid) -> Result<Receipt, AppError> {
+        let mut receipts = self.receipts.lock().await;
+        if let Some(existing) = receipts.values().find(|r| r.payment_id == payment_id) {
+            return Ok(existing.clone());
+        }
+
+        let payment = {
+            let payments = self.payments.lock().await;
+            payments
+                .get(&payment_id.to_string())
+                .cloned()
+                .ok_or(AppError::NotFound)?
+        };
+        let invoice = self.get_invoice(payment.school_id, payment.invoice_id).await?;
+
+        let sequence = {
+            let mut sequences = self.receipt_sequences.lock().await;
+            let next = sequences.entry(payment.school_id.to_string()).or_insert(0);
+            *next += 1;
+            *next
+        };
+
+        let receipt = Receipt {
+            id: Uuid::new_v4(),
+            school_id: payment.school_id,
+            payment_id,
+            number: format!("RCT-{:06}", sequence),
+            currency: payment.currency,
+            amount_kobo: payment.amount_kobo,
+            balance_after_kobo: invoice.balance_kobo,
+            issued_at: Utc::now(),
+        };
+        receipts.insert(receipt.id.to_string(), receipt.clone());
+
+        if let Some(stored) = self.payments.lock().await.get_mut(&payment_id.to_string()) {
+            stored.receipt_number = Some(receipt.number.clone());
+        }
+        Ok(receipt)
+    }
+
+    pub async fn get_receipt_document(&self, school_id: Uuid, payment_id: Uuid) -> Result<ReceiptDocument, AppError> {
+        let receipt = {
+            let receipts = self.receipts.lock().await;
+            receipts
+                .values()
+                .find(|r| r.payment_id == payment_id && r.school_id == school_id)
+                .cloned()
+                .ok_or(AppError::NotFound)?
+        };
+        let payment = {
+            let payments = self.payments.lock().await;
+            payments
+                .get(&payment_id.to_string())
+                .cloned()
+                .ok_or(AppError::NotFound)?
+        };
+
+        Ok(ReceiptDocument {
+            school: self.get_school(school_id).await?,
+            student: self.get_student(school_id, payment.student_id).await?,
+            invoice: self.get_invoice(school_id, payment.invoice_id).await?,
+            receipt,
+            payment,
+        })
+    }
+
+    pub async fn check_receipt(&self, code: Uuid) -> Result<ReceiptCheck, AppError> {
+        let receipt = self
+            .receipts
+            .lock()
+            .await
+            .get(&code.to_string())
+            .cloned()
+            .ok_or(AppError::NotFound)?;
+        let document = self.get_receipt_document(receipt.school_id, receipt.payment_id).await?;
+
+        let initial: String = document.student.first_name.chars().take(1).collect();
+        Ok(ReceiptCheck {
+            receipt_number: receipt.number,
+            school_name: document.school.name,
+            student_name: format!("{}. {}", initial, document.student.last_name),
+            amount_kobo: receipt.amount_kobo,
+            currency: document.payment.currency,
+            paid_at: document.payment.paid_at,
+            payment_status: document.payment.status,
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore,
+    ledger::{LedgerEntryKind, NewLedgerEntry, Payment, TransactionStatus},
+    money::Currency,
+};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum RefundStatus {
+    Pending, // waiting on the provider
+    Processed,
+    Failed,
+}
+
+/// Money given back against a payment. The ledger is only reversed once the
+/// refund is processed, so a failed refund leaves the student's account as it was.
+#[derive(Clone, Serialize)]
+pub struct Refund {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub payment_id: Uuid,
+    pub invoice_id: Uuid,
+    pub currency: Currency, // the payment's
+    pub amount_kobo: u64,
+    pub reason: String,
+    pub status: RefundStatus,
+    pub provider_refund_id: Option<String>,
+    pub failure_reason: Option<String>,
+    pub requested_by: String,
+    pub created_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateRefundRequest {
+    pub amount_kobo: Option<u64>, // in the payment's currency; defaults to everything not yet refunded
+    pub reason: String,
+}
+
+impl AppStore {
+    /// Opens a refund against a successful payment. Pending refunds count
+    /// against what's left to refund, so two requests can't both take it.
+    pub async fn request_refund(
+        &self,
+        school_id: Uuid,
+        payment_id: Uuid,
+        requested_by: &str,
+        req: CreateRefundRequest,
+    ) -> Result<(Refund, Payment), AppError> {
+        if req.reason.trim().is_empty() {
+            return Err(AppError::invalid("reason", "A reason is required"));
+        }
+
+        let payment = {
+            let payments = self.payments.lock().await;
+            payments
+                .get(&payment_id.to_string())
+                .filter(|p| p.school_id == school_id)
+                .cloned()
+                .ok_or(AppError::NotFound)?
+        };
+        if payment.status != TransactionStatus::Successful {
+            return Err(AppError::invalid("payment", "Only successful payments can be refunded"));
+        }
+
+        let mut refunds = self.refunds.lock().await;
+        let committed: u64 = refunds
+            .values()
+            .filter(|r| r.payment_id == payment_id && r.status != RefundStatus::Failed)
+            .map(|r| r.amount_kobo)
+            .sum();
+        let refundable = payment.amount_kobo.saturating_sub(committed);
+        let amount_kobo = req.amount_kobo.unwrap_or(refundable);
+        if amount_kobo == 0 || amount_kobo > refundable {
+            return Err(AppError::invalid(
+                "amount_kobo",
+                &format!("Amount must be between 1 and the {} kobo left to refund", refundable),
+            ));
+        }
+
+        let refund = Refund {
+            id: Uuid::new_v4(),
+            school_id,
+            student_id: payment.student_id,
+            payment_id,
+            invoice_id: payment.invoice_id,
+            currency: payment.currency,
+            amount_kobo,
+            reason: req.reason,
+            status: RefundStatus::Pending,
+            provider_refund_id: None,
+            failure_reason: None,
+            requested_by: requested_by.to_string(),
+            created_at: Utc::now(),
+            processed_at: None,
+        };
+        refunds.insert(refund.id.to_string(), refund.clone());
+        Ok((refund, payment))
+    }
+
+    pub async fn set_provider_refund_id(&self, refund_id: Uuid, provider_refund_id: String) -> Result<Refund, AppError> {
+        let mut refunds = self.refunds.lock().await;
+        let refund = refunds.get_mut(&refund_id.to_string()).ok_or(AppError::NotFound)?;
+        refund.provider_refund_id = Some(provider_refund_id);
+        Ok(refund.clone())
+    }
+
+    /// Marks a refund processed and posts the reversal. Calling it again is a no-op.
+    pub async fn complete_refund(&self, refund_id: Uuid) -> Result<Refund, AppError> {
+        let refund = {
+            let mut refunds = self.refunds.lock().await;
+            let refund = refunds.get_mut(&refund_id.to_string()).ok_or(AppError::NotFound)?;
+            match refund.status {
+                RefundStatus::Processed => return Ok(refund.clone()),
+                RefundStatus::Failed => return Err(AppError::Conflict("Refund has already failed".to_string())),
+                RefundStatus::Pending => {}
+            }
+            refund.status = RefundStatus::Processed;
+            refund.processed_at = Some(Utc::now());
+            refund.clone()
+        };
+
+        let reference = {
+            let mut payments = self.payments.lock().await;
+            let payment = payments
+                .get_mut(&refund.payment_id.to_string())
+                .ok_or(AppError::NotFound)?;
+            payment.refunded_kobo += refund.amount_kobo;
+            if payment.refunded_kobo >= payment.amount_kobo {
+                payment.status = TransactionStatus::Refunded;
+            }
+            payment.reference.clone()
+        };
+
+        self.post_ledger_entry(NewLedgerEntry {
+            school_id: refund.school_id,
+            student_id: refund.student_id,
+            invoice_id: Some(refund.invoice_id),
+            payment_id: Some(refund.payment_id),
+            kind: LedgerEntryKind::Refund,
+            description: format!("Refund of payment {}: {}", reference, refund.reason),
+            debit_kobo: refund.amount_kobo,
+            credit_kobo: 0,
+            created_by: refund.requested_by.clone(),
+        })
+        .await?;
+
+        Ok(refund)
+    }
+
+    pub async fn fail_refund(&self, refund_id: Uuid, reason: &str) -> Result<Refund, AppError> {
+        let mut refunds = self.refunds.lock().await;
+        let refund = refunds.get_mut(&refund_id.to_string()).ok_or(AppError::NotFound)?;
+        if refund.status == RefundStatus::Pending {
+            refund.status = RefundStatus::Failed;
+            refund.failure_reason = Some(reason.to_string());
+        }
+        Ok(refund.clone())
+    }
+
+    /// The refund a provider notification is about: by the provider's own
+    /// refund id when we have it, else the oldest pending refund on the payment.
+    pub async fn find_refund_for_event(
+        &self,
+        provider_refund_id: Option<&str>,
+        reference: Option<&str>,
+    ) -> Result<Refund, AppError> {
+        if let Some(id) = provider_refund_id {
+            let refunds = self.refunds.lock().await;
+            if let Some(refund) = refunds.values().find(|r| r.provider_refund_id.as_deref() == Some(id)) {
+                return Ok(refund.clone());
+            }
+        }
+
+        let payment_id = {
+            let payments = self.payments.lock().await;
+            payments
+                .values()
+                .find(|p| reference.is_some_and(|r| p.reference == r))
+                .map(|p| p.id)
+                .ok_or(AppError::NotFound)?
+        };
+        let refunds = self.refunds.lock().await;
+        refunds
+            .values()
+            .filter(|r| r.payment_id == payment_id && r.status == RefundStatus::Pending)
+            .min_by_key(|r| r.created_at)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn get_payment_refunds(&self, school_id: Uuid, payment_id: Uuid) -> Result<Vec<Refund>, AppError> {
+        let refunds = self.refunds.lock().await;
+        let mut list: Vec<Refund> = refunds
+            .values()
+            .filter(|r| r.school_id == school_id && r.payment_id == payment_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|r| r.created_at);
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        PaymentStatus,
+        testing::{billed_student, date, paid_payment},
+    };
+
+    fn refund_of(amount_kobo: Option<u64>) -> CreateRefundRequest {
+        CreateRefundRequest { amount_kobo, reason: "Overpaid".to_string() }
+    }
+
+    #[tokio::test]
+    async fn a_payment_can_be_refunded_in_parts_up_to_its_amount() {
+        let (store, invoice) = billed_student(5_000_000, date(2099, 12, 31)).await;
+        let payment = paid_payment(&store, &invoice, "sch-1", 2_000_000).await;
+
+        let (part, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(500_000))).await.unwrap();
+        store.complete_refund(part.id).await.unwrap();
+        // with no amount, whatever is left
+        let (rest, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(None)).await.unwrap();
+        assert_eq!(rest.amount_kobo, 1_500_000);
+        store.complete_refund(rest.id).await.unwrap();
+
+        assert!(store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(1))).await.is_err());
+        let payment = store.find_payment_by_reference(invoice.school_id, "sch-1").await.unwrap();
+        assert_eq!(payment.refunded_kobo, 2_000_000);
+        assert_eq!(payment.status, TransactionStatus::Refunded);
+    }
+
+    #[tokio::test]
+    async fn a_pending_refund_counts_against_what_is_left() {
+        let (store, invoice) = billed_student(5_000_000, date(2099, 12, 31)).await;
+        let payment = paid_payment(&store, &invoice, "sch-1", 2_000_000).await;
+
+        store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(1_500_000))).await.unwrap();
+        assert!(store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(600_000))).await.is_err());
+        assert!(store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(500_000))).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_failed_refund_releases_its_amount() {
+        let (store, invoice) = billed_student(5_000_000, date(2099, 12, 31)).await;
+        let payment = paid_payment(&store, &invoice, "sch-1", 2_000_000).await;
+
+        let (refund, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(None)).await.unwrap();
+        store.fail_refund(refund.id, "Card expired").await.unwrap();
+        assert!(store.complete_refund(refund.id).await.is_err());
+
+        let (retry, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(None)).await.unwrap();
+        assert_eq!(retry.amount_kobo, 2_000_000);
+    }
+
+    #[tokio::test]
+    async fn completing_a_refund_again_changes_nothing() {
+        let (store, invoice) = billed_student(5_000_000, date(2099, 12, 31)).await;
+        let payment = paid_payment(&store, &invoice, "sch-1", 2_000_000).await;
+        let (refund, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(500_000))).await.unwrap();
+
+        store.complete_refund(refund.id).await.unwrap();
+        let entries = store.ledger.lock().await.len();
+        let again = store.complete_refund(refund.id).await.unwrap();
+
+        assert_eq!(again.status, RefundStatus::Processed);
+        assert_eq!(store.ledger.lock().await.len(), entries);
+        let payment = store.find_payment_by_reference(invoice.school_id, "sch-1").await.unwrap();
+        assert_eq!(payment.refunded_kobo, 500_000);
+    }
+
+    #[tokio::test]
+    async fn a_refund_reopens_the_invoice_balance() {
+        let (store, invoice) = billed_student(5_000_000, date(2099, 12, 31)).await;
+        let payment = paid_payment(&store, &invoice, "sch-1", 5_000_000).await;
+        assert_eq!(store.get_invoice(invoice.school_id, invoice.id).await.unwrap().status, PaymentStatus::Paid);
+
+        let (part, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(Some(1_000_000))).await.unwrap();
+        store.complete_refund(part.id).await.unwrap();
+        let after_part = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!(after_part.total_kobo, 5_000_000);
+        assert_eq!((after_part.amount_paid_kobo, after_part.balance_kobo), (4_000_000, 1_000_000));
+        assert_eq!(after_part.status, PaymentStatus::PartiallyPaid);
+
+        let (rest, _) = store.request_refund(invoice.school_id, payment.id, "owner", refund_of(None)).await.unwrap();
+        store.complete_refund(rest.id).await.unwrap();
+        let after_all = store.get_invoice(invoice.school_id, invoice.id).await.unwrap();
+        assert_eq!((after_all.amount_paid_kobo, after_all.balance_kobo), (0, 5_000_000));
+        assert_eq!(after_all.status, PaymentStatus::Pending);
+    }
+}
@@ -0,0 +1,344 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore, PaymentStatus, Student,
+    ledger::Invoice,
+};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum ReminderChannel {
+    Email,
+    Sms,
+}
+
+/// When to remind, counted in days from the due date: -7 is a week before
+/// and 0 the day itself; 7 with `repeat_every_days: 7` is every week from
+/// a week late.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ReminderRule {
+    pub days_from_due: i64,
+    pub repeat_every_days: Option<u32>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct ReminderSchedule {
+    pub enabled: bool,
+    pub channels: Vec<ReminderChannel>,
+    pub rules: Vec<ReminderRule>,
+}
+
+/// Per-student opt-outs, set when a guardian asks not to be reminded.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct ReminderPreferences {
+    pub email_opt_out: bool,
+    pub sms_opt_out: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum DeliveryStatus {
+    Pending, // claimed by a run that is still sending it
+    Sent,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ReminderDelivery {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub invoice_id: Uuid,
+    pub channel: ReminderChannel,
+    pub recipient: String,
+    pub message: String,
+    pub payment_link: Option<String>,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    pub sent_on: NaiveDate,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A reminder that should go out today, before it has been sent.
+pub struct DueReminder {
+    pub student: Student,
+    pub invoice: Invoice,
+    pub due_date: NaiveDate,
+    pub channel: ReminderChannel,
+    pub recipient_name: Option<String>,
+    pub recipient: String,
+}
+
+// one reminder per invoice, channel and recipient a day, whatever became of it
+fn is_same_reminder(
+    delivery: &ReminderDelivery,
+    invoice_id: Uuid,
+    channel: ReminderChannel,
+    recipient: &str,
+    today: NaiveDate,
+) -> bool {
+    delivery.invoice_id == invoice_id
+        && delivery.channel == channel
+        && delivery.recipient == recipient
+        && delivery.sent_on == today
+}
+
+/// What the student's guardians are chased for next: the earliest unpaid
+/// installment, or the invoice itself.
+fn next_due_date(invoice: &Invoice) -> NaiveDate {
+    invoice
+        .installments
+        .iter()
+        .find(|i| i.status != PaymentStatus::Paid)
+        .map_or(invoice.due_date, |i| i.due_date)
+}
+
+impl ReminderRule {
+    fn matches(&self, days_from_due: i64) -> bool {
+        match self.repeat_every_days {
+            Some(every) if days_from_due > self.days_from_due => {
+                (days_from_due - self.days_from_due) % every as i64 == 0
+            }
+            _ => days_from_due == self.days_from_due,
+        }
+    }
+}
+
+impl AppStore {
+    pub async fn set_reminder_schedule(
+        &self,
+        school_id: Uuid,
+        schedule: ReminderSchedule,
+    ) -> Result<ReminderSchedule, AppError> {
+        if schedule.enabled && (schedule.channels.is_empty() || schedule.rules.is_empty()) {
+            return Err(AppError::invalid("rules", "Reminders need at least one channel and one rule"));
+        }
+        if schedule.rules.iter().any(|r| r.repeat_every_days == Some(0)) {
+            return Err(AppError::invalid("rules", "Repeating reminders need an interval of at least one day"));
+        }
+
+        self.reminder_schedules
+            .lock()
+            .await
+            .insert(school_id.to_string(), schedule.clone());
+        Ok(schedule)
+    }
+
+    pub async fn get_reminder_schedule(&self, school_id: Uuid) -> Result<ReminderSchedule, AppError> {
+        let schedules = self.reminder_schedules.lock().await;
+        Ok(schedules.get(&school_id.to_string()).cloned().unwrap_or_default())
+    }
+
+    pub async fn set_reminder_preferences(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        preferences: ReminderPreferences,
+    ) -> Result<ReminderPreferences, AppError> {
+        self.get_student(school_id, student_id).await?;
+        self.reminder_preferences
+            .lock()
+            .await
+            .insert(student_id.to_string(), preferences.clone());
+        Ok(preferences)
+    }
+
+    pub async fn get_reminder_preferences(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+    ) -> Result<ReminderPreferences, AppError> {
+        self.get_student(school_id, student_id).await?;
+        let preferences = self.reminder_preferences.lock().await;
+        Ok(preferences.get(&student_id.to_string()).cloned().unwrap_or_default())
+    }
+
+    /// Who to remind: every linked guardian, plus the guardian contact on the
+    /// student's own profile, without repeating an address.
+    async fn reminder_recipients(
+        &self,
+        student: &Student,
+        channel: ReminderChannel,
+    ) -> Vec<(Option<String>, String)> {
+        let guardians = self.guardians.lock().await;
+        let mut contacts: Vec<(Option<String>, Option<String>)> = guardians
+            .values()
+            .filter(|g| g.school_id == student.school_id && g.student_ids.contains(&student.id))
+            .map(|g| {
+                let address = match channel {
+                    ReminderChannel::Email => g.email.clone(),
+                    ReminderChannel::Sms => g.phone.clone(),
+                };
+                (Some(g.name.clone()), address)
+            })
+            .collect();
+        let profile = &student.profile;
+        contacts.push((
+            profile.guardian_name.clone(),
+            match channel {
+                ReminderChannel::Email => profile.guardian_email.clone(),
+                ReminderChannel::Sms => profile.guardian_phone.clone(),
+            },
+        ));
+
+        let mut recipients: Vec<(Option<String>, String)> = Vec::new();
+        for (name, address) in contacts {
+            let Some(address) = address.filter(|a| !a.trim().is_empty()) else {
+                continue;
+            };
+            if !recipients.iter().any(|(_, a)| a.eq_ignore_ascii_case(&address)) {
+                recipients.push((name, address));
+            }
+        }
+        recipients
+    }
+
+    /// Every reminder the school's schedule calls for on `today` that hasn't
+    /// already gone out (or been attempted) today.
+    pub async fn get_due_reminders(&self, school_id: Uuid, today: NaiveDate) -> Result<Vec<DueReminder>, AppError> {
+        let schedule = self.get_reminder_schedule(school_id).await?;
+        if !schedule.enabled {
+            return Ok(Vec::new());
+        }
+
+        let open: Vec<Invoice> = {
+            let invoices = self.invoices.lock().await;
+            invoices
+                .values()
+                .filter(|i| i.school_id == school_id && i.balance_kobo > 0)
+                .cloned()
+                .collect()
+        };
+
+        let mut due = Vec::new();
+        for invoice in open {
+            let due_date = next_due_date(&invoice);
+            let days_from_due = (today - due_date).num_days();
+            if !schedule.rules.iter().any(|r| r.matches(days_from_due)) {
+                continue;
+            }
+
+            let student = self.get_student(school_id, invoice.student_id).await?;
+            let preferences = self.get_reminder_preferences(school_id, student.id).await?;
+            for &channel in &schedule.channels {
+                let opted_out = match channel {
+                    ReminderChannel::Email => preferences.email_opt_out,
+                    ReminderChannel::Sms => preferences.sms_opt_out,
+                };
+                if opted_out {
+                    continue;
+                }
+
+                for (recipient_name, recipient) in self.reminder_recipients(&student, channel).await {
+                    let already_sent = self
+                        .reminder_log
+                        .lock()
+                        .await
+                        .values()
+                        .any(|d| is_same_reminder(d, invoice.id, channel, &recipient, today));
+                    if !already_sent {
+                        due.push(DueReminder {
+                            student: student.clone(),
+                            invoice: invoice.clone(),
+                            due_date,
+                            channel,
+                            recipient_name,
+                            recipient,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(due)
+    }
+
+    /// Logs a due reminder as Pending before it is sent, unless another run
+    /// already has it. Checking and logging under one lock means a send-now
+    /// racing the scheduled job can't send the same reminder twice.
+    pub async fn claim_reminder(&self, reminder: &DueReminder, today: NaiveDate) -> Option<ReminderDelivery> {
+        let mut log = self.reminder_log.lock().await;
+        let claimed = log
+            .values()
+            .any(|d| is_same_reminder(d, reminder.invoice.id, reminder.channel, &reminder.recipient, today));
+        if claimed {
+            return None;
+        }
+
+        let delivery = ReminderDelivery {
+            id: Uuid::new_v4(),
+            school_id: reminder.invoice.school_id,
+            student_id: reminder.student.id,
+            invoice_id: reminder.invoice.id,
+            channel: reminder.channel,
+            recipient: reminder.recipient.clone(),
+            message: String::new(),
+            payment_link: None,
+            status: DeliveryStatus::Pending,
+            error: None,
+            sent_on: today,
+            created_at: Utc::now(),
+        };
+        log.insert(delivery.id.to_string(), delivery.clone());
+        Some(delivery)
+    }
+
+    pub async fn record_reminder_delivery(&self, delivery: ReminderDelivery) -> ReminderDelivery {
+        self.reminder_log
+            .lock()
+            .await
+            .insert(delivery.id.to_string(), delivery.clone());
+        delivery
+    }
+
+    pub async fn get_student_reminders(&self, school_id: Uuid, student_id: Uuid) -> Result<Vec<ReminderDelivery>, AppError> {
+        self.get_student(school_id, student_id).await?;
+
+        let log = self.reminder_log.lock().await;
+        let mut list: Vec<ReminderDelivery> = log
+            .values()
+            .filter(|d| d.school_id == school_id && d.student_id == student_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|d| d.created_at);
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{billed_student, date};
+
+    async fn due_reminder(store: &AppStore, invoice: &Invoice) -> DueReminder {
+        DueReminder {
+            student: store.get_student(invoice.school_id, invoice.student_id).await.unwrap(),
+            invoice: invoice.clone(),
+            due_date: invoice.due_date,
+            channel: ReminderChannel::Sms,
+            recipient_name: None,
+            recipient: "+2348000000000".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_reminder_is_claimed_once_a_day() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 10, 1)).await;
+        let reminder = due_reminder(&store, &invoice).await;
+        let today = date(2026, 10, 8);
+
+        let (first, second) = tokio::join!(store.claim_reminder(&reminder, today), store.claim_reminder(&reminder, today));
+        assert_eq!(first.is_some() as u8 + second.is_some() as u8, 1);
+        let claimed = first.or(second).unwrap();
+        assert_eq!(claimed.status, DeliveryStatus::Pending);
+
+        // still claimed once the send has been recorded, whatever its outcome
+        store
+            .record_reminder_delivery(ReminderDelivery { status: DeliveryStatus::Failed, ..claimed })
+            .await;
+        assert!(store.claim_reminder(&reminder, today).await.is_none());
+
+        assert!(store.claim_reminder(&reminder, date(2026, 10, 9)).await.is_some());
+    }
+}
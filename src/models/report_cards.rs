@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore, Student,
+    academics::{SubjectSummary, Term},
+};
+
+// ---- Report card remarks ----
+
+#[derive(Clone, Serialize)]
+pub struct ReportCardRemarks {
+    pub school_id: Uuid,
+    pub student_id: Uuid,
+    pub term_code: String,
+    pub class_teacher_remark: Option<String>,
+    pub principal_remark: Option<String>,
+    pub days_opened: Option<u32>, // overrides the attendance register when set
+    pub days_present: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct SetReportCardRemarksRequest {
+    pub class_teacher_remark: Option<String>,
+    pub principal_remark: Option<String>,
+    pub days_opened: Option<u32>,
+    pub days_present: Option<u32>,
+}
+
+// ---- Report card ----
+
+#[derive(Clone, Serialize)]
+pub struct ReportCardAttendance {
+    pub days_opened: u32,
+    pub days_present: u32,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ReportCard {
+    pub school_name: String,
+    pub student: Student,
+    pub term: Term,
+    pub subjects: Vec<SubjectSummary>,
+    pub total: f64,
+    pub average: f64,
+    pub position: usize,
+    pub class_size: usize,
+    pub attendance: Option<ReportCardAttendance>,
+    pub class_teacher_remark: Option<String>,
+    pub principal_remark: Option<String>,
+}
+
+fn remarks_key(student_id: Uuid, term_code: &str) -> String {
+    format!("{}:{}", student_id, term_code)
+}
+
+impl AppStore {
+    pub async fn set_report_card_remarks(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        term_code: &str,
+        req: SetReportCardRemarksRequest,
+    ) -> Result<ReportCardRemarks, AppError> {
+        self.get_student(school_id, student_id).await?;
+        let term = self.get_term(school_id, term_code).await?;
+
+        if let (Some(opened), Some(present)) = (req.days_opened, req.days_present)
+            && present > opened
+        {
+            return Err(AppError::invalid("days_present", "Days present cannot exceed days opened"));
+        }
+
+        let remarks = ReportCardRemarks {
+            school_id,
+            student_id,
+            term_code: term.code,
+            class_teacher_remark: req.class_teacher_remark,
+            principal_remark: req.principal_remark,
+            days_opened: req.days_opened,
+            days_present: req.days_present,
+        };
+
+        self.report_card_remarks
+            .lock()
+            .await
+            .insert(remarks_key(student_id, term_code), remarks.clone());
+        Ok(remarks)
+    }
+
+    /// Report cards for every student in a class, built from a single pass
+    /// over the class results so positions are consistent across the batch.
+    pub async fn build_class_report_cards(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+        term_code: &str,
+    ) -> Result<Vec<ReportCard>, AppError> {
+        let school = self.get_school(school_id).await?;
+        let term = self.get_term(school_id, term_code).await?;
+        let students = self.get_class_students(school_id, class_name).await?;
+        let results = self.get_class_results(school_id, class_name, term_code).await?;
+        let class_size = results.len();
+
+        // attendance from the daily register, used unless the remarks override it
+        let mut recorded = HashMap::new();
+        for student in &students {
+            let summary = self.get_attendance_summary(school_id, student.id, term_code).await?;
+            if summary.days_recorded > 0 {
+                recorded.insert(
+                    student.id,
+                    ReportCardAttendance {
+                        days_opened: summary.days_recorded,
+                        days_present: summary.present + summary.late,
+                    },
+                );
+            }
+        }
+
+        let remarks = self.report_card_remarks.lock().await;
+        let cards = results
+            .into_iter()
+            .filter_map(|result| {
+                let student = students.iter().find(|s| s.id == result.student_id)?.clone();
+                let remark = remarks.get(&remarks_key(student.id, &term.code));
+                let manual = remark.and_then(|r| match (r.days_opened, r.days_present) {
+                    (Some(days_opened), Some(days_present)) => Some(ReportCardAttendance {
+                        days_opened,
+                        days_present,
+                    }),
+                    _ => None,
+                });
+                let attendance = manual.or_else(|| recorded.remove(&student.id));
+
+                Some(ReportCard {
+                    school_name: school.name.clone(),
+                    student,
+                    term: term.clone(),
+                    subjects: result.subjects,
+                    total: result.total,
+                    average: result.average,
+                    position: result.position,
+                    class_size,
+                    attendance,
+                    class_teacher_remark: remark.and_then(|r| r.class_teacher_remark.clone()),
+                    principal_remark: remark.and_then(|r| r.principal_remark.clone()),
+                })
+            })
+            .collect();
+
+        Ok(cards)
+    }
+
+    pub async fn build_report_card(
+        &self,
+        school_id: Uuid,
+        student_id: Uuid,
+        term_code: &str,
+    ) -> Result<ReportCard, AppError> {
+        let student = self.get_student(school_id, student_id).await?;
+        let class_name = student
+            .class_name
+            .ok_or_else(|| AppError::invalid("class_name", "Student has not been assigned to a class"))?;
+
+        self.build_class_report_cards(school_id, &class_name, term_code)
+            .await?
+            .into_iter()
+            .find(|card| card.student.id == student_id)
+            .ok_or(AppError::NotFound)
+    }
+}
@@ -0,0 +1,481 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{
+    AppStore, Student,
+    ledger::{Invoice, LedgerEntryKind, PaymentMethod},
+    money::Currency,
+};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionPeriod {
+    #[default]
+    Day,
+    Week, // ISO weeks, e.g. "2026-W42"
+    Term, // the term of the invoice that was paid
+}
+
+/// `class_name` matches every class that starts with it, as fee amounts
+/// do, so "JSS1" covers JSS1A and JSS1B.
+#[derive(Deserialize)]
+pub struct CollectionsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub group_by: CollectionPeriod,
+    pub method: Option<PaymentMethod>,
+    pub term: Option<String>,
+    pub class_name: Option<String>,
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// Filters shared by the outstanding balance, aging and debtors reports.
+#[derive(Deserialize)]
+pub struct BalanceQuery {
+    pub term: Option<String>,
+    pub class_name: Option<String>,
+    pub min_balance_kobo: Option<u64>,
+    pub as_of: Option<NaiveDate>, // defaults to today; only used for ages
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// Amounts in the report's currency, as are those of every row type below.
+#[derive(Serialize)]
+pub struct CollectionRow {
+    pub period: String,
+    pub method: PaymentMethod,
+    pub payments: usize,
+    pub collected_kobo: u64,
+    pub refunded_kobo: u64,
+    pub net_kobo: i64,
+}
+
+#[derive(Serialize)]
+pub struct MethodTotal {
+    pub method: PaymentMethod,
+    pub payments: usize,
+    pub net_kobo: i64,
+}
+
+#[derive(Serialize)]
+pub struct CollectionsReport {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub group_by: CollectionPeriod,
+    pub currency: Currency, // every amount in the report is in its minor unit
+    pub rows: Vec<CollectionRow>,
+    pub by_method: Vec<MethodTotal>,
+    pub collected_kobo: u64,
+    pub refunded_kobo: u64,
+    pub net_kobo: i64,
+}
+
+#[derive(Serialize)]
+pub struct ClassBalance {
+    pub class_name: String,
+    pub students: usize,
+    pub invoiced_kobo: u64,
+    pub paid_kobo: u64,
+    pub outstanding_kobo: u64,
+}
+
+#[derive(Serialize)]
+pub struct FeeItemBalance {
+    pub fee_item_id: Option<Uuid>, // none for late fees
+    pub description: String,
+    pub billed_kobo: u64,
+    pub outstanding_kobo: u64,
+}
+
+#[derive(Serialize)]
+pub struct OutstandingReport {
+    pub term: Option<String>,
+    pub currency: Currency, // every amount in the report is in its minor unit
+    pub by_class: Vec<ClassBalance>,
+    pub by_fee_item: Vec<FeeItemBalance>,
+    pub outstanding_kobo: u64,
+}
+
+#[derive(Serialize)]
+pub struct AgingBucket {
+    pub label: String, // "Not yet due", "0-30 days", "31-60 days", "60+ days"
+    pub invoices: usize,
+    pub outstanding_kobo: u64,
+}
+
+#[derive(Serialize)]
+pub struct AgingReport {
+    pub as_of: NaiveDate,
+    pub currency: Currency, // every amount in the report is in its minor unit
+    pub buckets: Vec<AgingBucket>,
+    pub outstanding_kobo: u64,
+}
+
+#[derive(Serialize)]
+pub struct Debtor {
+    pub student_id: Uuid,
+    pub admission_number: String,
+    pub name: String,
+    pub class_name: Option<String>,
+    pub guardian_phone: Option<String>,
+    pub invoices: usize,
+    pub balance_kobo: u64,
+    pub oldest_due_date: NaiveDate,
+    pub days_overdue: i64, // negative while nothing is due yet
+}
+
+#[derive(Serialize)]
+pub struct DebtorsReport {
+    pub as_of: NaiveDate,
+    pub currency: Currency, // every amount in the report is in its minor unit
+    pub debtors: Vec<Debtor>,
+    pub outstanding_kobo: u64,
+}
+
+fn in_class(student: Option<&Student>, class_name: Option<&str>) -> bool {
+    match class_name {
+        None => true,
+        Some(prefix) => student
+            .and_then(|s| s.class_name.as_deref())
+            .is_some_and(|c| c.starts_with(prefix)),
+    }
+}
+
+fn class_label(student: Option<&Student>) -> String {
+    student
+        .and_then(|s| s.class_name.clone())
+        .unwrap_or_else(|| "Unassigned".to_string())
+}
+
+/// Splits what is still owed on an invoice over its line items, with late
+/// fees last. Payments and discounts settle the lines in invoice order.
+fn outstanding_by_line(invoice: &Invoice) -> Vec<(Option<Uuid>, String, u64, u64)> {
+    let line_total: u64 = invoice.line_items.iter().map(|l| l.amount_kobo).sum();
+    let mut lines: Vec<(Option<Uuid>, String, u64)> = invoice
+        .line_items
+        .iter()
+        .map(|l| (l.fee_item_id, l.description.clone(), l.amount_kobo))
+        .collect();
+    let late_fees = invoice.total_kobo.saturating_sub(line_total);
+    if late_fees > 0 {
+        lines.push((None, "Late fees".to_string(), late_fees));
+    }
+
+    let mut settled = invoice.total_kobo.saturating_sub(invoice.balance_kobo.max(0) as u64);
+    lines
+        .into_iter()
+        .map(|(id, description, amount)| {
+            let covered = settled.min(amount);
+            settled -= covered;
+            (id, description, amount, amount - covered)
+        })
+        .collect()
+}
+
+fn aging_label(days_overdue: i64) -> &'static str {
+    match days_overdue {
+        ..0 => "Not yet due",
+        0..=30 => "0-30 days",
+        31..=60 => "31-60 days",
+        _ => "60+ days",
+    }
+}
+
+impl AppStore {
+    /// Money received, net of refunds, straight from the ledger: Payment
+    /// credits count on the day the payment was made, Refund debits on the
+    /// day they were posted.
+    pub async fn get_collections_report(
+        &self,
+        school_id: Uuid,
+        query: &CollectionsQuery,
+    ) -> Result<CollectionsReport, AppError> {
+        let currency = self.get_school(school_id).await?.currency;
+        let students: HashMap<Uuid, Student> = self.school_students(school_id).await;
+        let invoices: HashMap<Uuid, Invoice> = self.school_invoices(school_id).await;
+        let payments = self.payments.lock().await.clone();
+        let ledger = self.ledger.lock().await.clone();
+
+        let mut rows: BTreeMap<(String, String), CollectionRow> = BTreeMap::new();
+        for entry in ledger.iter().filter(|e| e.school_id == school_id) {
+            if !matches!(entry.kind, LedgerEntryKind::Payment | LedgerEntryKind::Refund) {
+                continue;
+            }
+            let Some(payment) = entry.payment_id.and_then(|id| payments.get(&id.to_string())) else {
+                continue;
+            };
+            let invoice = invoices.get(&payment.invoice_id);
+            let date = match entry.kind {
+                LedgerEntryKind::Payment => payment.paid_at.unwrap_or(entry.created_at),
+                _ => entry.created_at,
+            }
+            .date_naive();
+
+            if query.from.is_some_and(|from| date < from)
+                || query.to.is_some_and(|to| date > to)
+                || query.method.is_some_and(|m| m != payment.method)
+                || query
+                    .term
+                    .as_ref()
+                    .is_some_and(|t| invoice.is_none_or(|i| &i.term_code != t))
+                || !in_class(students.get(&payment.student_id), query.class_name.as_deref())
+            {
+                continue;
+            }
+
+            let period = match query.group_by {
+                CollectionPeriod::Day => date.to_string(),
+                CollectionPeriod::Week => {
+                    let week = date.iso_week();
+                    format!("{}-W{:02}", week.year(), week.week())
+                }
+                CollectionPeriod::Term => invoice.map(|i| i.term_code.clone()).unwrap_or_default(),
+            };
+            let row = rows
+                .entry((period.clone(), payment.method.label().to_string()))
+                .or_insert(CollectionRow {
+                    period,
+                    method: payment.method,
+                    payments: 0,
+                    collected_kobo: 0,
+                    refunded_kobo: 0,
+                    net_kobo: 0,
+                });
+            if entry.kind == LedgerEntryKind::Payment {
+                row.payments += 1;
+                row.collected_kobo += entry.credit_kobo;
+            } else {
+                row.refunded_kobo += entry.debit_kobo;
+            }
+            row.net_kobo = row.collected_kobo as i64 - row.refunded_kobo as i64;
+        }
+
+        let rows: Vec<CollectionRow> = rows.into_values().collect();
+        let mut by_method: Vec<MethodTotal> = Vec::new();
+        for row in &rows {
+            match by_method.iter_mut().find(|m| m.method == row.method) {
+                Some(total) => {
+                    total.payments += row.payments;
+                    total.net_kobo += row.net_kobo;
+                }
+                None => by_method.push(MethodTotal {
+                    method: row.method,
+                    payments: row.payments,
+                    net_kobo: row.net_kobo,
+                }),
+            }
+        }
+        let collected_kobo: u64 = rows.iter().map(|r| r.collected_kobo).sum();
+        let refunded_kobo: u64 = rows.iter().map(|r| r.refunded_kobo).sum();
+
+        Ok(CollectionsReport {
+            from: query.from,
+            to: query.to,
+            group_by: query.group_by,
+            currency,
+            rows,
+            by_method,
+            collected_kobo,
+            refunded_kobo,
+            net_kobo: collected_kobo as i64 - refunded_kobo as i64,
+        })
+    }
+
+    pub async fn get_outstanding_report(
+        &self,
+        school_id: Uuid,
+        query: &BalanceQuery,
+    ) -> Result<OutstandingReport, AppError> {
+        let currency = self.get_school(school_id).await?.currency;
+        let students = self.school_students(school_id).await;
+        let invoices = self.open_invoices(school_id, &students, query).await;
+
+        let mut by_class: BTreeMap<String, (ClassBalance, Vec<Uuid>)> = BTreeMap::new();
+        let mut by_fee_item: Vec<FeeItemBalance> = Vec::new();
+        for invoice in &invoices {
+            let class_name = class_label(students.get(&invoice.student_id));
+            let (line, seen) = by_class.entry(class_name.clone()).or_insert((
+                ClassBalance {
+                    class_name,
+                    students: 0,
+                    invoiced_kobo: 0,
+                    paid_kobo: 0,
+                    outstanding_kobo: 0,
+                },
+                Vec::new(),
+            ));
+            if !seen.contains(&invoice.student_id) {
+                seen.push(invoice.student_id);
+                line.students += 1;
+            }
+            line.invoiced_kobo += invoice.total_kobo;
+            line.paid_kobo += invoice.amount_paid_kobo;
+            line.outstanding_kobo += invoice.balance_kobo.max(0) as u64;
+
+            for (fee_item_id, description, billed, outstanding) in outstanding_by_line(invoice) {
+                let existing = by_fee_item
+                    .iter_mut()
+                    .find(|f| f.fee_item_id == fee_item_id && f.description == description);
+                match existing {
+                    Some(item) => {
+                        item.billed_kobo += billed;
+                        item.outstanding_kobo += outstanding;
+                    }
+                    None => by_fee_item.push(FeeItemBalance {
+                        fee_item_id,
+                        description,
+                        billed_kobo: billed,
+                        outstanding_kobo: outstanding,
+                    }),
+                }
+            }
+        }
+        by_fee_item.sort_by_key(|f| Reverse(f.outstanding_kobo));
+
+        let by_class: Vec<ClassBalance> = by_class.into_values().map(|(line, _)| line).collect();
+        Ok(OutstandingReport {
+            term: query.term.clone(),
+            currency,
+            outstanding_kobo: by_class.iter().map(|c| c.outstanding_kobo).sum(),
+            by_class,
+            by_fee_item,
+        })
+    }
+
+    /// Unpaid balances by how long past their due date the invoices are.
+    pub async fn get_aging_report(&self, school_id: Uuid, query: &BalanceQuery) -> Result<AgingReport, AppError> {
+        let currency = self.get_school(school_id).await?.currency;
+        let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+        let students = self.school_students(school_id).await;
+        let invoices = self.open_invoices(school_id, &students, query).await;
+
+        let mut buckets: Vec<AgingBucket> = ["Not yet due", "0-30 days", "31-60 days", "60+ days"]
+            .iter()
+            .map(|label| AgingBucket {
+                label: label.to_string(),
+                invoices: 0,
+                outstanding_kobo: 0,
+            })
+            .collect();
+        for invoice in &invoices {
+            let label = aging_label((as_of - invoice.due_date).num_days());
+            if let Some(bucket) = buckets.iter_mut().find(|b| b.label == label) {
+                bucket.invoices += 1;
+                bucket.outstanding_kobo += invoice.balance_kobo as u64;
+            }
+        }
+
+        Ok(AgingReport {
+            as_of,
+            currency,
+            outstanding_kobo: buckets.iter().map(|b| b.outstanding_kobo).sum(),
+            buckets,
+        })
+    }
+
+    /// Students who owe money, largest balance first. `min_balance_kobo`
+    /// applies to a student's total across the matching invoices.
+    pub async fn get_debtors_report(&self, school_id: Uuid, query: &BalanceQuery) -> Result<DebtorsReport, AppError> {
+        let currency = self.get_school(school_id).await?.currency;
+        let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+        let students = self.school_students(school_id).await;
+        let invoices = self.open_invoices(school_id, &students, query).await;
+
+        let mut debtors: Vec<Debtor> = Vec::new();
+        for invoice in &invoices {
+            let balance = invoice.balance_kobo as u64;
+            match debtors.iter_mut().find(|d| d.student_id == invoice.student_id) {
+                Some(debtor) => {
+                    debtor.invoices += 1;
+                    debtor.balance_kobo += balance;
+                    debtor.oldest_due_date = debtor.oldest_due_date.min(invoice.due_date);
+                }
+                None => {
+                    let student = students.get(&invoice.student_id);
+                    debtors.push(Debtor {
+                        student_id: invoice.student_id,
+                        admission_number: student.map(|s| s.admission_number.clone()).unwrap_or_default(),
+                        name: student
+                            .map(|s| format!("{} {}", s.first_name, s.last_name))
+                            .unwrap_or_default(),
+                        class_name: student.and_then(|s| s.class_name.clone()),
+                        guardian_phone: student.and_then(|s| s.profile.guardian_phone.clone()),
+                        invoices: 1,
+                        balance_kobo: balance,
+                        oldest_due_date: invoice.due_date,
+                        days_overdue: 0,
+                    });
+                }
+            }
+        }
+        for debtor in &mut debtors {
+            debtor.days_overdue = (as_of - debtor.oldest_due_date).num_days();
+        }
+        debtors.retain(|d| query.min_balance_kobo.is_none_or(|min| d.balance_kobo >= min));
+        debtors.sort_by(|a, b| b.balance_kobo.cmp(&a.balance_kobo).then(a.name.cmp(&b.name)));
+
+        Ok(DebtorsReport {
+            as_of,
+            currency,
+            outstanding_kobo: debtors.iter().map(|d| d.balance_kobo).sum(),
+            debtors,
+        })
+    }
+
+    async fn school_students(&self, school_id: Uuid) -> HashMap<Uuid, Student> {
+        let students = self.students.lock().await;
+        students
+            .values()
+            .filter(|s| s.school_id == school_id)
+            .map(|s| (s.id, s.clone()))
+            .collect()
+    }
+
+    async fn school_invoices(&self, school_id: Uuid) -> HashMap<Uuid, Invoice> {
+        let invoices = self.invoices.lock().await;
+        invoices
+            .values()
+            .filter(|i| i.school_id == school_id)
+            .map(|i| (i.id, i.clone()))
+            .collect()
+    }
+
+    // invoices with something still owed on them that match the filters
+    async fn open_invoices(
+        &self,
+        school_id: Uuid,
+        students: &HashMap<Uuid, Student>,
+        query: &BalanceQuery,
+    ) -> Vec<Invoice> {
+        let mut list: Vec<Invoice> = self
+            .school_invoices(school_id)
+            .await
+            .into_values()
+            .filter(|i| i.balance_kobo > 0)
+            .filter(|i| query.term.as_ref().is_none_or(|t| &i.term_code == t))
+            .filter(|i| in_class(students.get(&i.student_id), query.class_name.as_deref()))
+            .collect();
+        list.sort_by_key(|i| (i.due_date, i.created_at));
+        list
+    }
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::AppStore;
+
+/// What a login may do. The account a school registers with is its Owner;
+/// everyone else is added by the owner as staff.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum StaffRole {
+    #[default]
+    Owner,
+    Bursar,
+    Cashier, // records payments but can't approve them
+}
+
+#[derive(Clone, Serialize)]
+pub struct StaffUser {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub name: String,
+    pub username: String,
+    #[serde(skip_serializing)] // never expose password hash in responses
+    pub password_hash: String,
+    pub role: StaffRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateStaffRequest {
+    pub name: String,
+    pub username: String,
+    pub password: String,
+    pub role: StaffRole,
+}
+
+impl AppStore {
+    /// Usernames are shared by school and staff logins, so they must be
+    /// unique across both.
+    pub async fn username_taken(&self, username: &str) -> bool {
+        let school_taken = self.schools.lock().await.values().any(|s| s.username == username);
+        school_taken || self.staff.lock().await.values().any(|u| u.username == username)
+    }
+
+    pub async fn create_staff(&self, school_id: Uuid, req: CreateStaffRequest) -> Result<StaffUser, AppError> {
+        if req.role == StaffRole::Owner {
+            return Err(AppError::invalid("role", "A school has a single owner account"));
+        }
+        if req.username.trim().is_empty() || req.password.is_empty() {
+            return Err(AppError::invalid("username", "Username and password are required"));
+        }
+        if self.username_taken(&req.username).await {
+            return Err(AppError::Conflict("Username already taken".to_string()));
+        }
+
+        let password_hash = bcrypt::hash(&req.password, bcrypt::DEFAULT_COST)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let user = StaffUser {
+            id: Uuid::new_v4(),
+            school_id,
+            name: req.name,
+            username: req.username,
+            password_hash,
+            role: req.role,
+            created_at: Utc::now(),
+        };
+
+        self.staff.lock().await.insert(user.id.to_string(), user.clone());
+        Ok(user)
+    }
+
+    pub async fn get_staff(&self, school_id: Uuid) -> Result<Vec<StaffUser>, AppError> {
+        let staff = self.staff.lock().await;
+        let mut list: Vec<StaffUser> = staff
+            .values()
+            .filter(|u| u.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by_key(|u| u.created_at);
+        Ok(list)
+    }
+
+    pub async fn find_staff_by_username(&self, username: &str) -> Result<StaffUser, AppError> {
+        let staff = self.staff.lock().await;
+        staff
+            .values()
+            .find(|u| u.username == username)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+}
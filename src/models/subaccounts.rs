@@ -0,0 +1,206 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, ledger::TransactionStatus, money::Currency};
+
+/// Who pays Paystack's transaction fee on a split payment.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitBearer {
+    Account,    // the platform
+    Subaccount, // the school
+}
+
+/// A school's Paystack subaccount on the platform account: payments made
+/// through the platform settle straight into the school's bank account,
+/// less the platform's commission.
+#[derive(Clone, Serialize)]
+pub struct Subaccount {
+    pub school_id: Uuid,
+    pub subaccount_code: String, // Paystack's ACCT_... code
+    pub business_name: String,
+    pub settlement_bank: String, // Paystack bank code
+    pub account_number: String,
+    pub percentage_charge: f64, // the platform's share, in percent
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct SubaccountRequest {
+    pub business_name: String,
+    pub settlement_bank: String,
+    pub account_number: String,
+}
+
+/// How a payment was divided between the platform and the school, as
+/// requested from Paystack when the checkout was opened. Amounts are in the
+/// payment's currency.
+#[derive(Clone, Serialize)]
+pub struct PaymentSplit {
+    pub subaccount_code: String,
+    pub platform_fee_kobo: u64,
+    pub school_share_kobo: u64,
+    pub bearer: SplitBearer,
+}
+
+#[derive(Deserialize)]
+pub struct SettlementQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct SchoolSettlement {
+    pub school_id: Uuid,
+    pub subaccount_code: String,
+    pub currency: Currency, // every amount below is in its minor unit
+    pub payments: usize,
+    pub gross_kobo: u64,
+    pub platform_fee_kobo: u64,
+    pub school_share_kobo: u64,
+    pub refunded_kobo: u64,
+}
+
+#[derive(Serialize)]
+pub struct SettlementReport {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub schools: Vec<SchoolSettlement>,
+    pub totals: Vec<SettlementTotal>, // one per currency; amounts in different currencies never add up
+}
+
+#[derive(Serialize)]
+pub struct SettlementTotal {
+    pub currency: Currency, // every amount below is in its minor unit
+    pub gross_kobo: u64,
+    pub platform_fee_kobo: u64,
+    pub school_share_kobo: u64,
+}
+
+impl SubaccountRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.business_name.trim().is_empty() {
+            return Err(AppError::invalid("business_name", "Business name cannot be empty"));
+        }
+        if self.settlement_bank.trim().is_empty() {
+            return Err(AppError::invalid("settlement_bank", "Settlement bank code cannot be empty"));
+        }
+        // Nigerian (NUBAN) account numbers are ten digits
+        if self.account_number.len() != 10 || !self.account_number.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AppError::invalid("account_number", "Account number must be 10 digits"));
+        }
+        Ok(())
+    }
+}
+
+impl AppStore {
+    pub async fn save_subaccount(&self, subaccount: Subaccount) -> Subaccount {
+        self.subaccounts
+            .lock()
+            .await
+            .insert(subaccount.school_id.to_string(), subaccount.clone());
+        subaccount
+    }
+
+    pub async fn get_subaccount(&self, school_id: Uuid) -> Result<Subaccount, AppError> {
+        self.subaccounts
+            .lock()
+            .await
+            .get(&school_id.to_string())
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn set_payment_split(&self, reference: &str, split: PaymentSplit) -> Result<(), AppError> {
+        let mut payments = self.payments.lock().await;
+        let payment = payments
+            .values_mut()
+            .find(|p| p.reference == reference)
+            .ok_or(AppError::NotFound)?;
+        payment.split = Some(split);
+        Ok(())
+    }
+
+    /// Totals of settled split payments per school, by the day they were
+    /// paid. `school_id` limits the report to one school.
+    pub async fn get_settlement_report(
+        &self,
+        school_id: Option<Uuid>,
+        query: SettlementQuery,
+    ) -> Result<SettlementReport, AppError> {
+        let payments = self.payments.lock().await;
+        let mut schools: Vec<SchoolSettlement> = Vec::new();
+
+        for payment in payments.values() {
+            let Some(split) = &payment.split else {
+                continue;
+            };
+            let Some(paid_on) = payment.paid_at.map(|t| t.date_naive()) else {
+                continue;
+            };
+            let settled = matches!(payment.status, TransactionStatus::Successful | TransactionStatus::Refunded);
+            if !settled
+                || school_id.is_some_and(|id| id != payment.school_id)
+                || query.from.is_some_and(|from| paid_on < from)
+                || query.to.is_some_and(|to| paid_on > to)
+            {
+                continue;
+            }
+
+            let index = match schools.iter().position(|s| s.school_id == payment.school_id) {
+                Some(index) => index,
+                None => {
+                    schools.push(SchoolSettlement {
+                        school_id: payment.school_id,
+                        subaccount_code: split.subaccount_code.clone(),
+                        currency: payment.currency,
+                        payments: 0,
+                        gross_kobo: 0,
+                        platform_fee_kobo: 0,
+                        school_share_kobo: 0,
+                        refunded_kobo: 0,
+                    });
+                    schools.len() - 1
+                }
+            };
+            let line = &mut schools[index];
+            line.payments += 1;
+            line.gross_kobo += payment.amount_kobo;
+            line.platform_fee_kobo += split.platform_fee_kobo;
+            line.school_share_kobo += split.school_share_kobo;
+            line.refunded_kobo += payment.refunded_kobo;
+        }
+        schools.sort_by_key(|s| s.school_id);
+
+        let mut totals: Vec<SettlementTotal> = Vec::new();
+        for school in &schools {
+            let index = match totals.iter().position(|t| t.currency == school.currency) {
+                Some(index) => index,
+                None => {
+                    totals.push(SettlementTotal {
+                        currency: school.currency,
+                        gross_kobo: 0,
+                        platform_fee_kobo: 0,
+                        school_share_kobo: 0,
+                    });
+                    totals.len() - 1
+                }
+            };
+            let total = &mut totals[index];
+            total.gross_kobo += school.gross_kobo;
+            total.platform_fee_kobo += school.platform_fee_kobo;
+            total.school_share_kobo += school.school_share_kobo;
+        }
+
+        Ok(SettlementReport {
+            from: query.from,
+            to: query.to,
+            schools,
+            totals,
+        })
+    }
+}
@@ -0,0 +1,104 @@
+//! Builders for model tests: a school with a term, a fee schedule and
+//! billed students, set up through the same store methods the handlers use.
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use super::{
+    AppStore, CreateStudentRequest, School, SchoolBranding, Student,
+    academics::CreateTermRequest,
+    fees::{FeeAmount, FeeCategory, FeeItem, FeeItemRequest},
+    ledger::{CreateInvoiceRequest, Invoice, Payment, PaymentMethod},
+    money::Currency,
+    payment_settings::{PaymentGateway, PaymentProviderKind},
+};
+
+pub const TERM: &str = "2026-T1";
+
+pub fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// A school with one term and no fees yet. It is inserted directly, as
+/// hashing a password would slow every test down.
+pub async fn school(store: &AppStore) -> Uuid {
+    let school = School {
+        id: Uuid::new_v4(),
+        name: "Green Hill School".to_string(),
+        username: format!("school-{}", Uuid::new_v4()),
+        password_hash: String::new(),
+        branding: SchoolBranding::default(),
+        currency: Currency::Ngn,
+    };
+    store.schools.lock().await.insert(school.id.to_string(), school.clone());
+
+    let term = CreateTermRequest {
+        code: TERM.to_string(),
+        name: "First Term 2026".to_string(),
+        starts_on: date(2026, 9, 7),
+        ends_on: date(2026, 12, 11),
+    };
+    store.create_term(school.id, term).await.unwrap();
+    school.id
+}
+
+pub async fn fee_item(store: &AppStore, school_id: Uuid, name: &str, amount_kobo: u64) -> FeeItem {
+    let req = FeeItemRequest {
+        name: name.to_string(),
+        category: FeeCategory::Tuition,
+        mandatory: true,
+        amounts: vec![FeeAmount { grade_level: None, term_code: None, amount_kobo }],
+    };
+    store.create_fee_item(school_id, req).await.unwrap()
+}
+
+pub async fn student(store: &AppStore, school_id: Uuid, first_name: &str) -> Student {
+    let req = CreateStudentRequest {
+        first_name: first_name.to_string(),
+        last_name: "Obi".to_string(),
+        email: format!("{}@example.com", first_name.to_lowercase()),
+        department: "Science".to_string(),
+        class_name: Some("JSS1A".to_string()),
+        profile: Default::default(),
+        custom_fields: Default::default(),
+    };
+    store.create_student(school_id, "Green Hill School".to_string(), req).await.unwrap()
+}
+
+pub async fn invoice(store: &AppStore, school_id: Uuid, student_id: Uuid, due_date: NaiveDate) -> Invoice {
+    let req = CreateInvoiceRequest { term: TERM.to_string(), due_date: Some(due_date) };
+    store.create_invoice(school_id, student_id, "bursar", req).await.unwrap()
+}
+
+/// A store holding one student billed `amount_kobo` for the term.
+pub async fn billed_student(amount_kobo: u64, due_date: NaiveDate) -> (AppStore, Invoice) {
+    let store = AppStore::new();
+    let school_id = school(&store).await;
+    fee_item(&store, school_id, "Tuition", amount_kobo).await;
+    let student = student(&store, school_id, "Ada").await;
+    let invoice = invoice(&store, school_id, student.id, due_date).await;
+    (store, invoice)
+}
+
+/// An online payment waiting on `gateway`, as `start_checkout` leaves it.
+pub async fn pending_payment(
+    store: &AppStore,
+    invoice: &Invoice,
+    reference: &str,
+    amount_kobo: u64,
+    gateway: PaymentGateway,
+) -> Payment {
+    store
+        .create_pending_payment(invoice, reference.to_string(), amount_kobo, PaymentMethod::Online)
+        .await
+        .unwrap();
+    store.set_payment_gateway(reference, gateway).await.unwrap();
+    store.find_payment_by_reference(invoice.school_id, reference).await.unwrap()
+}
+
+/// An online payment of `amount_kobo` that went through the platform and succeeded.
+pub async fn paid_payment(store: &AppStore, invoice: &Invoice, reference: &str, amount_kobo: u64) -> Payment {
+    let gateway = PaymentGateway { provider: PaymentProviderKind::Paystack, platform: true };
+    pending_payment(store, invoice, reference, amount_kobo, gateway).await;
+    store.complete_payment(reference, amount_kobo).await.unwrap()
+}
@@ -0,0 +1,257 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::AppStore;
+
+// ---- Teacher ----
+
+#[derive(Clone, Serialize)]
+pub struct Teacher {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateTeacherRequest {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+}
+
+// ---- Timetable ----
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub fn to_chrono(self) -> chrono::Weekday {
+        match self {
+            Weekday::Monday => chrono::Weekday::Mon,
+            Weekday::Tuesday => chrono::Weekday::Tue,
+            Weekday::Wednesday => chrono::Weekday::Wed,
+            Weekday::Thursday => chrono::Weekday::Thu,
+            Weekday::Friday => chrono::Weekday::Fri,
+            Weekday::Saturday => chrono::Weekday::Sat,
+            Weekday::Sunday => chrono::Weekday::Sun,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct TimetableSlot {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub class_name: String,
+    pub subject_id: Uuid,
+    pub teacher_id: Uuid,
+    pub room: String,
+    pub weekday: Weekday,
+    pub period: u8,
+    pub starts_at: NaiveTime,
+    pub ends_at: NaiveTime,
+}
+
+#[derive(Deserialize)]
+pub struct CreateTimetableSlotRequest {
+    pub class_name: String,
+    pub subject_id: Uuid,
+    pub teacher_id: Uuid,
+    pub room: String,
+    pub weekday: Weekday,
+    pub period: u8,
+    pub starts_at: NaiveTime,
+    pub ends_at: NaiveTime,
+}
+
+/// A slot with subject and teacher names filled in, as returned to clients.
+#[derive(Clone, Serialize)]
+pub struct TimetableEntry {
+    #[serde(flatten)]
+    pub slot: TimetableSlot,
+    pub subject_name: String,
+    pub teacher_name: String,
+}
+
+impl TimetableSlot {
+    fn overlaps(&self, weekday: Weekday, starts_at: NaiveTime, ends_at: NaiveTime) -> bool {
+        self.weekday == weekday && self.starts_at < ends_at && starts_at < self.ends_at
+    }
+}
+
+impl AppStore {
+    // -- Teacher methods --
+
+    pub async fn create_teacher(&self, school_id: Uuid, req: CreateTeacherRequest) -> Result<Teacher, AppError> {
+        let mut teachers = self.teachers.lock().await;
+        let taken = teachers
+            .values()
+            .any(|t| t.school_id == school_id && t.email.eq_ignore_ascii_case(&req.email));
+        if taken {
+            return Err(AppError::Conflict("A teacher with this email already exists".to_string()));
+        }
+
+        let teacher = Teacher {
+            id: Uuid::new_v4(),
+            school_id,
+            first_name: req.first_name,
+            last_name: req.last_name,
+            email: req.email,
+        };
+
+        teachers.insert(teacher.id.to_string(), teacher.clone());
+        Ok(teacher)
+    }
+
+    pub async fn get_teachers(&self, school_id: Uuid) -> Result<Vec<Teacher>, AppError> {
+        let teachers = self.teachers.lock().await;
+        let mut list: Vec<Teacher> = teachers
+            .values()
+            .filter(|t| t.school_id == school_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| (&a.last_name, &a.first_name).cmp(&(&b.last_name, &b.first_name)));
+        Ok(list)
+    }
+
+    pub async fn get_teacher(&self, school_id: Uuid, id: Uuid) -> Result<Teacher, AppError> {
+        let teachers = self.teachers.lock().await;
+        teachers
+            .values()
+            .find(|t| t.id == id && t.school_id == school_id)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    // -- Timetable methods --
+
+    pub async fn create_timetable_slot(
+        &self,
+        school_id: Uuid,
+        req: CreateTimetableSlotRequest,
+    ) -> Result<TimetableSlot, AppError> {
+        if req.ends_at <= req.starts_at {
+            return Err(AppError::invalid("ends_at", "Slot must end after it starts"));
+        }
+        if req.room.trim().is_empty() {
+            return Err(AppError::invalid("room", "Room cannot be empty"));
+        }
+        self.get_subject(school_id, req.subject_id).await?;
+        self.get_teacher(school_id, req.teacher_id).await?;
+
+        let mut slots = self.timetable_slots.lock().await;
+        for existing in slots.values().filter(|s| s.school_id == school_id) {
+            if !existing.overlaps(req.weekday, req.starts_at, req.ends_at) {
+                continue;
+            }
+            if existing.teacher_id == req.teacher_id {
+                return Err(AppError::Conflict(format!(
+                    "Teacher is already teaching {} on {:?} {}-{}",
+                    existing.class_name, existing.weekday, existing.starts_at, existing.ends_at
+                )));
+            }
+            if existing.room.eq_ignore_ascii_case(req.room.trim()) {
+                return Err(AppError::Conflict(format!(
+                    "Room {} is already booked by {} on {:?} {}-{}",
+                    existing.room, existing.class_name, existing.weekday, existing.starts_at, existing.ends_at
+                )));
+            }
+            if existing.class_name == req.class_name {
+                return Err(AppError::Conflict(format!(
+                    "Class {} already has a lesson on {:?} {}-{}",
+                    existing.class_name, existing.weekday, existing.starts_at, existing.ends_at
+                )));
+            }
+        }
+
+        let slot = TimetableSlot {
+            id: Uuid::new_v4(),
+            school_id,
+            class_name: req.class_name,
+            subject_id: req.subject_id,
+            teacher_id: req.teacher_id,
+            room: req.room.trim().to_string(),
+            weekday: req.weekday,
+            period: req.period,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+        };
+
+        slots.insert(slot.id.to_string(), slot.clone());
+        Ok(slot)
+    }
+
+    pub async fn delete_timetable_slot(&self, school_id: Uuid, id: Uuid) -> Result<(), AppError> {
+        let mut slots = self.timetable_slots.lock().await;
+        match slots.get(&id.to_string()) {
+            Some(slot) if slot.school_id == school_id => {
+                slots.remove(&id.to_string());
+                Ok(())
+            }
+            _ => Err(AppError::NotFound),
+        }
+    }
+
+    async fn timetable_entries(
+        &self,
+        school_id: Uuid,
+        filter: impl Fn(&TimetableSlot) -> bool,
+    ) -> Result<Vec<TimetableEntry>, AppError> {
+        let subjects = self.get_subjects(school_id).await?;
+        let teachers = self.get_teachers(school_id).await?;
+
+        let slots = self.timetable_slots.lock().await;
+        let mut entries: Vec<TimetableEntry> = slots
+            .values()
+            .filter(|s| s.school_id == school_id && filter(s))
+            .map(|slot| TimetableEntry {
+                subject_name: subjects
+                    .iter()
+                    .find(|s| s.id == slot.subject_id)
+                    .map(|s| s.name.clone())
+                    .unwrap_or_default(),
+                teacher_name: teachers
+                    .iter()
+                    .find(|t| t.id == slot.teacher_id)
+                    .map(|t| format!("{} {}", t.first_name, t.last_name))
+                    .unwrap_or_default(),
+                slot: slot.clone(),
+            })
+            .collect();
+
+        entries.sort_by_key(|e| (e.slot.weekday, e.slot.starts_at));
+        Ok(entries)
+    }
+
+    pub async fn get_class_timetable(
+        &self,
+        school_id: Uuid,
+        class_name: &str,
+    ) -> Result<Vec<TimetableEntry>, AppError> {
+        self.timetable_entries(school_id, |s| s.class_name == class_name)
+            .await
+    }
+
+    pub async fn get_teacher_timetable(
+        &self,
+        school_id: Uuid,
+        teacher_id: Uuid,
+    ) -> Result<Vec<TimetableEntry>, AppError> {
+        self.get_teacher(school_id, teacher_id).await?;
+        self.timetable_entries(school_id, |s| s.teacher_id == teacher_id)
+            .await
+    }
+}
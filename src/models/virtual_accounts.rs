@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{errors::AppError, logger::AppLogger};
+
+use super::{
+    AppStore,
+    ledger::{Invoice, Payment, PaymentMethod},
+};
+
+/// A Paystack dedicated bank account that parents transfer fees into. It
+/// belongs to one student, or to a guardian and covers all their children.
+#[derive(Clone, Serialize)]
+pub struct VirtualAccount {
+    pub id: Uuid,
+    pub school_id: Uuid,
+    pub student_id: Option<Uuid>,
+    pub guardian_id: Option<Uuid>,
+    pub customer_code: String, // the Paystack customer the account is issued to
+    pub account_number: String,
+    pub account_name: String,
+    pub bank_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How an incoming transfer was applied.
+#[derive(Serialize)]
+pub struct TransferOutcome {
+    pub payments: Vec<Payment>,
+    pub overpaid_kobo: u64, // left as credit on the newest invoice
+}
+
+impl AppStore {
+    pub async fn save_virtual_account(&self, account: VirtualAccount) -> VirtualAccount {
+        self.virtual_accounts
+            .lock()
+            .await
+            .insert(account.id.to_string(), account.clone());
+        account
+    }
+
+    pub async fn find_virtual_account(&self, account_number: &str) -> Result<VirtualAccount, AppError> {
+        let accounts = self.virtual_accounts.lock().await;
+        accounts
+            .values()
+            .find(|a| a.account_number == account_number)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn get_guardian_virtual_account(&self, school_id: Uuid, guardian_id: Uuid) -> Option<VirtualAccount> {
+        let accounts = self.virtual_accounts.lock().await;
+        accounts
+            .values()
+            .find(|a| a.school_id == school_id && a.guardian_id == Some(guardian_id))
+            .cloned()
+    }
+
+    /// The account a student's fees should be sent to: their own, or else
+    /// one held by any of their guardians.
+    pub async fn get_student_virtual_account(&self, school_id: Uuid, student_id: Uuid) -> Result<VirtualAccount, AppError> {
+        self.get_student(school_id, student_id).await?;
+
+        let guardian_ids: Vec<Uuid> = {
+            let guardians = self.guardians.lock().await;
+            guardians
+                .values()
+                .filter(|g| g.school_id == school_id && g.student_ids.contains(&student_id))
+                .map(|g| g.id)
+                .collect()
+        };
+
+        let accounts = self.virtual_accounts.lock().await;
+        let own = accounts
+            .values()
+            .find(|a| a.school_id == school_id && a.student_id == Some(student_id));
+        let guardians = || {
+            accounts
+                .values()
+                .find(|a| a.school_id == school_id && a.guardian_id.is_some_and(|g| guardian_ids.contains(&g)))
+        };
+        own.or_else(guardians).cloned().ok_or(AppError::NotFound)
+    }
+
+    /// Applies a transfer into a virtual account to the account holder's
+    /// open invoices, oldest due first, as one payment per invoice. Less than
+    /// is owed leaves the invoice part paid; anything beyond what is owed is
+    /// kept as credit on the newest invoice.
+    pub async fn receive_transfer(
+        &self,
+        account: &VirtualAccount,
+        reference: &str,
+        amount_kobo: u64,
+    ) -> Result<TransferOutcome, AppError> {
+        let already_applied = self
+            .payments
+            .lock()
+            .await
+            .values()
+            .any(|p| p.reference == reference);
+        if already_applied {
+            return Err(AppError::Conflict("Transfer has already been applied".to_string()));
+        }
+
+        let student_ids: Vec<Uuid> = match (account.student_id, account.guardian_id) {
+            (Some(student_id), _) => vec![student_id],
+            (None, Some(guardian_id)) => {
+                let guardians = self.guardians.lock().await;
+                guardians
+                    .get(&guardian_id.to_string())
+                    .map(|g| g.student_ids.clone())
+                    .unwrap_or_default()
+            }
+            (None, None) => Vec::new(),
+        };
+
+        let mut invoices: Vec<Invoice> = {
+            let invoices = self.invoices.lock().await;
+            invoices
+                .values()
+                .filter(|i| i.school_id == account.school_id && student_ids.contains(&i.student_id))
+                .cloned()
+                .collect()
+        };
+        invoices.sort_by_key(|i| (i.due_date, i.created_at));
+        let newest = invoices
+            .iter()
+            .max_by_key(|i| i.created_at)
+            .cloned()
+            .ok_or_else(|| AppError::invalid("account_number", "The account holder has no invoice to pay"))?;
+
+        // (invoice, amount) pairs; the excess tops up the last one
+        let mut allocations: Vec<(Invoice, u64)> = Vec::new();
+        let mut remaining = amount_kobo;
+        for invoice in invoices.into_iter().filter(|i| i.balance_kobo > 0) {
+            if remaining == 0 {
+                break;
+            }
+            let take = remaining.min(invoice.balance_kobo as u64);
+            remaining -= take;
+            allocations.push((invoice, take));
+        }
+        let overpaid_kobo = remaining;
+        match allocations.last_mut() {
+            Some((invoice, amount)) if invoice.id == newest.id => *amount += overpaid_kobo,
+            _ if overpaid_kobo > 0 => allocations.push((newest, overpaid_kobo)),
+            _ => {}
+        }
+        if overpaid_kobo > 0 {
+            AppLogger::warn(&format!(
+                "Transfer {} overpaid by {} kobo; kept as credit",
+                reference, overpaid_kobo
+            ));
+        }
+
+        let mut payments = Vec::new();
+        for (index, (invoice, amount)) in allocations.iter().enumerate() {
+            let payment_reference = match index {
+                0 => reference.to_string(),
+                n => format!("{}-{}", reference, n + 1),
+            };
+            self.create_pending_payment(invoice, payment_reference.clone(), *amount, PaymentMethod::BankTransfer)
+                .await?;
+            payments.push(self.complete_payment(&payment_reference, *amount).await?);
+        }
+
+        Ok(TransferOutcome { payments, overpaid_kobo })
+    }
+}
@@ -0,0 +1,455 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+use super::{AppStore, ledger::Payment, payment_settings::PaymentProviderKind};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum WebhookEventStatus {
+    Received,
+    Processed,
+    Ignored, // event types we don't act on
+    Failed,
+}
+
+/// What an event means for us, whichever provider sent it.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum WebhookEventKind {
+    ChargeSuccess,
+    ChargeFailed,
+    RefundProcessed,
+    RefundFailed,
+    TransferReceived, // a bank transfer into a dedicated virtual account
+    Other,
+}
+
+/// A provider's webhook payload reduced to the fields we act on.
+pub struct ParsedWebhookEvent {
+    pub event_key: String, // unique per provider, used to spot redeliveries
+    pub event_type: String, // the provider's own name for the event
+    pub kind: WebhookEventKind,
+    pub reference: Option<String>,
+    pub refund_id: Option<String>, // the provider's id, for refund events
+    pub amount_kobo: Option<u64>,
+    pub currency: Option<String>,
+    pub account_number: Option<String>, // the virtual account credited, for transfers
+    pub payload: Value,
+}
+
+/// A webhook delivery as received, kept so that retries from the gateway are
+/// recognised and failed events can be looked at and replayed.
+#[derive(Clone, Serialize)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    pub provider: PaymentProviderKind,
+    pub event_key: String,
+    pub event_type: String,
+    pub kind: WebhookEventKind,
+    pub reference: Option<String>,
+    pub refund_id: Option<String>,
+    pub amount_kobo: Option<u64>,
+    pub currency: Option<String>,
+    pub account_number: Option<String>,
+    pub school_id: Option<Uuid>, // the school whose endpoint received it, or whose payment it matched
+    pub platform: bool, // received on the platform's endpoint rather than a school's
+    pub payload: Value,
+    pub status: WebhookEventStatus,
+    pub error: Option<String>,
+    pub attempts: u32,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookEventQuery {
+    pub status: Option<WebhookEventStatus>,
+}
+
+impl AppStore {
+    /// Stores a delivery unless the same event was already received. The flag
+    /// is false for duplicates, which must not be processed again.
+    pub async fn record_webhook_event(
+        &self,
+        provider: PaymentProviderKind,
+        school_id: Option<Uuid>,
+        parsed: ParsedWebhookEvent,
+    ) -> (WebhookEvent, bool) {
+        let mut events = self.webhook_events.lock().await;
+        if let Some(existing) = events
+            .values()
+            .find(|e| e.provider == provider && e.event_key == parsed.event_key)
+        {
+            return (existing.clone(), false);
+        }
+
+        let event = WebhookEvent {
+            id: Uuid::new_v4(),
+            provider,
+            event_key: parsed.event_key,
+            event_type: parsed.event_type,
+            kind: parsed.kind,
+            reference: parsed.reference,
+            refund_id: parsed.refund_id,
+            amount_kobo: parsed.amount_kobo,
+            currency: parsed.currency,
+            account_number: parsed.account_number,
+            school_id,
+            platform: school_id.is_none(),
+            payload: parsed.payload,
+            status: WebhookEventStatus::Received,
+            error: None,
+            attempts: 0,
+            received_at: Utc::now(),
+            processed_at: None,
+        };
+        events.insert(event.id.to_string(), event.clone());
+        (event, true)
+    }
+
+    /// Applies a stored event and records the outcome on it. Validation
+    /// failures leave the event Failed with the reason, ready for replay.
+    pub async fn process_webhook_event(&self, id: Uuid) -> Result<WebhookEvent, AppError> {
+        let event = {
+            let mut events = self.webhook_events.lock().await;
+            let event = events.get_mut(&id.to_string()).ok_or(AppError::NotFound)?;
+            if event.status == WebhookEventStatus::Processed {
+                return Err(AppError::Conflict("Event has already been processed".to_string()));
+            }
+            event.attempts += 1;
+            event.clone()
+        };
+
+        let outcome = self.apply_webhook_event(&event).await;
+
+        let mut events = self.webhook_events.lock().await;
+        let stored = events.get_mut(&id.to_string()).ok_or(AppError::NotFound)?;
+        match outcome {
+            Ok(status) => {
+                stored.status = status;
+                stored.error = None;
+                stored.processed_at = Some(Utc::now());
+            }
+            Err(e) => {
+                stored.status = WebhookEventStatus::Failed;
+                stored.error = Some(e.to_string());
+            }
+        }
+        Ok(stored.clone())
+    }
+
+    async fn apply_webhook_event(&self, event: &WebhookEvent) -> Result<WebhookEventStatus, AppError> {
+        match event.kind {
+            WebhookEventKind::Other => return Ok(WebhookEventStatus::Ignored),
+            WebhookEventKind::RefundProcessed | WebhookEventKind::RefundFailed => {
+                return self.apply_refund_event(event).await;
+            }
+            WebhookEventKind::TransferReceived => return self.apply_transfer_event(event).await,
+            WebhookEventKind::ChargeSuccess | WebhookEventKind::ChargeFailed => {}
+        }
+
+        let reference = event
+            .reference
+            .as_deref()
+            .ok_or_else(|| AppError::invalid("reference", "Event has no transaction reference"))?;
+
+        // only references we issued, for the school the event was sent to, can settle anything
+        let payment = {
+            let payments = self.payments.lock().await;
+            payments
+                .values()
+                .find(|p| p.reference == reference)
+                .cloned()
+                .ok_or_else(|| AppError::invalid("reference", "No payment was started with this reference"))?
+        };
+        if event.school_id.is_some_and(|id| id != payment.school_id) {
+            return Err(AppError::invalid("reference", "Reference belongs to another school"));
+        }
+        self.check_event_gateway(event, &payment).await?;
+        self.record_event_school(event.id, payment.school_id).await;
+
+        if event.kind == WebhookEventKind::ChargeFailed {
+            self.fail_payment(reference).await?;
+            return Ok(WebhookEventStatus::Processed);
+        }
+
+        let amount_kobo = event
+            .amount_kobo
+            .ok_or_else(|| AppError::invalid("amount", "Event has no amount"))?;
+        payment.currency.expect(event.currency.as_deref())?;
+        if amount_kobo != payment.amount_kobo {
+            return Err(AppError::invalid(
+                "amount",
+                &format!("Charged {} kobo but the payment was for {} kobo", amount_kobo, payment.amount_kobo),
+            ));
+        }
+
+        self.complete_payment(reference, amount_kobo).await?;
+        Ok(WebhookEventStatus::Processed)
+    }
+
+    async fn apply_refund_event(&self, event: &WebhookEvent) -> Result<WebhookEventStatus, AppError> {
+        let refund = self
+            .find_refund_for_event(event.refund_id.as_deref(), event.reference.as_deref())
+            .await
+            .map_err(|_| AppError::invalid("refund", "No pending refund matches this event"))?;
+        if event.school_id.is_some_and(|id| id != refund.school_id) {
+            return Err(AppError::invalid("reference", "Reference belongs to another school"));
+        }
+        let payment = self
+            .payments
+            .lock()
+            .await
+            .get(&refund.payment_id.to_string())
+            .cloned()
+            .ok_or(AppError::NotFound)?;
+        self.check_event_gateway(event, &payment).await?;
+        self.record_event_school(event.id, refund.school_id).await;
+
+        if event.kind == WebhookEventKind::RefundProcessed {
+            self.complete_refund(refund.id).await?;
+        } else {
+            self.fail_refund(refund.id, "The provider could not process the refund").await?;
+        }
+        Ok(WebhookEventStatus::Processed)
+    }
+
+    async fn apply_transfer_event(&self, event: &WebhookEvent) -> Result<WebhookEventStatus, AppError> {
+        let account_number = event
+            .account_number
+            .as_deref()
+            .ok_or_else(|| AppError::invalid("account_number", "Event has no receiving account"))?;
+        let account = self
+            .find_virtual_account(account_number)
+            .await
+            .map_err(|_| AppError::invalid("account_number", "No virtual account matches this transfer"))?;
+        if event.school_id.is_some_and(|id| id != account.school_id) {
+            return Err(AppError::invalid("account_number", "Account belongs to another school"));
+        }
+        self.record_event_school(event.id, account.school_id).await;
+
+        let reference = event
+            .reference
+            .as_deref()
+            .ok_or_else(|| AppError::invalid("reference", "Event has no transaction reference"))?;
+        let amount_kobo = event
+            .amount_kobo
+            .ok_or_else(|| AppError::invalid("amount", "Event has no amount"))?;
+        // a school's invoices are all in its currency, which is fixed once it bills
+        let school = self.get_school(account.school_id).await?;
+        school.currency.expect(event.currency.as_deref())?;
+
+        self.receive_transfer(&account, reference, amount_kobo).await?;
+        Ok(WebhookEventStatus::Processed)
+    }
+
+    // only the gateway account that took a payment can settle or refund it; a
+    // school's own secret must not move money that went through the platform
+    async fn check_event_gateway(&self, event: &WebhookEvent, payment: &Payment) -> Result<(), AppError> {
+        let gateway = self.payment_gateway(payment).await;
+        if gateway.provider != event.provider || gateway.platform != event.platform {
+            return Err(AppError::invalid("reference", "The payment went through another gateway account"));
+        }
+        Ok(())
+    }
+
+    // lets a school see events for its own payments that came in on the shared endpoint
+    async fn record_event_school(&self, id: Uuid, school_id: Uuid) {
+        if let Some(event) = self.webhook_events.lock().await.get_mut(&id.to_string()) {
+            event.school_id = Some(school_id);
+        }
+    }
+
+    /// A school's events, or with no school the ones matched to none, which
+    /// only the platform operator can see.
+    pub async fn get_webhook_events(
+        &self,
+        school_id: Option<Uuid>,
+        query: WebhookEventQuery,
+    ) -> Result<Vec<WebhookEvent>, AppError> {
+        let events = self.webhook_events.lock().await;
+        let mut list: Vec<WebhookEvent> = events
+            .values()
+            .filter(|e| e.school_id == school_id)
+            .filter(|e| query.status.is_none_or(|s| e.status == s))
+            .cloned()
+            .collect();
+        list.sort_by_key(|e| e.received_at);
+        Ok(list)
+    }
+
+    pub async fn replay_webhook_event(&self, school_id: Option<Uuid>, id: Uuid) -> Result<WebhookEvent, AppError> {
+        let owned = self
+            .webhook_events
+            .lock()
+            .await
+            .get(&id.to_string())
+            .is_some_and(|e| e.school_id == school_id);
+        if !owned {
+            return Err(AppError::NotFound);
+        }
+        self.process_webhook_event(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::{
+        ledger::{PaymentMethod, TransactionStatus},
+        payment_settings::PaymentGateway,
+        refunds::{CreateRefundRequest, RefundStatus},
+        testing::{billed_student, date, pending_payment},
+    };
+
+    const PLATFORM: PaymentGateway = PaymentGateway { provider: PaymentProviderKind::Paystack, platform: true };
+
+    fn charge(key: &str, kind: WebhookEventKind, reference: &str, amount_kobo: u64, currency: &str) -> ParsedWebhookEvent {
+        ParsedWebhookEvent {
+            event_key: key.to_string(),
+            event_type: "charge".to_string(),
+            kind,
+            reference: Some(reference.to_string()),
+            refund_id: None,
+            amount_kobo: Some(amount_kobo),
+            currency: Some(currency.to_string()),
+            account_number: None,
+            payload: json!({}),
+        }
+    }
+
+    async fn receive(
+        store: &AppStore,
+        provider: PaymentProviderKind,
+        school_id: Option<Uuid>,
+        parsed: ParsedWebhookEvent,
+    ) -> WebhookEvent {
+        let (event, _) = store.record_webhook_event(provider, school_id, parsed).await;
+        store.process_webhook_event(event.id).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_school_endpoint_cannot_settle_a_platform_payment() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 12, 1)).await;
+        pending_payment(&store, &invoice, "sch-1", 5_000_000, PLATFORM).await;
+
+        for provider in [PaymentProviderKind::Stripe, PaymentProviderKind::Paystack] {
+            let parsed = charge(&format!("{:?}", provider), WebhookEventKind::ChargeSuccess, "sch-1", 5_000_000, "NGN");
+            let event = receive(&store, provider, Some(invoice.school_id), parsed).await;
+            assert_eq!(event.status, WebhookEventStatus::Failed);
+        }
+        let payment = store.find_payment_by_reference(invoice.school_id, "sch-1").await.unwrap();
+        assert_eq!(payment.status, TransactionStatus::Pending);
+        assert_eq!(store.get_invoice(invoice.school_id, invoice.id).await.unwrap().balance_kobo, 5_000_000);
+
+        // the platform's own notification still goes through
+        let parsed = charge("platform", WebhookEventKind::ChargeSuccess, "sch-1", 5_000_000, "NGN");
+        let event = receive(&store, PaymentProviderKind::Paystack, None, parsed).await;
+        assert_eq!(event.status, WebhookEventStatus::Processed);
+    }
+
+    #[tokio::test]
+    async fn a_school_endpoint_cannot_complete_a_refund_of_a_platform_payment() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 12, 1)).await;
+        let payment = pending_payment(&store, &invoice, "sch-1", 5_000_000, PLATFORM).await;
+        store.complete_payment("sch-1", 5_000_000).await.unwrap();
+        let req = CreateRefundRequest { amount_kobo: None, reason: "Left the school".to_string() };
+        let (refund, _) = store.request_refund(invoice.school_id, payment.id, "owner", req).await.unwrap();
+
+        let parsed = charge("refund", WebhookEventKind::RefundProcessed, "sch-1", 5_000_000, "NGN");
+        let event = receive(&store, PaymentProviderKind::Flutterwave, Some(invoice.school_id), parsed).await;
+
+        assert_eq!(event.status, WebhookEventStatus::Failed);
+        let refunds = store.get_payment_refunds(invoice.school_id, payment.id).await.unwrap();
+        assert_eq!(refunds[0].id, refund.id);
+        assert_eq!(refunds[0].status, RefundStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn payments_without_a_recorded_gateway_use_the_schools_current_one() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 12, 1)).await;
+        store
+            .create_pending_payment(&invoice, "sch-1".to_string(), 5_000_000, PaymentMethod::Online)
+            .await
+            .unwrap();
+
+        // no settings, so only the platform account can settle it
+        let parsed = charge("school", WebhookEventKind::ChargeSuccess, "sch-1", 5_000_000, "NGN");
+        let event = receive(&store, PaymentProviderKind::Paystack, Some(invoice.school_id), parsed).await;
+        assert_eq!(event.status, WebhookEventStatus::Failed);
+
+        let parsed = charge("platform", WebhookEventKind::ChargeSuccess, "sch-1", 5_000_000, "NGN");
+        let event = receive(&store, PaymentProviderKind::Paystack, None, parsed).await;
+        assert_eq!(event.status, WebhookEventStatus::Processed);
+    }
+
+    #[tokio::test]
+    async fn a_redelivered_event_is_processed_once() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 12, 1)).await;
+        pending_payment(&store, &invoice, "sch-1", 2_000_000, PLATFORM).await;
+
+        let parsed = || charge("charge.success:1", WebhookEventKind::ChargeSuccess, "sch-1", 2_000_000, "NGN");
+        let (first, is_new) = store.record_webhook_event(PaymentProviderKind::Paystack, None, parsed()).await;
+        assert!(is_new);
+        store.process_webhook_event(first.id).await.unwrap();
+
+        let (again, is_new) = store.record_webhook_event(PaymentProviderKind::Paystack, None, parsed()).await;
+        assert!(!is_new);
+        assert_eq!(again.id, first.id);
+        assert!(store.process_webhook_event(first.id).await.is_err());
+
+        // the same key from another provider is another event
+        let (other, is_new) = store.record_webhook_event(PaymentProviderKind::Stripe, None, parsed()).await;
+        assert!(is_new);
+        assert_ne!(other.id, first.id);
+
+        assert_eq!(store.webhook_events.lock().await.len(), 2);
+        assert_eq!(store.get_invoice(invoice.school_id, invoice.id).await.unwrap().balance_kobo, 3_000_000);
+    }
+
+    #[tokio::test]
+    async fn a_charge_for_the_wrong_amount_or_currency_fails() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 12, 1)).await;
+        pending_payment(&store, &invoice, "sch-1", 2_000_000, PLATFORM).await;
+
+        let short = charge("short", WebhookEventKind::ChargeSuccess, "sch-1", 1_999_999, "NGN");
+        let event = receive(&store, PaymentProviderKind::Paystack, None, short).await;
+        assert_eq!(event.status, WebhookEventStatus::Failed);
+        assert!(event.error.is_some());
+
+        let dollars = charge("dollars", WebhookEventKind::ChargeSuccess, "sch-1", 2_000_000, "USD");
+        let event = receive(&store, PaymentProviderKind::Paystack, None, dollars).await;
+        assert_eq!(event.status, WebhookEventStatus::Failed);
+
+        let payment = store.find_payment_by_reference(invoice.school_id, "sch-1").await.unwrap();
+        assert_eq!(payment.status, TransactionStatus::Pending);
+        assert_eq!(store.get_invoice(invoice.school_id, invoice.id).await.unwrap().balance_kobo, 5_000_000);
+    }
+
+    #[tokio::test]
+    async fn a_failed_event_can_be_replayed_once_the_cause_is_fixed() {
+        let (store, invoice) = billed_student(5_000_000, date(2026, 12, 1)).await;
+
+        // the notification beat the payment record
+        let parsed = charge("early", WebhookEventKind::ChargeSuccess, "sch-1", 2_000_000, "NGN");
+        let event = receive(&store, PaymentProviderKind::Paystack, None, parsed).await;
+        assert_eq!(event.status, WebhookEventStatus::Failed);
+        assert_eq!(store.get_webhook_events(None, WebhookEventQuery { status: None }).await.unwrap().len(), 1);
+
+        pending_payment(&store, &invoice, "sch-1", 2_000_000, PLATFORM).await;
+        let replayed = store.replay_webhook_event(None, event.id).await.unwrap();
+        assert_eq!(replayed.status, WebhookEventStatus::Processed);
+        assert_eq!(replayed.attempts, 2);
+        assert_eq!(replayed.error, None);
+
+        // now the school's, and done with
+        let events = store.get_webhook_events(Some(invoice.school_id), WebhookEventQuery { status: None }).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(store.replay_webhook_event(Some(invoice.school_id), event.id).await.is_err());
+        assert_eq!(store.get_invoice(invoice.school_id, invoice.id).await.unwrap().balance_kobo, 3_000_000);
+    }
+}
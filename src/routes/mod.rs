@@ -1,13 +1,110 @@
-use axum::{Router, middleware, routing::{get, post}};
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, patch, post, put},
+};
 
 use crate::{
     auth::middleware::auth_middleware,
     handlers::{
-        create_student_handler, delete_student_handler, get_all_students_handler,
-        get_student_handler, initiate_payment_handler, login_handler,
-        paystack_webhook_handler, register_handler,
+        academics::{
+            create_subject_handler, create_term_handler, get_class_results_handler,
+            get_grading_scale_handler, get_subject_broadsheet_handler, get_subjects_handler,
+            get_terms_handler, record_scores_handler, set_assessment_components_handler,
+            set_grading_scale_handler,
+        },
+        admission::{get_admission_format_handler, set_admission_format_handler},
+        attendance::{
+            correct_attendance_handler, get_attendance_register_handler,
+            get_attendance_summary_handler, get_chronic_absentees_handler, mark_attendance_handler,
+        },
+        bank_statements::{
+            confirm_statement_line_handler, get_bank_statement_handler, get_bank_statements_handler,
+            ignore_statement_line_handler, import_bank_statement_handler,
+        },
+        currency::{get_currency_handler, set_currency_handler},
+        custom_fields::{
+            create_custom_field_handler, delete_custom_field_handler, get_custom_fields_handler,
+        },
+        dashboard::get_dashboard_handler,
+        discounts::{
+            create_discount_handler, delete_discount_handler, get_discounts_handler,
+            set_student_discounts_handler, update_discount_handler,
+        },
+        fees::{
+            create_fee_item_handler, delete_fee_item_handler, get_fee_items_handler,
+            get_fee_statement_handler, set_optional_fee_items_handler, update_fee_item_handler,
+        },
+        guardians::{
+            create_guardian_handler, get_guardians_handler, get_siblings_handler,
+            set_guardian_students_handler,
+        },
+        installments::{
+            assign_installment_plan_handler, create_installment_plan_handler,
+            get_installment_plans_handler,
+        },
+        late_fees::{
+            apply_late_fees_handler, get_invoice_late_fees_handler, get_late_fee_policy_handler,
+            set_late_fee_policy_handler, waive_late_fee_handler,
+        },
+        ledger::{
+            create_invoice_handler, get_invoice_handler, get_student_invoices_handler,
+            get_student_ledger_handler, get_student_payments_handler, list_invoices_handler,
+            verify_payment_handler,
+        },
+        manual_payments::{
+            approve_manual_payment_handler, get_manual_payment_policy_handler,
+            get_payment_evidence_handler, get_payments_awaiting_approval_handler,
+            record_manual_payment_handler, reject_manual_payment_handler,
+            set_manual_payment_policy_handler,
+        },
+        payment_links::{
+            create_payment_link_handler, get_payment_links_handler, payment_page_handler,
+            payment_return_handler, revoke_payment_link_handler, start_link_checkout_handler,
+        },
+        payment_settings::{get_payment_settings_handler, set_payment_settings_handler},
+        receipts::{
+            check_receipt_handler, get_branding_handler, get_payment_receipt_handler,
+            set_branding_handler,
+        },
+        refunds::{create_refund_handler, get_payment_refunds_handler},
+        reminders::{
+            get_reminder_preferences_handler, get_reminder_schedule_handler,
+            get_student_reminders_handler, send_reminders_handler,
+            set_reminder_preferences_handler, set_reminder_schedule_handler,
+        },
+        report_cards::{
+            get_class_report_cards_handler, get_report_card_handler,
+            get_report_card_preview_handler, set_report_card_remarks_handler,
+        },
+        reports::{
+            get_aging_report_handler, get_collections_report_handler, get_debtors_report_handler,
+            get_outstanding_report_handler,
+        },
+        staff::{create_staff_handler, get_staff_handler},
+        subaccounts::{
+            get_platform_settlements_handler, get_settlements_handler, get_subaccount_handler,
+            set_subaccount_handler,
+        },
+        timetable::{
+            create_teacher_handler, create_timetable_slot_handler, delete_timetable_slot_handler,
+            get_class_timetable_handler, get_teacher_calendar_handler,
+            get_teacher_timetable_handler, get_teachers_handler,
+        },
+        virtual_accounts::{
+            create_guardian_virtual_account_handler, create_student_virtual_account_handler,
+            get_student_virtual_account_handler,
+        },
+        webhooks::{
+            get_platform_webhook_events_handler, get_webhook_events_handler,
+            replay_platform_webhook_event_handler, replay_webhook_event_handler, school_webhook_handler,
+        },
+        create_student_handler, delete_student_handler, export_students_handler,
+        get_all_students_handler, get_student_handler, initiate_payment_handler, login_handler,
+        paystack_webhook_handler, register_handler, update_student_handler,
     },
-    models::AppStore,
+    models::{AppStore, bank_statements::MAX_STATEMENT_BYTES, manual_payments::MAX_EVIDENCE_BYTES},
 };
 
 pub fn create_router(store: AppStore) -> Router {
@@ -16,13 +113,161 @@ pub fn create_router(store: AppStore) -> Router {
         .route("/", get(|| async { "Hello from Axum! 🦀" }))
         .route("/auth/register", post(register_handler))
         .route("/auth/login", post(login_handler))
-        .route("/webhook/paystack", post(paystack_webhook_handler));
+        .route("/webhook/paystack", post(paystack_webhook_handler))
+        .route("/webhook/schools/{school_id}", post(school_webhook_handler))
+        .route("/receipts/{code}/check", get(check_receipt_handler))
+        .route("/pay/{token}", get(payment_page_handler).post(start_link_checkout_handler))
+        .route("/pay/{token}/return", get(payment_return_handler))
+        .route("/platform/settlements", get(get_platform_settlements_handler))
+        .route("/platform/webhook-events", get(get_platform_webhook_events_handler))
+        .route("/platform/webhook-events/{id}/replay", post(replay_platform_webhook_event_handler));
 
     // Protected routes — token required
     let protected_routes = Router::new()
+        .route("/dashboard", get(get_dashboard_handler))
         .route("/students", post(create_student_handler).get(get_all_students_handler))
-        .route("/students/{id}", get(get_student_handler).delete(delete_student_handler))
+        .route("/students/export", get(export_students_handler))
+        .route(
+            "/students/{id}",
+            get(get_student_handler)
+                .put(update_student_handler)
+                .delete(delete_student_handler),
+        )
         .route("/students/{id}/pay", post(initiate_payment_handler))
+        .route(
+            "/students/{id}/payment-links",
+            post(create_payment_link_handler).get(get_payment_links_handler),
+        )
+        .route("/students/{id}/fee-items", put(set_optional_fee_items_handler))
+        .route("/students/{id}/discounts", put(set_student_discounts_handler))
+        .route("/students/{id}/siblings", get(get_siblings_handler))
+        .route(
+            "/students/{id}/reminder-preferences",
+            put(set_reminder_preferences_handler).get(get_reminder_preferences_handler),
+        )
+        .route("/students/{id}/reminders", get(get_student_reminders_handler))
+        .route(
+            "/students/{id}/virtual-account",
+            post(create_student_virtual_account_handler).get(get_student_virtual_account_handler),
+        )
+        .route("/students/{id}/fees/{term}", get(get_fee_statement_handler))
+        .route(
+            "/students/{id}/invoices",
+            post(create_invoice_handler).get(get_student_invoices_handler),
+        )
+        .route("/students/{id}/ledger", get(get_student_ledger_handler))
+        .route("/students/{id}/payments", get(get_student_payments_handler))
+        .route(
+            "/students/{id}/payments/manual",
+            // room for the evidence file on top of the form fields
+            post(record_manual_payment_handler).layer(DefaultBodyLimit::max(MAX_EVIDENCE_BYTES + 64 * 1024)),
+        )
+        .route("/payment-links/{id}/revoke", post(revoke_payment_link_handler))
+        .route("/payments/awaiting-approval", get(get_payments_awaiting_approval_handler))
+        .route("/payments/{reference}/verify", post(verify_payment_handler))
+        .route("/payments/{id}/approve", post(approve_manual_payment_handler))
+        .route("/payments/{id}/reject", post(reject_manual_payment_handler))
+        .route("/payments/{id}/evidence", get(get_payment_evidence_handler))
+        .route("/payments/{id}/receipt", get(get_payment_receipt_handler))
+        .route(
+            "/payments/{id}/refunds",
+            post(create_refund_handler).get(get_payment_refunds_handler),
+        )
+        .route("/webhooks/events", get(get_webhook_events_handler))
+        .route("/webhooks/events/{id}/replay", post(replay_webhook_event_handler))
+        .route("/invoices", get(list_invoices_handler))
+        .route("/invoices/{id}", get(get_invoice_handler))
+        .route("/invoices/{id}/installment-plan", put(assign_installment_plan_handler))
+        .route("/invoices/{id}/late-fees", get(get_invoice_late_fees_handler))
+        .route("/late-fees/run", post(apply_late_fees_handler))
+        .route("/late-fees/{id}/waive", post(waive_late_fee_handler))
+        .route("/reminders/run", post(send_reminders_handler))
+        .route("/settlements", get(get_settlements_handler))
+        .route(
+            "/bank-statements",
+            post(import_bank_statement_handler)
+                .get(get_bank_statements_handler)
+                .layer(DefaultBodyLimit::max(MAX_STATEMENT_BYTES + 64 * 1024)),
+        )
+        .route("/bank-statements/{id}", get(get_bank_statement_handler))
+        .route("/bank-statements/lines/{id}/confirm", post(confirm_statement_line_handler))
+        .route("/bank-statements/lines/{id}/ignore", post(ignore_statement_line_handler))
+        .route("/reports/collections", get(get_collections_report_handler))
+        .route("/reports/outstanding", get(get_outstanding_report_handler))
+        .route("/reports/aging", get(get_aging_report_handler))
+        .route("/reports/debtors", get(get_debtors_report_handler))
+        .route(
+            "/fees/installment-plans",
+            post(create_installment_plan_handler).get(get_installment_plans_handler),
+        )
+        .route("/fees/items", post(create_fee_item_handler).get(get_fee_items_handler))
+        .route(
+            "/fees/items/{id}",
+            put(update_fee_item_handler).delete(delete_fee_item_handler),
+        )
+        .route("/fees/discounts", post(create_discount_handler).get(get_discounts_handler))
+        .route(
+            "/fees/discounts/{id}",
+            put(update_discount_handler).delete(delete_discount_handler),
+        )
+        .route("/guardians", post(create_guardian_handler).get(get_guardians_handler))
+        .route("/guardians/{id}/students", put(set_guardian_students_handler))
+        .route("/guardians/{id}/virtual-account", post(create_guardian_virtual_account_handler))
+        .route(
+            "/settings/custom-fields",
+            post(create_custom_field_handler).get(get_custom_fields_handler),
+        )
+        .route("/settings/custom-fields/{key}", delete(delete_custom_field_handler))
+        .route("/staff", post(create_staff_handler).get(get_staff_handler))
+        .route(
+            "/settings/manual-payments",
+            put(set_manual_payment_policy_handler).get(get_manual_payment_policy_handler),
+        )
+        .route("/settings/branding", put(set_branding_handler).get(get_branding_handler))
+        .route("/settings/currency", put(set_currency_handler).get(get_currency_handler))
+        .route(
+            "/settings/late-fees",
+            put(set_late_fee_policy_handler).get(get_late_fee_policy_handler),
+        )
+        .route(
+            "/settings/reminders",
+            put(set_reminder_schedule_handler).get(get_reminder_schedule_handler),
+        )
+        .route("/settings/subaccount", put(set_subaccount_handler).get(get_subaccount_handler))
+        .route(
+            "/settings/payments",
+            put(set_payment_settings_handler).get(get_payment_settings_handler),
+        )
+        .route(
+            "/settings/admission-number",
+            put(set_admission_format_handler).get(get_admission_format_handler),
+        )
+        .route("/terms", post(create_term_handler).get(get_terms_handler))
+        .route("/terms/{term}/assessment-components", put(set_assessment_components_handler))
+        .route("/subjects", post(create_subject_handler).get(get_subjects_handler))
+        .route("/grading-scale", put(set_grading_scale_handler).get(get_grading_scale_handler))
+        .route(
+            "/classes/{class}/subjects/{subject_id}/terms/{term}/scores",
+            post(record_scores_handler).get(get_subject_broadsheet_handler),
+        )
+        .route("/classes/{class}/terms/{term}/results", get(get_class_results_handler))
+        .route("/students/{id}/report-cards/{term}", get(get_report_card_handler))
+        .route("/students/{id}/report-cards/{term}/preview", get(get_report_card_preview_handler))
+        .route("/students/{id}/report-cards/{term}/remarks", put(set_report_card_remarks_handler))
+        .route("/classes/{class}/report-cards/{term}", get(get_class_report_cards_handler))
+        .route(
+            "/classes/{class}/attendance",
+            post(mark_attendance_handler).get(get_attendance_register_handler),
+        )
+        .route("/attendance/absentees", get(get_chronic_absentees_handler))
+        .route("/attendance/{id}", patch(correct_attendance_handler))
+        .route("/students/{id}/attendance/{term}", get(get_attendance_summary_handler))
+        .route("/teachers", post(create_teacher_handler).get(get_teachers_handler))
+        .route("/teachers/{id}/timetable", get(get_teacher_timetable_handler))
+        .route("/teachers/{id}/timetable.ics", get(get_teacher_calendar_handler))
+        .route("/timetable/slots", post(create_timetable_slot_handler))
+        .route("/timetable/slots/{id}", delete(delete_timetable_slot_handler))
+        .route("/classes/{class}/timetable", get(get_class_timetable_handler))
         .layer(middleware::from_fn_with_state(store.clone(), auth_middleware));
 
     Router::new()
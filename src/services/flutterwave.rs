@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    errors::AppError,
+    models::{
+        ledger::TransactionStatus,
+        payment_settings::PaymentProviderKind,
+        refunds::RefundStatus,
+        webhooks::{ParsedWebhookEvent, WebhookEventKind},
+    },
+};
+
+use super::{
+    Checkout, CheckoutRequest, PaymentProvider, RefundReceipt, VerifiedTransaction, base_url, gateway_error, id_string,
+};
+
+pub struct FlutterwaveProvider {
+    client: reqwest::Client,
+    secret_key: String,
+    secret_hash: String, // set on the Flutterwave dashboard and echoed in `verif-hash`
+    base_url: String,
+}
+
+impl FlutterwaveProvider {
+    pub fn new(secret_key: String, secret_hash: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            secret_key,
+            secret_hash,
+            base_url: base_url("FLUTTERWAVE_BASE_URL", "https://api.flutterwave.com"),
+        }
+    }
+}
+
+// Flutterwave works in major units, we work in minor units (kobo, pesewas, cents)
+fn to_major(amount_kobo: u64) -> f64 {
+    amount_kobo as f64 / 100.0
+}
+
+fn to_kobo(amount: f64) -> u64 {
+    (amount * 100.0).round() as u64
+}
+
+#[derive(Serialize)]
+struct Customer<'a> {
+    email: &'a str,
+}
+
+#[derive(Serialize)]
+struct PaymentBody<'a> {
+    tx_ref: &'a str,
+    amount: f64,
+    currency: &'a str,
+    redirect_url: &'a str,
+    customer: Customer<'a>,
+}
+
+#[derive(Serialize)]
+struct RefundBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct FlutterwaveResponse<T> {
+    status: String, // "success" or "error"
+    message: String,
+    data: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct PaymentLink {
+    link: String,
+}
+
+#[derive(Deserialize)]
+struct TransactionData {
+    id: u64,
+    tx_ref: String,
+    status: String, // "successful", "failed", "pending", ...
+    amount: f64,
+    currency: String,
+}
+
+#[derive(Deserialize)]
+struct RefundData {
+    id: Value,
+    status: String,
+}
+
+impl FlutterwaveProvider {
+    async fn read<T: for<'de> Deserialize<'de>>(&self, request: reqwest::RequestBuilder) -> Result<T, AppError> {
+        let parsed = request
+            .header("Authorization", format!("Bearer {}", self.secret_key))
+            .send()
+            .await
+            .map_err(|e| gateway_error("Flutterwave", e))?
+            .json::<FlutterwaveResponse<T>>()
+            .await
+            .map_err(|e| gateway_error("Flutterwave", e))?;
+
+        match parsed.data {
+            Some(data) if parsed.status == "success" => Ok(data),
+            _ => Err(gateway_error("Flutterwave", parsed.message)),
+        }
+    }
+
+    async fn find_transaction(&self, reference: &str) -> Result<TransactionData, AppError> {
+        let url = format!("{}/v3/transactions/verify_by_reference", self.base_url);
+        self.read(self.client.get(url).query(&[("tx_ref", reference)])).await
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for FlutterwaveProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::Flutterwave
+    }
+
+    async fn initialize(&self, checkout: &CheckoutRequest) -> Result<Checkout, AppError> {
+        let redirect_url = checkout
+            .callback_url
+            .as_deref()
+            .ok_or_else(|| gateway_error("Flutterwave", "a callback URL is required (set PAYMENT_CALLBACK_URL)"))?;
+        let body = PaymentBody {
+            tx_ref: &checkout.reference,
+            amount: to_major(checkout.amount.minor_units()),
+            currency: checkout.amount.currency.code(),
+            redirect_url,
+            customer: Customer { email: &checkout.email },
+        };
+        let data: PaymentLink = self
+            .read(self.client.post(format!("{}/v3/payments", self.base_url)).json(&body))
+            .await?;
+
+        Ok(Checkout {
+            authorization_url: data.link,
+            reference: checkout.reference.clone(),
+        })
+    }
+
+    async fn verify(&self, reference: &str) -> Result<VerifiedTransaction, AppError> {
+        let data = self.find_transaction(reference).await?;
+
+        Ok(VerifiedTransaction {
+            status: match data.status.as_str() {
+                "successful" => TransactionStatus::Successful,
+                "failed" | "cancelled" => TransactionStatus::Failed,
+                _ => TransactionStatus::Pending,
+            },
+            reference: data.tx_ref,
+            amount_kobo: to_kobo(data.amount),
+            currency: data.currency,
+        })
+    }
+
+    async fn refund(&self, reference: &str, amount_kobo: Option<u64>) -> Result<RefundReceipt, AppError> {
+        // refunds are made against Flutterwave's transaction id, not our reference
+        let transaction = self.find_transaction(reference).await?;
+        let body = RefundBody { amount: amount_kobo.map(to_major) };
+        let url = format!("{}/v3/transactions/{}/refund", self.base_url, transaction.id);
+        let data: RefundData = self.read(self.client.post(url).json(&body)).await?;
+
+        Ok(RefundReceipt {
+            refund_id: id_string(&data.id),
+            status: match data.status.as_str() {
+                "completed" => RefundStatus::Processed,
+                "failed" => RefundStatus::Failed,
+                _ => RefundStatus::Pending,
+            },
+        })
+    }
+
+    // Flutterwave doesn't sign the body; it sends back the secret hash as is
+    fn verify_signature(&self, headers: &HeaderMap, _body: &[u8]) -> bool {
+        headers
+            .get("verif-hash")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|hash| {
+                !self.secret_hash.is_empty() && bool::from(hash.as_bytes().ct_eq(self.secret_hash.as_bytes()))
+            })
+    }
+
+    fn parse_event(&self, body: &[u8]) -> Result<ParsedWebhookEvent, AppError> {
+        let payload: Value = serde_json::from_slice(body).map_err(|e| AppError::ParsingError(e.to_string()))?;
+        let event_type = payload["event"].as_str().unwrap_or_default().to_string();
+        let data = &payload["data"];
+        let status = data["status"].as_str().unwrap_or_default();
+
+        Ok(ParsedWebhookEvent {
+            // the same transaction is reported again when its status changes
+            event_key: format!("{}:{}:{}", event_type, data["id"], status),
+            kind: match (event_type.as_str(), status) {
+                ("charge.completed", "successful") => WebhookEventKind::ChargeSuccess,
+                ("charge.completed", "failed") => WebhookEventKind::ChargeFailed,
+                ("refund.completed", "completed") => WebhookEventKind::RefundProcessed,
+                ("refund.completed", "failed") => WebhookEventKind::RefundFailed,
+                _ => WebhookEventKind::Other,
+            },
+            refund_id: event_type.starts_with("refund.").then(|| id_string(&data["id"])),
+            event_type,
+            reference: data["tx_ref"].as_str().map(str::to_string),
+            amount_kobo: data["amount"].as_f64().map(to_kobo),
+            currency: data["currency"].as_str().map(str::to_string),
+            account_number: None,
+            payload,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(hash: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("verif-hash", hash.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn accepts_the_configured_hash() {
+        let provider = FlutterwaveProvider::new("FLWSECK_TEST".to_string(), "hash-1".to_string());
+        assert!(provider.verify_signature(&headers("hash-1"), b"{}"));
+    }
+
+    #[test]
+    fn rejects_another_hash_or_none() {
+        let provider = FlutterwaveProvider::new("FLWSECK_TEST".to_string(), "hash-1".to_string());
+        assert!(!provider.verify_signature(&headers("hash-2"), b"{}"));
+        assert!(!provider.verify_signature(&headers("hash-10"), b"{}"));
+        assert!(!provider.verify_signature(&HeaderMap::new(), b"{}"));
+    }
+
+    #[test]
+    fn rejects_everything_when_no_hash_is_configured() {
+        let provider = FlutterwaveProvider::new("FLWSECK_TEST".to_string(), String::new());
+        assert!(!provider.verify_signature(&headers(""), b"{}"));
+    }
+}
@@ -1,57 +1,280 @@
-use serde::{Deserialize, Serialize};
-use crate::errors::AppError;
+pub mod flutterwave;
+pub mod notifications;
+pub mod paystack;
+pub mod stripe;
 
-#[derive(Serialize)]
-struct InitializePaymentBody {
-    email: String,
-    amount: u64, // in kobo (smallest currency unit). e.g. NGN 5000 = 500000 kobo
-    reference: String,
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use uuid::Uuid;
+
+use crate::{
+    config::get_env_vars,
+    errors::AppError,
+    models::{
+        AppStore,
+        ledger::{Invoice, Payment, PaymentMethod, TransactionStatus},
+        money::Money,
+        payment_settings::{PaymentGateway, PaymentProviderKind, PaymentSettings},
+        refunds::RefundStatus,
+        subaccounts::{PaymentSplit, SplitBearer},
+        webhooks::ParsedWebhookEvent,
+    },
+};
+use flutterwave::FlutterwaveProvider;
+use paystack::PaystackProvider;
+use stripe::StripeProvider;
+
+pub struct CheckoutRequest {
+    pub email: String,
+    pub amount: Money,
+    pub reference: String, // ours, so the provider can hand it back to us
+    pub callback_url: Option<String>, // where the payer returns after checkout
+    pub split: Option<CheckoutSplit>, // Paystack only
 }
 
-#[derive(Deserialize)]
-struct PaystackInitResponse {
-    status: bool,
-    data: PaystackInitData,
+/// Routes a platform payment to a school's subaccount.
+pub struct CheckoutSplit {
+    pub subaccount: String,
+    pub transaction_charge_kobo: Option<u64>,
+    pub bearer: SplitBearer,
 }
 
-#[derive(Deserialize)]
-pub struct PaystackInitData {
+pub struct Checkout {
     pub authorization_url: String,
     pub reference: String,
 }
 
-pub async fn initialize_paystack_transaction(
-    secret_key: &str,
-    email: &str,
+pub struct VerifiedTransaction {
+    pub reference: String,
+    pub status: TransactionStatus,
+    pub amount_kobo: u64,
+    pub currency: String,
+}
+
+pub struct RefundReceipt {
+    pub refund_id: String,
+    pub status: RefundStatus, // most providers only confirm later, by webhook
+}
+
+/// A payment gateway. Each adapter maps its provider's API and webhook
+/// format onto these calls so the rest of the app never sees the difference.
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    fn kind(&self) -> PaymentProviderKind;
+
+    async fn initialize(&self, checkout: &CheckoutRequest) -> Result<Checkout, AppError>;
+
+    async fn verify(&self, reference: &str) -> Result<VerifiedTransaction, AppError>;
+
+    /// Refunds a settled transaction; `None` refunds the whole amount.
+    async fn refund(&self, reference: &str, amount_kobo: Option<u64>) -> Result<RefundReceipt, AppError>;
+
+    fn verify_signature(&self, headers: &HeaderMap, body: &[u8]) -> bool;
+
+    fn parse_event(&self, body: &[u8]) -> Result<ParsedWebhookEvent, AppError>;
+}
+
+// each provider's base URL can be pointed at a mock server for local testing
+fn base_url(env_key: &str, default: &str) -> String {
+    let base: String = get_env_vars(env_key.to_string()).unwrap_or_else(|_| default.to_string());
+    base.trim_end_matches('/').to_string()
+}
+
+// ids arrive as numbers from some providers and strings from others
+fn id_string(id: &serde_json::Value) -> String {
+    match id.as_str() {
+        Some(id) => id.to_string(),
+        None => id.to_string(),
+    }
+}
+
+fn gateway_error(provider: &str, e: impl std::fmt::Display) -> AppError {
+    AppError::InternalServerError(format!("{}: {}", provider, e))
+}
+
+pub fn provider_for(settings: &PaymentSettings) -> Box<dyn PaymentProvider> {
+    let secret_key = settings.secret_key.clone();
+    let webhook_secret = settings.webhook_secret.clone().unwrap_or_default();
+    match settings.provider {
+        PaymentProviderKind::Paystack => Box::new(PaystackProvider::new(secret_key)),
+        PaymentProviderKind::Flutterwave => Box::new(FlutterwaveProvider::new(secret_key, webhook_secret)),
+        PaymentProviderKind::Stripe => Box::new(StripeProvider::new(secret_key, webhook_secret)),
+    }
+}
+
+/// The platform's own Paystack account, used by schools that haven't
+/// configured a provider of their own.
+pub fn platform_provider() -> Result<Box<dyn PaymentProvider>, AppError> {
+    Ok(Box::new(platform_paystack()?))
+}
+
+pub fn platform_paystack() -> Result<PaystackProvider, AppError> {
+    let secret_key: String = get_env_vars("PAYSTACK_SECRET_KEY".to_string())?;
+    Ok(PaystackProvider::new(secret_key))
+}
+
+/// The Paystack account a school collects through, for Paystack-only
+/// features such as dedicated virtual accounts.
+pub async fn school_paystack(store: &AppStore, school_id: Uuid) -> Result<PaystackProvider, AppError> {
+    match store.get_payment_settings(school_id).await {
+        Ok(settings) if settings.provider == PaymentProviderKind::Paystack => {
+            Ok(PaystackProvider::new(settings.secret_key))
+        }
+        Ok(_) => Err(AppError::invalid("provider", "This needs the school to collect through Paystack")),
+        Err(_) => platform_paystack(),
+    }
+}
+
+/// The platform's commission, in percent, on payments settled to school
+/// subaccounts (PLATFORM_COMMISSION_PERCENT, default 0).
+pub fn platform_commission_percent() -> Result<f64, AppError> {
+    let percent: f64 = get_env_vars("PLATFORM_COMMISSION_PERCENT".to_string()).unwrap_or(0.0);
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(AppError::ParsingError(
+            "PLATFORM_COMMISSION_PERCENT must be between 0 and 100".to_string(),
+        ));
+    }
+    Ok(percent)
+}
+
+/// The platform's cut of `amount_kobo`: a flat charge when one is set,
+/// otherwise the percentage. Never more than the payment itself.
+fn platform_fee_kobo(amount_kobo: u64, percentage_charge: f64, transaction_charge_kobo: Option<u64>) -> u64 {
+    match transaction_charge_kobo {
+        Some(charge) => charge.min(amount_kobo),
+        None => {
+            let percentage = percentage_charge.clamp(0.0, 100.0);
+            ((amount_kobo as f64 * percentage / 100.0).round() as u64).min(amount_kobo)
+        }
+    }
+}
+
+/// How a platform payment of `amount_kobo` is split with a school's
+/// subaccount. A flat PLATFORM_TRANSACTION_CHARGE_KOBO replaces the
+/// percentage; PLATFORM_FEE_BEARER says who pays Paystack's fee.
+fn platform_split(subaccount_code: &str, percentage_charge: f64, amount_kobo: u64) -> (CheckoutSplit, PaymentSplit) {
+    let transaction_charge_kobo: Option<u64> = get_env_vars("PLATFORM_TRANSACTION_CHARGE_KOBO".to_string()).ok();
+    let bearer = match get_env_vars::<String>("PLATFORM_FEE_BEARER".to_string()).as_deref() {
+        Ok("account") => SplitBearer::Account,
+        _ => SplitBearer::Subaccount,
+    };
+    let platform_fee_kobo = platform_fee_kobo(amount_kobo, percentage_charge, transaction_charge_kobo);
+
+    (
+        CheckoutSplit {
+            subaccount: subaccount_code.to_string(),
+            // the capped charge, so Paystack takes exactly what we record
+            transaction_charge_kobo: transaction_charge_kobo.map(|_| platform_fee_kobo),
+            bearer,
+        },
+        PaymentSplit {
+            subaccount_code: subaccount_code.to_string(),
+            platform_fee_kobo,
+            school_share_kobo: amount_kobo.saturating_sub(platform_fee_kobo),
+            bearer,
+        },
+    )
+}
+
+pub async fn school_provider(store: &AppStore, school_id: Uuid) -> Result<Box<dyn PaymentProvider>, AppError> {
+    match store.get_payment_settings(school_id).await {
+        Ok(settings) => Ok(provider_for(&settings)),
+        Err(_) => platform_provider(),
+    }
+}
+
+/// The gateway account that took `payment`, so refunds and checks reach it
+/// even after the school has moved to another provider. Payments from
+/// before gateways were recorded go to the school's current one.
+pub async fn payment_provider(store: &AppStore, payment: &Payment) -> Result<Box<dyn PaymentProvider>, AppError> {
+    let Some(gateway) = payment.gateway else {
+        return school_provider(store, payment.school_id).await;
+    };
+    if gateway.platform {
+        return platform_provider();
+    }
+    match store.get_payment_settings(payment.school_id).await {
+        Ok(settings) if settings.provider == gateway.provider => Ok(provider_for(&settings)),
+        _ => Err(AppError::Conflict(format!(
+            "This payment went through the school's {:?} account, which is no longer configured",
+            gateway.provider
+        ))),
+    }
+}
+
+/// Opens a checkout with the school's gateway for `amount_kobo` of an
+/// invoice. The payment is recorded as pending first, so the gateway can
+/// never call us back about a reference we don't know. The payer returns to
+/// `callback_url`, or to PAYMENT_CALLBACK_URL when none is given.
+pub async fn start_checkout(
+    store: &AppStore,
+    invoice: &Invoice,
     amount_kobo: u64,
-    reference: &str,
-) -> Result<PaystackInitData, AppError> {
-    let client = reqwest::Client::new();
+    email: &str,
+    callback_url: Option<String>,
+) -> Result<Checkout, AppError> {
+    let settings = store.get_payment_settings(invoice.school_id).await.ok();
+    let provider = match &settings {
+        Some(settings) => provider_for(settings),
+        None => platform_provider()?,
+    };
+    let gateway = PaymentGateway {
+        provider: provider.kind(),
+        platform: settings.is_none(),
+    };
+    // only platform payments can be split; schools on their own account keep it all
+    let uses_platform = gateway.platform;
+    let split = match store.get_subaccount(invoice.school_id).await {
+        Ok(subaccount) if uses_platform => Some(platform_split(
+            &subaccount.subaccount_code,
+            subaccount.percentage_charge,
+            amount_kobo,
+        )),
+        _ => None,
+    };
+
+    let reference = format!("sch-{}", Uuid::new_v4());
+    store
+        .create_pending_payment(invoice, reference.clone(), amount_kobo, PaymentMethod::Online)
+        .await?;
+    store.set_payment_gateway(&reference, gateway).await?;
 
-    let body = InitializePaymentBody {
+    let (checkout_split, payment_split) = split.unzip();
+    if let Some(payment_split) = payment_split {
+        store.set_payment_split(&reference, payment_split).await?;
+    }
+
+    let checkout = CheckoutRequest {
         email: email.to_string(),
-        amount: amount_kobo,
-        reference: reference.to_string(),
+        amount: Money::from_minor(amount_kobo, invoice.currency),
+        reference: reference.clone(),
+        callback_url: callback_url.or_else(|| get_env_vars("PAYMENT_CALLBACK_URL".to_string()).ok()),
+        split: checkout_split,
     };
+    match provider.initialize(&checkout).await {
+        Ok(data) => Ok(data),
+        Err(e) => {
+            let _ = store.fail_payment(&reference).await;
+            Err(e)
+        }
+    }
+}
 
-    let response = client
-        .post("https://api.paystack.co/transaction/initialize")
-        .header("Authorization", format!("Bearer {}", secret_key))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
-
-    let parsed = response
-        .json::<PaystackInitResponse>()
-        .await
-        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
-
-    if !parsed.status {
-        return Err(AppError::InternalServerError(
-            "Paystack rejected the transaction".to_string(),
-        ));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platform_fee_is_a_percentage_of_the_payment() {
+        assert_eq!(platform_fee_kobo(1_000_000, 2.5, None), 25_000);
+        assert_eq!(platform_fee_kobo(1_000_000, 0.0, None), 0);
     }
 
-    Ok(parsed.data)
-}
\ No newline at end of file
+    #[test]
+    fn platform_fee_never_exceeds_the_payment() {
+        assert_eq!(platform_fee_kobo(1_000_000, 150.0, None), 1_000_000);
+        assert_eq!(platform_fee_kobo(1_000_000, -5.0, None), 0);
+        assert_eq!(platform_fee_kobo(1_000_000, 2.5, Some(5_000_000)), 1_000_000);
+        assert_eq!(platform_fee_kobo(1_000_000, 2.5, Some(10_000)), 10_000);
+    }
+}
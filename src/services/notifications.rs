@@ -0,0 +1,139 @@
+use serde::Serialize;
+
+use crate::{config::get_env_vars, errors::AppError};
+
+use super::{base_url, gateway_error};
+
+/// Sends email through SendGrid and SMS through Termii. Either channel is
+/// left unconfigured when its API key isn't set, and sending on it fails.
+pub struct Notifier {
+    client: reqwest::Client,
+    email: Option<EmailConfig>,
+    sms: Option<SmsConfig>,
+}
+
+struct EmailConfig {
+    api_key: String,
+    from: String,
+    base_url: String,
+}
+
+struct SmsConfig {
+    api_key: String,
+    sender_id: String,
+    base_url: String,
+}
+
+#[derive(Serialize)]
+struct SendGridBody<'a> {
+    personalizations: Vec<SendGridPersonalization<'a>>,
+    from: SendGridAddress<'a>,
+    subject: &'a str,
+    content: Vec<SendGridContent<'a>>,
+}
+
+#[derive(Serialize)]
+struct SendGridPersonalization<'a> {
+    to: Vec<SendGridAddress<'a>>,
+}
+
+#[derive(Serialize)]
+struct SendGridAddress<'a> {
+    email: &'a str,
+}
+
+#[derive(Serialize)]
+struct SendGridContent<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct TermiiBody<'a> {
+    api_key: &'a str,
+    to: &'a str,
+    from: &'a str,
+    sms: &'a str,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    channel: &'a str,
+}
+
+impl Notifier {
+    pub fn from_env() -> Self {
+        let email = get_env_vars::<String>("SENDGRID_API_KEY".to_string())
+            .ok()
+            .map(|api_key| EmailConfig {
+                api_key,
+                from: get_env_vars("EMAIL_FROM".to_string()).unwrap_or_else(|_| "no-reply@example.com".to_string()),
+                base_url: base_url("SENDGRID_BASE_URL", "https://api.sendgrid.com"),
+            });
+        let sms = get_env_vars::<String>("TERMII_API_KEY".to_string())
+            .ok()
+            .map(|api_key| SmsConfig {
+                api_key,
+                sender_id: get_env_vars("SMS_SENDER_ID".to_string()).unwrap_or_else(|_| "School".to_string()),
+                base_url: base_url("TERMII_BASE_URL", "https://api.ng.termii.com"),
+            });
+
+        Self {
+            client: reqwest::Client::new(),
+            email,
+            sms,
+        }
+    }
+
+    pub async fn send_email(&self, to: &str, subject: &str, body: &str) -> Result<(), AppError> {
+        let config = self
+            .email
+            .as_ref()
+            .ok_or_else(|| AppError::InternalServerError("Email is not configured".to_string()))?;
+        let request = SendGridBody {
+            personalizations: vec![SendGridPersonalization { to: vec![SendGridAddress { email: to }] }],
+            from: SendGridAddress { email: &config.from },
+            subject,
+            content: vec![SendGridContent { kind: "text/plain", value: body }],
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/v3/mail/send", config.base_url))
+            .bearer_auth(&config.api_key)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| gateway_error("SendGrid", e))?;
+        if !response.status().is_success() {
+            return Err(gateway_error("SendGrid", response.status()));
+        }
+        Ok(())
+    }
+
+    pub async fn send_sms(&self, to: &str, body: &str) -> Result<(), AppError> {
+        let config = self
+            .sms
+            .as_ref()
+            .ok_or_else(|| AppError::InternalServerError("SMS is not configured".to_string()))?;
+        let request = TermiiBody {
+            api_key: &config.api_key,
+            to,
+            from: &config.sender_id,
+            sms: body,
+            kind: "plain",
+            channel: "generic",
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/sms/send", config.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| gateway_error("Termii", e))?;
+        if !response.status().is_success() {
+            return Err(gateway_error("Termii", response.status()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha512;
+
+use crate::{
+    config::get_env_vars,
+    errors::AppError,
+    models::{
+        ledger::TransactionStatus,
+        payment_settings::PaymentProviderKind,
+        refunds::RefundStatus,
+        subaccounts::{SplitBearer, SubaccountRequest},
+        webhooks::{ParsedWebhookEvent, WebhookEventKind},
+    },
+};
+
+use super::{
+    Checkout, CheckoutRequest, PaymentProvider, RefundReceipt, VerifiedTransaction, base_url, gateway_error, id_string,
+};
+
+pub struct PaystackProvider {
+    client: reqwest::Client,
+    secret_key: String,
+    base_url: String,
+}
+
+impl PaystackProvider {
+    pub fn new(secret_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            secret_key,
+            base_url: base_url("PAYSTACK_BASE_URL", "https://api.paystack.co"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct InitializePaymentBody<'a> {
+    email: &'a str,
+    amount: u64, // in the currency's minor unit, e.g. NGN 5000 = 500000 kobo
+    currency: &'a str,
+    reference: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callback_url: Option<&'a str>,
+    // split settlement to a school's subaccount
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subaccount: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction_charge: Option<u64>, // flat platform fee in kobo, overrides the subaccount's percentage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bearer: Option<SplitBearer>,
+}
+
+#[derive(Serialize)]
+struct SubaccountBody<'a> {
+    business_name: &'a str,
+    settlement_bank: &'a str,
+    account_number: &'a str,
+    percentage_charge: f64,
+}
+
+#[derive(Serialize)]
+struct RefundBody<'a> {
+    transaction: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<u64>,
+}
+
+// every Paystack response shares this envelope
+#[derive(Deserialize)]
+struct PaystackResponse<T> {
+    status: bool,
+    message: String,
+    data: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct PaystackInitData {
+    authorization_url: String,
+    reference: String,
+}
+
+#[derive(Deserialize)]
+struct PaystackVerifyData {
+    status: String, // "success", "failed", "abandoned", "ongoing", ...
+    reference: String,
+    amount: u64,
+    currency: String,
+}
+
+#[derive(Serialize)]
+struct CustomerBody<'a> {
+    email: &'a str,
+    first_name: &'a str,
+    last_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct DedicatedAccountBody<'a> {
+    customer: &'a str,
+    preferred_bank: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PaystackCustomerData {
+    customer_code: String,
+}
+
+#[derive(Deserialize)]
+pub struct PaystackBank {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct PaystackDedicatedAccountData {
+    pub account_name: String,
+    pub account_number: String,
+    pub bank: PaystackBank,
+}
+
+#[derive(Deserialize)]
+pub struct PaystackSubaccountData {
+    pub subaccount_code: String,
+}
+
+#[derive(Deserialize)]
+struct PaystackRefundData {
+    id: Value,
+    status: String,
+}
+
+impl PaystackProvider {
+    async fn read<T: for<'de> Deserialize<'de>>(&self, request: reqwest::RequestBuilder) -> Result<T, AppError> {
+        let parsed = request
+            .header("Authorization", format!("Bearer {}", self.secret_key))
+            .send()
+            .await
+            .map_err(|e| gateway_error("Paystack", e))?
+            .json::<PaystackResponse<T>>()
+            .await
+            .map_err(|e| gateway_error("Paystack", e))?;
+
+        match parsed.data {
+            Some(data) if parsed.status => Ok(data),
+            _ => Err(gateway_error("Paystack", parsed.message)),
+        }
+    }
+
+    pub async fn create_subaccount(
+        &self,
+        req: &SubaccountRequest,
+        percentage_charge: f64,
+    ) -> Result<PaystackSubaccountData, AppError> {
+        let body = SubaccountBody {
+            business_name: &req.business_name,
+            settlement_bank: &req.settlement_bank,
+            account_number: &req.account_number,
+            percentage_charge,
+        };
+        self.read(self.client.post(format!("{}/subaccount", self.base_url)).json(&body))
+            .await
+    }
+
+    /// Creates a customer and issues them a dedicated virtual account,
+    /// returning the customer code with the account.
+    pub async fn create_dedicated_account(
+        &self,
+        email: &str,
+        first_name: &str,
+        last_name: &str,
+        phone: Option<&str>,
+    ) -> Result<(String, PaystackDedicatedAccountData), AppError> {
+        let body = CustomerBody { email, first_name, last_name, phone };
+        let customer: PaystackCustomerData = self
+            .read(self.client.post(format!("{}/customer", self.base_url)).json(&body))
+            .await?;
+
+        let preferred_bank: String =
+            get_env_vars("PAYSTACK_DVA_BANK".to_string()).unwrap_or_else(|_| "wema-bank".to_string());
+        let body = DedicatedAccountBody { customer: &customer.customer_code, preferred_bank: &preferred_bank };
+        let account: PaystackDedicatedAccountData = self
+            .read(self.client.post(format!("{}/dedicated_account", self.base_url)).json(&body))
+            .await?;
+        Ok((customer.customer_code, account))
+    }
+
+    pub async fn update_subaccount(
+        &self,
+        subaccount_code: &str,
+        req: &SubaccountRequest,
+        percentage_charge: f64,
+    ) -> Result<PaystackSubaccountData, AppError> {
+        let body = SubaccountBody {
+            business_name: &req.business_name,
+            settlement_bank: &req.settlement_bank,
+            account_number: &req.account_number,
+            percentage_charge,
+        };
+        self.read(
+            self.client
+                .put(format!("{}/subaccount/{}", self.base_url, subaccount_code))
+                .json(&body),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for PaystackProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::Paystack
+    }
+
+    async fn initialize(&self, checkout: &CheckoutRequest) -> Result<Checkout, AppError> {
+        let body = InitializePaymentBody {
+            email: &checkout.email,
+            amount: checkout.amount.minor_units(),
+            currency: checkout.amount.currency.code(),
+            reference: &checkout.reference,
+            callback_url: checkout.callback_url.as_deref(),
+            subaccount: checkout.split.as_ref().map(|s| s.subaccount.as_str()),
+            transaction_charge: checkout.split.as_ref().and_then(|s| s.transaction_charge_kobo),
+            bearer: checkout.split.as_ref().map(|s| s.bearer),
+        };
+        let data: PaystackInitData = self
+            .read(self.client.post(format!("{}/transaction/initialize", self.base_url)).json(&body))
+            .await?;
+
+        Ok(Checkout {
+            authorization_url: data.authorization_url,
+            reference: data.reference,
+        })
+    }
+
+    async fn verify(&self, reference: &str) -> Result<VerifiedTransaction, AppError> {
+        let data: PaystackVerifyData = self
+            .read(self.client.get(format!("{}/transaction/verify/{}", self.base_url, reference)))
+            .await?;
+
+        Ok(VerifiedTransaction {
+            status: match data.status.as_str() {
+                "success" => TransactionStatus::Successful,
+                "failed" | "abandoned" | "reversed" => TransactionStatus::Failed,
+                _ => TransactionStatus::Pending,
+            },
+            reference: data.reference,
+            amount_kobo: data.amount,
+            currency: data.currency,
+        })
+    }
+
+    async fn refund(&self, reference: &str, amount_kobo: Option<u64>) -> Result<RefundReceipt, AppError> {
+        let body = RefundBody { transaction: reference, amount: amount_kobo };
+        let data: PaystackRefundData = self
+            .read(self.client.post(format!("{}/refund", self.base_url)).json(&body))
+            .await?;
+
+        Ok(RefundReceipt {
+            refund_id: id_string(&data.id),
+            status: match data.status.as_str() {
+                "processed" => RefundStatus::Processed,
+                "failed" => RefundStatus::Failed,
+                _ => RefundStatus::Pending,
+            },
+        })
+    }
+
+    // Paystack signs the raw body with HMAC-SHA512 keyed by the secret key
+    fn verify_signature(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        let Some(signature) = headers
+            .get("x-paystack-signature")
+            .and_then(|v| v.to_str().ok())
+        else {
+            return false;
+        };
+
+        let mut mac = Hmac::<Sha512>::new_from_slice(self.secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(body);
+        hex::decode(signature).is_ok_and(|sig| mac.verify_slice(&sig).is_ok())
+    }
+
+    fn parse_event(&self, body: &[u8]) -> Result<ParsedWebhookEvent, AppError> {
+        let payload: Value = serde_json::from_slice(body).map_err(|e| AppError::ParsingError(e.to_string()))?;
+        let event_type = payload["event"].as_str().unwrap_or_default().to_string();
+        let data = &payload["data"];
+        let is_refund = event_type.starts_with("refund.");
+        // transfers into a dedicated virtual account arrive as ordinary charges
+        let is_transfer = event_type == "charge.success" && data["channel"].as_str() == Some("dedicated_nuban");
+        // refund events name the refunded transaction's reference differently
+        let reference = match is_refund {
+            true => data["transaction_reference"].as_str(),
+            false => data["reference"].as_str(),
+        }
+        .map(str::to_string);
+        let event_id = match &data["id"] {
+            Value::Null => reference.clone().unwrap_or_default(),
+            id => id_string(id),
+        };
+
+        Ok(ParsedWebhookEvent {
+            event_key: format!("{}:{}", event_type, event_id),
+            kind: match event_type.as_str() {
+                "charge.success" if is_transfer => WebhookEventKind::TransferReceived,
+                "charge.success" => WebhookEventKind::ChargeSuccess,
+                "refund.processed" => WebhookEventKind::RefundProcessed,
+                "refund.failed" => WebhookEventKind::RefundFailed,
+                _ => WebhookEventKind::Other,
+            },
+            event_type,
+            reference,
+            refund_id: is_refund.then_some(event_id),
+            // refund amounts come through as strings
+            amount_kobo: data["amount"]
+                .as_u64()
+                .or_else(|| data["amount"].as_str().and_then(|a| a.parse().ok())),
+            currency: data["currency"].as_str().map(str::to_string),
+            account_number: data["authorization"]["receiver_bank_account_number"]
+                .as_str()
+                .filter(|_| is_transfer)
+                .map(str::to_string),
+            payload,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"event":"charge.success","data":{"id":1,"reference":"sch-1"}}"#;
+
+    fn signed(secret: &str, body: &[u8]) -> HeaderMap {
+        let mut mac = Hmac::<Sha512>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-paystack-signature", hex::encode(mac.finalize().into_bytes()).parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn accepts_a_body_signed_with_the_secret_key() {
+        let provider = PaystackProvider::new("sk_test".to_string());
+        assert!(provider.verify_signature(&signed("sk_test", BODY), BODY));
+    }
+
+    #[test]
+    fn rejects_another_key_a_changed_body_or_no_signature() {
+        let provider = PaystackProvider::new("sk_test".to_string());
+        assert!(!provider.verify_signature(&signed("sk_other", BODY), BODY));
+        assert!(!provider.verify_signature(&signed("sk_test", BODY), br#"{"event":"charge.success"}"#));
+        assert!(!provider.verify_signature(&HeaderMap::new(), BODY));
+
+        let mut garbled = HeaderMap::new();
+        garbled.insert("x-paystack-signature", "not-hex".parse().unwrap());
+        assert!(!provider.verify_signature(&garbled, BODY));
+    }
+}
@@ -0,0 +1,306 @@
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::{
+    errors::AppError,
+    models::{
+        ledger::TransactionStatus,
+        payment_settings::PaymentProviderKind,
+        refunds::RefundStatus,
+        webhooks::{ParsedWebhookEvent, WebhookEventKind},
+    },
+};
+
+use super::{Checkout, CheckoutRequest, PaymentProvider, RefundReceipt, VerifiedTransaction, base_url, gateway_error};
+
+// how old a signed webhook may be before it's treated as a replay
+const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+pub struct StripeProvider {
+    client: reqwest::Client,
+    secret_key: String,
+    signing_secret: String, // the endpoint's "whsec_..." secret
+    base_url: String,
+}
+
+impl StripeProvider {
+    pub fn new(secret_key: String, signing_secret: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            secret_key,
+            signing_secret,
+            base_url: base_url("STRIPE_BASE_URL", "https://api.stripe.com"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StripeError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error: StripeError,
+}
+
+#[derive(Deserialize)]
+struct CheckoutSession {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct PaymentIntent {
+    id: String,
+    status: String, // "succeeded", "processing", "canceled", "requires_payment_method", ...
+    amount: u64,
+    amount_received: u64,
+    currency: String,
+}
+
+#[derive(Deserialize)]
+struct SearchResult<T> {
+    data: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct Refund {
+    id: String,
+    status: String,
+}
+
+impl StripeProvider {
+    async fn read<T: for<'de> Deserialize<'de>>(&self, request: reqwest::RequestBuilder) -> Result<T, AppError> {
+        let response = request
+            .bearer_auth(&self.secret_key)
+            .send()
+            .await
+            .map_err(|e| gateway_error("Stripe", e))?;
+
+        if !response.status().is_success() {
+            let message = match response.json::<ErrorEnvelope>().await {
+                Ok(envelope) => envelope.error.message,
+                Err(e) => e.to_string(),
+            };
+            return Err(gateway_error("Stripe", message));
+        }
+        response.json::<T>().await.map_err(|e| gateway_error("Stripe", e))
+    }
+
+    // our reference is stored in the payment intent's metadata at checkout
+    async fn find_payment_intent(&self, reference: &str) -> Result<Option<PaymentIntent>, AppError> {
+        let query = format!("metadata['reference']:'{}'", reference);
+        let result: SearchResult<PaymentIntent> = self
+            .read(
+                self.client
+                    .get(format!("{}/v1/payment_intents/search", self.base_url))
+                    .query(&[("query", query)]),
+            )
+            .await?;
+        Ok(result.data.into_iter().next())
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for StripeProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::Stripe
+    }
+
+    async fn initialize(&self, checkout: &CheckoutRequest) -> Result<Checkout, AppError> {
+        let return_url = checkout
+            .callback_url
+            .clone()
+            .ok_or_else(|| gateway_error("Stripe", "a callback URL is required (set PAYMENT_CALLBACK_URL)"))?;
+        let form = [
+            ("mode", "payment".to_string()),
+            ("customer_email", checkout.email.clone()),
+            ("client_reference_id", checkout.reference.clone()),
+            ("success_url", return_url.clone()),
+            ("cancel_url", return_url),
+            ("line_items[0][quantity]", "1".to_string()),
+            ("line_items[0][price_data][currency]", checkout.amount.currency.code().to_lowercase()),
+            ("line_items[0][price_data][unit_amount]", checkout.amount.minor_units().to_string()),
+            ("line_items[0][price_data][product_data][name]", "School fees".to_string()),
+            ("payment_intent_data[metadata][reference]", checkout.reference.clone()),
+        ];
+        let session: CheckoutSession = self
+            .read(self.client.post(format!("{}/v1/checkout/sessions", self.base_url)).form(&form))
+            .await?;
+
+        Ok(Checkout {
+            authorization_url: session.url,
+            reference: checkout.reference.clone(),
+        })
+    }
+
+    async fn verify(&self, reference: &str) -> Result<VerifiedTransaction, AppError> {
+        // no payment intent yet means the payer never got past the checkout page
+        let Some(intent) = self.find_payment_intent(reference).await? else {
+            return Ok(VerifiedTransaction {
+                reference: reference.to_string(),
+                status: TransactionStatus::Pending,
+                amount_kobo: 0,
+                currency: String::new(),
+            });
+        };
+
+        let status = match intent.status.as_str() {
+            "succeeded" => TransactionStatus::Successful,
+            "canceled" => TransactionStatus::Failed,
+            _ => TransactionStatus::Pending,
+        };
+        Ok(VerifiedTransaction {
+            reference: reference.to_string(),
+            amount_kobo: if status == TransactionStatus::Successful { intent.amount_received } else { intent.amount },
+            currency: intent.currency.to_uppercase(),
+            status,
+        })
+    }
+
+    async fn refund(&self, reference: &str, amount_kobo: Option<u64>) -> Result<RefundReceipt, AppError> {
+        let intent = self
+            .find_payment_intent(reference)
+            .await?
+            .ok_or_else(|| gateway_error("Stripe", "no payment found for this reference"))?;
+
+        let mut form = vec![
+            ("payment_intent", intent.id),
+            ("metadata[reference]", reference.to_string()),
+        ];
+        if let Some(amount) = amount_kobo {
+            form.push(("amount", amount.to_string()));
+        }
+        let refund: Refund = self
+            .read(self.client.post(format!("{}/v1/refunds", self.base_url)).form(&form))
+            .await?;
+
+        Ok(RefundReceipt {
+            refund_id: refund.id,
+            status: match refund.status.as_str() {
+                "succeeded" => RefundStatus::Processed,
+                "failed" | "canceled" => RefundStatus::Failed,
+                _ => RefundStatus::Pending,
+            },
+        })
+    }
+
+    // Stripe-Signature is "t=<timestamp>,v1=<hex hmac-sha256 of "t.body">"
+    fn verify_signature(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        let Some(header) = headers.get("stripe-signature").and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            match part.split_once('=') {
+                Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                Some(("v1", sig)) => signatures.push(sig),
+                _ => {}
+            }
+        }
+        let Some(timestamp) = timestamp else {
+            return false;
+        };
+        if (Utc::now().timestamp() - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+            return false;
+        }
+
+        signatures.iter().any(|sig| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(self.signing_secret.as_bytes())
+                .expect("HMAC can take key of any size");
+            mac.update(timestamp.to_string().as_bytes());
+            mac.update(b".");
+            mac.update(body);
+            hex::decode(sig).is_ok_and(|sig| mac.verify_slice(&sig).is_ok())
+        })
+    }
+
+    fn parse_event(&self, body: &[u8]) -> Result<ParsedWebhookEvent, AppError> {
+        let payload: Value = serde_json::from_slice(body).map_err(|e| AppError::ParsingError(e.to_string()))?;
+        let event_type = payload["type"].as_str().unwrap_or_default().to_string();
+        let object = &payload["data"]["object"];
+
+        let is_refund = event_type.starts_with("refund.");
+
+        Ok(ParsedWebhookEvent {
+            event_key: payload["id"].as_str().unwrap_or_default().to_string(),
+            kind: match (event_type.as_str(), object["status"].as_str().unwrap_or_default()) {
+                ("payment_intent.succeeded", _) => WebhookEventKind::ChargeSuccess,
+                ("payment_intent.payment_failed", _) => WebhookEventKind::ChargeFailed,
+                ("refund.updated", "succeeded") => WebhookEventKind::RefundProcessed,
+                ("refund.updated", "failed" | "canceled") => WebhookEventKind::RefundFailed,
+                _ => WebhookEventKind::Other,
+            },
+            event_type,
+            // both payment intents and refunds carry our reference in their metadata
+            reference: object["metadata"]["reference"].as_str().map(str::to_string),
+            refund_id: is_refund.then(|| object["id"].as_str().unwrap_or_default().to_string()),
+            amount_kobo: match is_refund {
+                true => object["amount"].as_u64(),
+                false => object["amount_received"].as_u64(),
+            },
+            currency: object["currency"].as_str().map(str::to_uppercase),
+            account_number: None,
+            payload,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"id":"evt_1","type":"payment_intent.succeeded"}"#;
+
+    fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("stripe-signature", value.parse().unwrap());
+        headers
+    }
+
+    fn provider() -> StripeProvider {
+        StripeProvider::new("sk_test".to_string(), "whsec_test".to_string())
+    }
+
+    #[test]
+    fn accepts_a_fresh_signature_among_several() {
+        let now = Utc::now().timestamp();
+        let header = format!("t={},v1={},v1={}", now, "00".repeat(32), signature("whsec_test", now, BODY));
+        assert!(provider().verify_signature(&headers(&header), BODY));
+    }
+
+    #[test]
+    fn rejects_a_wrong_secret_or_changed_body() {
+        let now = Utc::now().timestamp();
+        let wrong_secret = format!("t={},v1={}", now, signature("whsec_other", now, BODY));
+        assert!(!provider().verify_signature(&headers(&wrong_secret), BODY));
+
+        let valid = format!("t={},v1={}", now, signature("whsec_test", now, BODY));
+        assert!(!provider().verify_signature(&headers(&valid), br#"{"id":"evt_2"}"#));
+    }
+
+    #[test]
+    fn rejects_an_old_timestamp_or_a_missing_one() {
+        let old = Utc::now().timestamp() - SIGNATURE_TOLERANCE_SECS - 60;
+        let stale = format!("t={},v1={}", old, signature("whsec_test", old, BODY));
+        assert!(!provider().verify_signature(&headers(&stale), BODY));
+
+        let untimed = format!("v1={}", signature("whsec_test", 0, BODY));
+        assert!(!provider().verify_signature(&headers(&untimed), BODY));
+        assert!(!provider().verify_signature(&HeaderMap::new(), BODY));
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use roxmltree::{Document, Node};
+
+use crate::errors::AppError;
+
+use super::{Direction, ParsedLine, parse_amount};
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+// follows a path of element names below `node`, e.g. ["BookgDt", "Dt"]
+fn path<'a, 'input>(node: Node<'a, 'input>, names: &[&str]) -> Option<Node<'a, 'input>> {
+    names.iter().try_fold(node, |node, name| child(node, name))
+}
+
+fn text(node: Option<Node>) -> Option<String> {
+    node.and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+fn entry_date(entry: Node) -> Option<NaiveDate> {
+    [["BookgDt", "Dt"], ["BookgDt", "DtTm"], ["ValDt", "Dt"], ["ValDt", "DtTm"]]
+        .iter()
+        .find_map(|p| text(path(entry, p)))
+        .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok())
+}
+
+/// Reads an ISO 20022 CAMT.053 statement: one line per `Ntry`, with the
+/// narration taken from the remittance information and the currency from
+/// the amount, or failing that the account.
+pub fn parse(xml: &str) -> Result<Vec<ParsedLine>, AppError> {
+    let doc = Document::parse(xml).map_err(|e| AppError::ParsingError(e.to_string()))?;
+
+    let mut lines = Vec::new();
+    for entry in doc.descendants().filter(|n| n.has_tag_name("Ntry")) {
+        let amount_kobo = text(child(entry, "Amt"))
+            .and_then(|a| parse_amount(&a))
+            .ok_or_else(|| AppError::invalid("file", "An entry has no amount"))?;
+        let currency = child(entry, "Amt")
+            .and_then(|a| a.attribute("Ccy").map(str::to_string))
+            .or_else(|| {
+                let statement = entry.ancestors().find(|n| n.has_tag_name("Stmt"))?;
+                text(path(statement, &["Acct", "Ccy"]))
+            })
+            .map(|c| c.to_uppercase());
+        let direction = match text(child(entry, "CdtDbtInd")).as_deref() {
+            Some("CRDT") => Direction::Credit,
+            Some("DBIT") => Direction::Debit,
+            _ => return Err(AppError::invalid("file", "An entry is neither a credit nor a debit")),
+        };
+        let date = entry_date(entry).ok_or_else(|| AppError::invalid("file", "An entry has no booking date"))?;
+
+        let details = path(entry, &["NtryDtls", "TxDtls"]);
+        let mut narration: Vec<String> = details
+            .into_iter()
+            .flat_map(|d| d.descendants().filter(|n| n.has_tag_name("Ustrd")))
+            .filter_map(|n| text(Some(n)))
+            .collect();
+        if let Some(info) = text(child(entry, "AddtlNtryInf")) {
+            narration.push(info);
+        }
+        if let Some(debtor) = details.and_then(|d| text(path(d, &["RltdPties", "Dbtr", "Nm"]))) {
+            narration.push(debtor);
+        }
+        let reference = details
+            .and_then(|d| {
+                text(path(d, &["Refs", "EndToEndId"]))
+                    .filter(|r| r != "NOTPROVIDED")
+                    .or_else(|| text(path(d, &["Refs", "AcctSvcrRef"])))
+            })
+            .or_else(|| text(child(entry, "AcctSvcrRef")));
+
+        lines.push(ParsedLine {
+            date,
+            direction,
+            amount_kobo,
+            narration: narration.join(" "),
+            reference,
+            currency,
+        });
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATEMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Acct><Ccy>GHS</Ccy></Acct>
+      <Ntry>
+        <Amt Ccy="ngn">50000.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <BookgDt><Dt>2026-10-02</Dt></BookgDt>
+        <NtryDtls>
+          <TxDtls>
+            <Refs><EndToEndId>SCH-123</EndToEndId></Refs>
+            <RltdPties><Dbtr><Nm>Mrs Obi</Nm></Dbtr></RltdPties>
+            <RmtInf><Ustrd>ADA OBI FEES</Ustrd></RmtInf>
+          </TxDtls>
+        </NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt>25.50</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <ValDt><DtTm>2026-10-03T10:00:00</DtTm></ValDt>
+        <AcctSvcrRef>BANK-9</AcctSvcrRef>
+        <AddtlNtryInf>CHARGES</AddtlNtryInf>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn reads_entries() {
+        let lines = parse(STATEMENT).unwrap();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].date, NaiveDate::from_ymd_opt(2026, 10, 2).unwrap());
+        assert_eq!(lines[0].direction, Direction::Credit);
+        assert_eq!(lines[0].amount_kobo, 5000000);
+        assert_eq!(lines[0].narration, "ADA OBI FEES Mrs Obi");
+        assert_eq!(lines[0].reference.as_deref(), Some("SCH-123"));
+
+        assert_eq!(lines[1].date, NaiveDate::from_ymd_opt(2026, 10, 3).unwrap());
+        assert_eq!(lines[1].direction, Direction::Debit);
+        assert_eq!(lines[1].amount_kobo, 2550);
+        assert_eq!(lines[1].narration, "CHARGES");
+        assert_eq!(lines[1].reference.as_deref(), Some("BANK-9"));
+    }
+
+    #[test]
+    fn takes_the_currency_from_the_amount_then_the_account() {
+        let lines = parse(STATEMENT).unwrap();
+        assert_eq!(lines[0].currency.as_deref(), Some("NGN"));
+        assert_eq!(lines[1].currency.as_deref(), Some("GHS"));
+    }
+
+    #[test]
+    fn rejects_entries_it_cannot_place() {
+        let no_direction = STATEMENT.replace("<CdtDbtInd>DBIT</CdtDbtInd>", "");
+        assert!(parse(&no_direction).is_err());
+        assert!(parse("<Document><Ntry>").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::NaiveDate;
+
+use crate::errors::AppError;
+
+use super::{Direction, ParsedLine, parse_amount};
+
+const DATE_HEADERS: [&str; 5] = ["date", "transaction date", "trans date", "value date", "posting date"];
+const CREDIT_HEADERS: [&str; 4] = ["credit", "credit amount", "deposit", "money in"];
+const DEBIT_HEADERS: [&str; 4] = ["debit", "debit amount", "withdrawal", "money out"];
+const AMOUNT_HEADERS: [&str; 2] = ["amount", "transaction amount"];
+const NARRATION_HEADERS: [&str; 5] = ["narration", "description", "details", "remarks", "particulars"];
+const REFERENCE_HEADERS: [&str; 4] = ["reference", "ref", "transaction reference", "reference number"];
+const CURRENCY_HEADERS: [&str; 2] = ["currency", "ccy"];
+const DATE_FORMATS: [&str; 6] = ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y", "%d-%b-%Y", "%d %b %Y", "%d-%b-%y"];
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    // some banks add the time after the date
+    let value = value.trim().split([' ', 'T']).next().unwrap_or_default();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .or_else(|| {
+            let full = value.trim();
+            DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(full, format).ok())
+        })
+}
+
+/// Reads a bank's CSV export. Columns are found by their header, so the
+/// usual layouts work: separate credit and debit columns, or one signed
+/// amount column.
+pub fn parse(bytes: &[u8]) -> Result<Vec<ParsedLine>, AppError> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(bytes);
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::ParsingError(e.to_string()))?
+        .iter()
+        .map(|h| h.trim().trim_start_matches('\u{feff}').to_lowercase())
+        .collect();
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+
+    let date = column(&DATE_HEADERS).ok_or_else(|| AppError::invalid("file", "The statement has no date column"))?;
+    let credit = column(&CREDIT_HEADERS);
+    let debit = column(&DEBIT_HEADERS);
+    let amount = column(&AMOUNT_HEADERS);
+    if credit.is_none() && amount.is_none() {
+        return Err(AppError::invalid("file", "The statement needs a credit or an amount column"));
+    }
+    let narration = column(&NARRATION_HEADERS);
+    let reference = column(&REFERENCE_HEADERS);
+    let currency = column(&CURRENCY_HEADERS);
+
+    let mut lines = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| AppError::ParsingError(e.to_string()))?;
+        let cell = |i: Option<usize>| i.and_then(|i| record.get(i)).map(str::trim).unwrap_or_default();
+        if record.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        let row = index + 2; // counting the header, as a spreadsheet would
+
+        let date = parse_date(cell(Some(date)))
+            .ok_or_else(|| AppError::invalid("file", &format!("Row {} has an unreadable date", row)))?;
+        let (direction, amount_kobo) = match (parse_amount(cell(credit)), parse_amount(cell(debit))) {
+            (Some(kobo), _) if kobo > 0 => (Direction::Credit, kobo),
+            (_, Some(kobo)) if kobo > 0 => (Direction::Debit, kobo),
+            _ => {
+                let value = cell(amount);
+                let kobo = parse_amount(value)
+                    .ok_or_else(|| AppError::invalid("file", &format!("Row {} has no amount", row)))?;
+                let direction = if value.starts_with('-') || value.ends_with("DR") {
+                    Direction::Debit
+                } else {
+                    Direction::Credit
+                };
+                (direction, kobo)
+            }
+        };
+
+        let reference = cell(reference);
+        let currency = cell(currency);
+        lines.push(ParsedLine {
+            date,
+            direction,
+            amount_kobo,
+            narration: cell(narration).to_string(),
+            reference: (!reference.is_empty()).then(|| reference.to_string()),
+            currency: (!currency.is_empty()).then(|| currency.to_uppercase()),
+        });
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn reads_separate_credit_and_debit_columns() {
+        let csv = "Date,Narration,Credit,Debit,Reference,Currency\n\
+                   2026-10-02,ADA OBI FEES,\"50,000.00\",,SCH-123,ngn\n\
+                   ,,,,,\n\
+                   03/10/2026,CHARGES,,25.50,,\n";
+        let lines = parse(csv.as_bytes()).unwrap();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].date, date(2026, 10, 2));
+        assert_eq!(lines[0].direction, Direction::Credit);
+        assert_eq!(lines[0].amount_kobo, 5000000);
+        assert_eq!(lines[0].narration, "ADA OBI FEES");
+        assert_eq!(lines[0].reference.as_deref(), Some("SCH-123"));
+        assert_eq!(lines[0].currency.as_deref(), Some("NGN"));
+
+        assert_eq!(lines[1].date, date(2026, 10, 3));
+        assert_eq!(lines[1].direction, Direction::Debit);
+        assert_eq!(lines[1].amount_kobo, 2550);
+        assert_eq!(lines[1].reference, None);
+        assert_eq!(lines[1].currency, None);
+    }
+
+    #[test]
+    fn reads_a_signed_amount_column() {
+        let csv = "\u{feff}Transaction Date,Description,Amount\n\
+                   03-Oct-2026 10:15,Transfer in,1500\n\
+                   04-Oct-2026,Bank fee,-200.00\n\
+                   05-Oct-2026,Reversal,75.00 DR\n";
+        let lines = parse(csv.as_bytes()).unwrap();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].date, date(2026, 10, 3));
+        assert_eq!((lines[0].direction, lines[0].amount_kobo), (Direction::Credit, 150000));
+        assert_eq!((lines[1].direction, lines[1].amount_kobo), (Direction::Debit, 20000));
+        assert_eq!((lines[2].direction, lines[2].amount_kobo), (Direction::Debit, 7500));
+    }
+
+    #[test]
+    fn needs_a_date_and_an_amount_column() {
+        assert!(parse(b"Narration,Credit\nFees,100\n").is_err());
+        assert!(parse(b"Date,Narration\n2026-10-02,Fees\n").is_err());
+    }
+
+    #[test]
+    fn rejects_an_unreadable_date() {
+        assert!(parse(b"Date,Credit\nyesterday,100\n").is_err());
+    }
+}
@@ -0,0 +1,137 @@
+pub mod camt053;
+pub mod delimited;
+pub mod mt940;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppError;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    Csv,
+    Mt940,
+    Camt053,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Direction {
+    Credit, // money into the school's account
+    Debit,
+}
+
+/// One transaction as the bank reported it, whatever the file format.
+pub struct ParsedLine {
+    pub date: NaiveDate,
+    pub direction: Direction,
+    pub amount_kobo: u64,
+    pub narration: String,
+    pub reference: Option<String>,
+    pub currency: Option<String>, // ISO code, when the statement gives one
+}
+
+impl StatementFormat {
+    /// Guesses the format from the file itself: CAMT is XML, MT940 is made
+    /// of `:tag:` fields, anything else is treated as CSV.
+    pub fn detect(bytes: &[u8]) -> StatementFormat {
+        let text = String::from_utf8_lossy(bytes);
+        let start = text.trim_start_matches('\u{feff}').trim_start();
+        if start.starts_with('<') {
+            StatementFormat::Camt053
+        } else if text.contains(":61:") {
+            StatementFormat::Mt940
+        } else {
+            StatementFormat::Csv
+        }
+    }
+}
+
+pub fn parse(format: StatementFormat, bytes: &[u8]) -> Result<Vec<ParsedLine>, AppError> {
+    let lines = match format {
+        StatementFormat::Csv => delimited::parse(bytes)?,
+        StatementFormat::Mt940 => mt940::parse(&String::from_utf8_lossy(bytes))?,
+        StatementFormat::Camt053 => camt053::parse(&String::from_utf8_lossy(bytes))?,
+    };
+    if lines.is_empty() {
+        return Err(AppError::invalid("file", "The statement has no transactions"));
+    }
+    Ok(lines)
+}
+
+/// e.g. "1,234.50" or "1234,50" -> 123450. Banks write decimals both ways;
+/// a comma followed by exactly two digits at the end is taken as the
+/// decimal point.
+pub fn parse_amount(value: &str) -> Option<u64> {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .collect();
+    let decimal_comma = cleaned.rfind(',').is_some_and(|i| cleaned.len() - i <= 3 && !cleaned.contains('.'));
+    let normalized = if decimal_comma {
+        cleaned.replace(',', ".")
+    } else {
+        cleaned.replace(',', "")
+    };
+
+    let (major, minor) = normalized.split_once('.').unwrap_or((&normalized, ""));
+    if major.is_empty() && minor.is_empty() {
+        return None;
+    }
+    let major: u64 = if major.is_empty() { 0 } else { major.parse().ok()? };
+    let minor: u64 = match minor.len() {
+        0 => 0,
+        1 => minor.parse::<u64>().ok()? * 10,
+        2 => minor.parse().ok()?,
+        _ => return None,
+    };
+    major.checked_mul(100)?.checked_add(minor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_amount_reads_both_decimal_styles() {
+        assert_eq!(parse_amount("1,234.50"), Some(123450));
+        assert_eq!(parse_amount("1234,50"), Some(123450));
+        assert_eq!(parse_amount("12,5"), Some(1250));
+        assert_eq!(parse_amount("1234.5"), Some(123450));
+    }
+
+    #[test]
+    fn parse_amount_treats_a_comma_before_three_digits_as_thousands() {
+        assert_eq!(parse_amount("1,234"), Some(123400));
+        assert_eq!(parse_amount("1,234,567"), Some(123456700));
+    }
+
+    #[test]
+    fn parse_amount_ignores_currency_and_sign() {
+        assert_eq!(parse_amount("NGN 1,000.05"), Some(100005));
+        assert_eq!(parse_amount("-200.00"), Some(20000));
+        assert_eq!(parse_amount(" .75 "), Some(75));
+    }
+
+    #[test]
+    fn parse_amount_rejects_what_it_cannot_read() {
+        assert_eq!(parse_amount(""), None);
+        assert_eq!(parse_amount("n/a"), None);
+        assert_eq!(parse_amount("1.234"), None); // three decimals
+        assert_eq!(parse_amount("1.234.567"), None);
+        assert_eq!(parse_amount("999999999999999999999"), None);
+    }
+
+    #[test]
+    fn detect_tells_the_formats_apart() {
+        assert_eq!(StatementFormat::detect("\u{feff}<?xml version=\"1.0\"?><Document/>".as_bytes()), StatementFormat::Camt053);
+        assert_eq!(StatementFormat::detect(b":20:STMT\r\n:61:261002C1,00NTRFX\r\n"), StatementFormat::Mt940);
+        assert_eq!(StatementFormat::detect(b"Date,Amount\n2026-10-02,100\n"), StatementFormat::Csv);
+    }
+
+    #[test]
+    fn parse_rejects_a_statement_without_transactions() {
+        assert!(parse(StatementFormat::Csv, b"Date,Narration,Credit\n").is_err());
+    }
+}
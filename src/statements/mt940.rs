@@ -0,0 +1,158 @@
+use chrono::NaiveDate;
+
+use crate::errors::AppError;
+
+use super::{Direction, ParsedLine, parse_amount};
+
+// ":61:" holds the booking itself; the ":86:" after it the free-text narrative
+fn parse_statement_line(value: &str) -> Option<ParsedLine> {
+    let date = NaiveDate::parse_from_str(value.get(..6)?, "%y%m%d").ok()?;
+    let mut rest = &value[6..];
+    // optional four-digit entry date
+    if rest.get(..4).is_some_and(|d| d.chars().all(|c| c.is_ascii_digit())) {
+        rest = &rest[4..];
+    }
+
+    let (direction, skip) = if rest.starts_with("RC") {
+        (Direction::Debit, 2) // reversal of a credit
+    } else if rest.starts_with("RD") {
+        (Direction::Credit, 2)
+    } else if rest.starts_with('C') {
+        (Direction::Credit, 1)
+    } else if rest.starts_with('D') {
+        (Direction::Debit, 1)
+    } else {
+        return None;
+    };
+    rest = &rest[skip..];
+    // optional funds code: the last letter of the currency code
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..];
+    }
+
+    let amount_end = rest.find(|c: char| !c.is_ascii_digit() && c != ',').unwrap_or(rest.len());
+    let amount_kobo = parse_amount(&rest[..amount_end])?;
+    rest = &rest[amount_end..];
+
+    // transaction type (e.g. "NTRF") then our reference, then the bank's after "//"
+    let references = rest.get(4..).unwrap_or_default();
+    let (customer, bank) = references.split_once("//").unwrap_or((references, ""));
+    let customer = customer.lines().next().unwrap_or_default().trim();
+    let reference = if !customer.is_empty() && customer != "NONREF" {
+        Some(customer.to_string())
+    } else {
+        let bank = bank.lines().next().unwrap_or_default().trim();
+        (!bank.is_empty()).then(|| bank.to_string())
+    };
+
+    Some(ParsedLine {
+        date,
+        direction,
+        amount_kobo,
+        narration: String::new(),
+        reference,
+        currency: None, // the :61: line only carries the last letter of it
+    })
+}
+
+/// Reads a SWIFT MT940 statement. Continuation lines are folded into the
+/// field above them; each booking takes its currency from the opening
+/// balance (`:60F:` or `:60M:`) of the statement it belongs to.
+pub fn parse(text: &str) -> Result<Vec<ParsedLine>, AppError> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end();
+        let tag = line
+            .strip_prefix(':')
+            .and_then(|rest| rest.split_once(':'))
+            .filter(|(tag, _)| tag.len() <= 3 && tag.chars().all(|c| c.is_ascii_alphanumeric()));
+        match tag {
+            Some((tag, value)) => fields.push((tag.to_string(), value.to_string())),
+            None => {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+
+    let mut lines: Vec<ParsedLine> = Vec::new();
+    let mut currency: Option<String> = None;
+    for (tag, value) in fields {
+        match tag.as_str() {
+            // "C261001NGN1000,00": credit or debit mark, date, currency, amount
+            "60F" | "60M" => {
+                currency = value
+                    .get(7..10)
+                    .filter(|c| c.chars().all(|c| c.is_ascii_alphabetic()))
+                    .map(str::to_uppercase);
+            }
+            "61" => {
+                let mut line = parse_statement_line(&value).ok_or_else(|| {
+                    AppError::invalid("file", &format!("Unreadable :61: line {}", value.lines().next().unwrap_or_default()))
+                })?;
+                line.currency = currency.clone();
+                lines.push(line);
+            }
+            "86" => {
+                if let Some(line) = lines.last_mut() {
+                    line.narration = value.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATEMENT: &str = ":20:STMT1\r\n\
+                             :25:0123456789\r\n\
+                             :28C:1/1\r\n\
+                             :60F:C261001NGN1000,00\r\n\
+                             :61:2610021002C50000,00NTRFSCH-123//BANKREF1\r\n\
+                             :86:ADA OBI FEES\r\n\
+                             \x20JSS1A\r\n\
+                             :61:261003D2500,5NTRFNONREF//BANKREF2\r\n\
+                             :86:CHARGES\r\n\
+                             :62F:C261003NGN48500,50\r\n";
+
+    #[test]
+    fn reads_bookings_with_their_narrative_and_currency() {
+        let lines = parse(STATEMENT).unwrap();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].date, NaiveDate::from_ymd_opt(2026, 10, 2).unwrap());
+        assert_eq!(lines[0].direction, Direction::Credit);
+        assert_eq!(lines[0].amount_kobo, 5000000);
+        assert_eq!(lines[0].reference.as_deref(), Some("SCH-123"));
+        assert_eq!(lines[0].narration, "ADA OBI FEES JSS1A");
+        assert_eq!(lines[0].currency.as_deref(), Some("NGN"));
+
+        assert_eq!(lines[1].direction, Direction::Debit);
+        assert_eq!(lines[1].amount_kobo, 250050);
+        assert_eq!(lines[1].reference.as_deref(), Some("BANKREF2")); // NONREF falls back to the bank's
+        assert_eq!(lines[1].narration, "CHARGES");
+    }
+
+    #[test]
+    fn reads_reversals_and_funds_codes() {
+        let reversal = parse_statement_line("261004RC100,00NTRFSCH-9").unwrap();
+        assert_eq!(reversal.direction, Direction::Debit);
+        assert_eq!(reversal.amount_kobo, 10000);
+
+        let with_funds_code = parse_statement_line("261004CN7,25NTRFSCH-10").unwrap();
+        assert_eq!(with_funds_code.direction, Direction::Credit);
+        assert_eq!(with_funds_code.amount_kobo, 725);
+        assert_eq!(with_funds_code.reference.as_deref(), Some("SCH-10"));
+    }
+
+    #[test]
+    fn rejects_an_unreadable_booking() {
+        assert!(parse(":60F:C261001NGN0,00\r\n:61:26100X\r\n").is_err());
+    }
+}
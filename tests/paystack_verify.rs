@@ -0,0 +1,224 @@
+//! Runs the server against a mock Paystack to check that payments are
+//! settled from the verify endpoint, both on demand and by the background
+//! reconciliation job.
+
+use std::{
+    net::TcpListener,
+    process::{Child, Command},
+    time::Duration,
+};
+
+use chrono::{Duration as Days, Utc};
+use serde_json::{Value, json};
+use wiremock::{
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+    matchers::{method, path, path_regex},
+};
+
+struct TestServer {
+    child: Child,
+    base: String,
+    token: String,
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+/// Echoes the reference a transaction was initialized with, as Paystack does.
+struct InitializeResponder;
+
+impl Respond for InitializeResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+        ResponseTemplate::new(200).set_body_json(json!({
+            "status": true,
+            "message": "Authorization URL created",
+            "data": {
+                "authorization_url": "https://checkout.paystack.test/abc",
+                "access_code": "abc",
+                "reference": body["reference"],
+            }
+        }))
+    }
+}
+
+/// Replies to /transaction/verify/{reference} with the given outcome.
+struct VerifyResponder {
+    status: &'static str,
+    amount: u64,
+}
+
+impl Respond for VerifyResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let reference = request.url.path().rsplit('/').next().unwrap_or_default();
+        ResponseTemplate::new(200).set_body_json(json!({
+            "status": true,
+            "message": "Verification successful",
+            "data": {
+                "status": self.status,
+                "reference": reference,
+                "amount": self.amount,
+                "currency": "NGN",
+            }
+        }))
+    }
+}
+
+async fn mock_paystack(verify: VerifyResponder) -> MockServer {
+    let paystack = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/transaction/initialize"))
+        .respond_with(InitializeResponder)
+        .mount(&paystack)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex("^/transaction/verify/.+$"))
+        .respond_with(verify)
+        .mount(&paystack)
+        .await;
+    paystack
+}
+
+async fn start_server(paystack: &MockServer, extra_env: &[(&str, &str)]) -> TestServer {
+    let port = TcpListener::bind("[::1]:0").unwrap().local_addr().unwrap().port();
+    let child = Command::new(env!("CARGO_BIN_EXE_sch_mgt_sys"))
+        .env("PORT", port.to_string())
+        .env("JWT_SECRET", "test-secret")
+        .env("PAYSTACK_SECRET_KEY", "sk_test")
+        .env("PAYSTACK_BASE_URL", paystack.uri())
+        .envs(extra_env.iter().copied())
+        .spawn()
+        .expect("server binary should start");
+    let base = format!("http://[::1]:{}", port);
+    let client = reqwest::Client::new();
+
+    for _ in 0..50 {
+        if client.get(&base).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let credentials = json!({ "name": "Test School", "username": "test", "password": "pw" });
+    client.post(format!("{}/auth/register", base)).json(&credentials).send().await.unwrap();
+    let login: Value = client
+        .post(format!("{}/auth/login", base))
+        .json(&credentials)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    TestServer {
+        child,
+        base,
+        token: login["token"].as_str().unwrap().to_string(),
+    }
+}
+
+impl TestServer {
+    async fn post(&self, route: &str, body: Value) -> Value {
+        reqwest::Client::new()
+            .post(format!("{}{}", self.base, route))
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+
+    async fn get(&self, route: &str) -> Value {
+        reqwest::Client::new()
+            .get(format!("{}{}", self.base, route))
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+
+    /// Bills a student for a term running today and starts a payment,
+    /// returning the student id and the payment reference.
+    async fn start_payment(&self) -> (String, String) {
+        let today = Utc::now().date_naive();
+        self.post("/terms", json!({
+            "code": "T1",
+            "name": "First Term",
+            "starts_on": today - Days::days(7),
+            "ends_on": today + Days::days(60),
+        }))
+        .await;
+        self.post("/fees/items", json!({
+            "name": "Tuition",
+            "category": "Tuition",
+            "mandatory": true,
+            "amounts": [{ "amount_kobo": 5_000_000 }],
+        }))
+        .await;
+        let student = self
+            .post("/students", json!({
+                "first_name": "Ada",
+                "last_name": "Obi",
+                "email": "ada@example.com",
+                "department": "Science",
+                "class_name": "JSS1A",
+            }))
+            .await;
+        let student_id = student["id"].as_str().unwrap().to_string();
+
+        let payment = self.post(&format!("/students/{}/pay", student_id), json!({})).await;
+        let reference = payment["reference"].as_str().unwrap().to_string();
+        (student_id, reference)
+    }
+}
+
+#[tokio::test]
+async fn verify_endpoint_settles_a_pending_payment() {
+    let paystack = mock_paystack(VerifyResponder { status: "success", amount: 5_000_000 }).await;
+    let server = start_server(&paystack, &[]).await;
+    let (student_id, reference) = server.start_payment().await;
+
+    let verified = server.post(&format!("/payments/{}/verify", reference), json!({})).await;
+    assert_eq!(verified["status"], "Successful");
+    assert_eq!(verified["amount_kobo"], 5_000_000);
+
+    // verifying again must not credit the ledger twice
+    server.post(&format!("/payments/{}/verify", reference), json!({})).await;
+    let ledger = server.get(&format!("/students/{}/ledger", student_id)).await;
+    assert_eq!(ledger["balance_kobo"], 0);
+
+    let student = server.get(&format!("/students/{}", student_id)).await;
+    assert_eq!(student["status"], "Paid");
+}
+
+#[tokio::test]
+async fn reconciliation_fails_abandoned_payments() {
+    let paystack = mock_paystack(VerifyResponder { status: "abandoned", amount: 5_000_000 }).await;
+    let server = start_server(&paystack, &[
+        ("PAYMENT_RECONCILE_INTERVAL_SECS", "1"),
+        ("PAYMENT_PENDING_MINUTES", "0"),
+    ])
+    .await;
+    let (student_id, reference) = server.start_payment().await;
+
+    let mut status = Value::Null;
+    for _ in 0..30 {
+        let payments = server.get(&format!("/students/{}/payments", student_id)).await;
+        status = payments[0]["status"].clone();
+        if status != "Pending" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    assert_eq!(status, "Failed", "payment {} was not reconciled", reference);
+}